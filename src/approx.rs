@@ -0,0 +1,128 @@
+//! Rational approximation of floating-point numbers.
+
+use std::f64::consts;
+
+use num::rational::Ratio;
+
+#[cfg(test)]
+mod tests;
+
+/// Iterate over the continued fraction convergents of `x`, calling `f` with
+/// each one until it returns `false`.
+///
+/// Iteration also stops once a convergent equals `x` exactly or the next
+/// convergent would overflow an `i64`. The convergents are positive; the sign
+/// of `x` is ignored.
+fn convergents<F: FnMut(i64, i64, i64, i64) -> bool>(x: f64, mut f: F) {
+    let x = x.abs();
+    // (p0 / q0) and (p1 / q1) are the previous two convergents
+    let (mut p0, mut q0, mut p1, mut q1) = (0_i64, 1_i64, 1_i64, 0_i64);
+    let mut r = x;
+    loop {
+        let a = r.floor();
+        if a >= i64::MAX as f64 {
+            return;
+        }
+        let a = a as i64;
+        let p2 = match a.checked_mul(p1).and_then(|n| n.checked_add(p0)) {
+            Some(p) => p,
+            None => return,
+        };
+        let q2 = match a.checked_mul(q1).and_then(|n| n.checked_add(q0)) {
+            Some(q) => q,
+            None => return,
+        };
+        if !f(p0, q0, p2, q2) {
+            return;
+        }
+        p0 = p1;
+        q0 = q1;
+        p1 = p2;
+        q1 = q2;
+        let frac = r - r.floor();
+        if frac == 0.0 || p1 as f64 / q1 as f64 == x {
+            return;
+        }
+        r = frac.recip();
+    }
+}
+
+/// Find the best rational approximation of `x` whose denominator does not
+/// exceed `max_den`, using continued fractions.
+///
+/// Returns `None` if `x` is not finite, if it is too large to be represented,
+/// or if `max_den` is not positive.
+pub fn approximate(x: f64, max_den: i64) -> Option<Ratio<i64>> {
+    if !x.is_finite() || x.abs() >= i64::MAX as f64 || max_den < 1 {
+        return None;
+    }
+    let mut best = None;
+    convergents(x, |p0, q0, p2, q2| {
+        if q2 <= max_den {
+            best = Some((p2, q2));
+            return true;
+        }
+        // The next convergent's denominator is too large, but a
+        // semiconvergent between the last two convergents may still be
+        // closer than the last convergent.
+        if let Some((p1, q1)) = best {
+            let k = (max_den - q0) / q1;
+            let (ps, qs) = (p0 + k * p1, q0 + k * q1);
+            let err = |p: i64, q: i64| (p as f64 / q as f64 - x.abs()).abs();
+            if err(ps, qs) < err(p1, q1) {
+                best = Some((ps, qs));
+            }
+        }
+        false
+    });
+    best.map(|(p, q)| Ratio::new(p, q) * x.signum() as i64)
+}
+
+/// Find the first continued fraction convergent of `x` that lies within `tol`
+/// of `x`.
+///
+/// Returns `None` if `x` is not finite or if no convergent that fits in an
+/// `i64` is close enough.
+pub fn rationalize(x: f64, tol: f64) -> Option<Ratio<i64>> {
+    if !x.is_finite() || x.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    let mut found = None;
+    convergents(x, |_, _, p, q| {
+        if (p as f64 / q as f64 - x.abs()).abs() <= tol {
+            found = Some((p, q));
+            false
+        } else {
+            true
+        }
+    });
+    found.map(|(p, q)| Ratio::new(p, q) * x.signum() as i64)
+}
+
+/// Find a simple fraction that is practically equal to `x`, i.e., a fraction
+/// with a small denominator that differs from `x` only by rounding error.
+pub fn simple_fraction(x: f64) -> Option<Ratio<i64>> {
+    let r = approximate(x, 1000)?;
+    let err = (*r.numer() as f64 / *r.denom() as f64 - x).abs();
+    if err <= 1e-12 * x.abs().max(1.0) {
+        Some(r)
+    } else {
+        None
+    }
+}
+
+/// Find a simple rational multiple of pi that is practically equal to `x`.
+///
+/// The multiple `r` is returned such that `x` is approximately `r * pi`.
+pub fn pi_multiple(x: f64) -> Option<Ratio<i64>> {
+    let r = approximate(x / consts::PI, 12)?;
+    if r == Ratio::from_integer(0) || r.numer().abs() > 100 {
+        return None;
+    }
+    let err = (*r.numer() as f64 / *r.denom() as f64 * consts::PI - x).abs();
+    if err <= 1e-12 * x.abs().max(1.0) {
+        Some(r)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,59 @@
+use std::f64::consts;
+
+use num::rational::Ratio;
+
+use crate::{approximate, pi_multiple, rationalize, simple_fraction};
+
+fn r(n: i64, d: i64) -> Option<Ratio<i64>> {
+    Some(Ratio::new(n, d))
+}
+
+#[test]
+fn approximate_pi() {
+    assert_eq!(approximate(consts::PI, 1), r(3, 1));
+    assert_eq!(approximate(consts::PI, 7), r(22, 7));
+    assert_eq!(approximate(consts::PI, 100), r(311, 99));
+    assert_eq!(approximate(consts::PI, 113), r(355, 113));
+    assert_eq!(approximate(-consts::PI, 113), r(-355, 113));
+}
+
+#[test]
+fn approximate_exact() {
+    assert_eq!(approximate(0.75, 1000), r(3, 4));
+    assert_eq!(approximate(-2.5, 1000), r(-5, 2));
+    assert_eq!(approximate(0.1 + 0.2, 1000), r(3, 10));
+    assert_eq!(approximate(0.0, 1000), r(0, 1));
+}
+
+#[test]
+fn approximate_invalid() {
+    assert_eq!(approximate(f64::NAN, 10), None);
+    assert_eq!(approximate(f64::INFINITY, 10), None);
+    assert_eq!(approximate(1e300, 10), None);
+    assert_eq!(approximate(0.5, 0), None);
+}
+
+#[test]
+fn rationalize_tolerance() {
+    assert_eq!(rationalize(consts::PI, 0.01), r(22, 7));
+    assert_eq!(rationalize(consts::PI, 1e-6), r(355, 113));
+    assert_eq!(rationalize(-0.333, 0.001), r(-1, 3));
+    assert_eq!(rationalize(1.0 / 3.0, 0.0), r(1, 3));
+    assert_eq!(rationalize(f64::NAN, 0.1), None);
+}
+
+#[test]
+fn simple_fractions() {
+    assert_eq!(simple_fraction(1.0 / 3.0), r(1, 3));
+    assert_eq!(simple_fraction(0.1 + 0.2), r(3, 10));
+    assert_eq!(simple_fraction(consts::PI), None);
+}
+
+#[test]
+fn pi_multiples() {
+    assert_eq!(pi_multiple(consts::PI), r(1, 1));
+    assert_eq!(pi_multiple(consts::FRAC_PI_2), r(1, 2));
+    assert_eq!(pi_multiple(-3.0 * consts::FRAC_PI_4), r(-3, 4));
+    assert_eq!(pi_multiple(0.0), None);
+    assert_eq!(pi_multiple(1.0), None);
+}
@@ -5,31 +5,218 @@ use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-use num::traits::Signed;
+use if_chain::if_chain;
+use num::pow::pow;
+use num::rational::BigRational;
+use num::traits::{CheckedDiv, CheckedSub, One, Signed, ToPrimitive, Zero};
+use num::BigInt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::{Context, Error, Span, Value};
+use crate::interpreter::{binary, call_value, do_exp, Op};
+use crate::{
+    approximate, bessel_j, bessel_y, beta, binomial, binomial_f64, catalan, catalan_f64,
+    correlation, covariance, differentiate, double_factorial, double_factorial_f64, erf, erfc,
+    exact_cos, exact_sin, exact_tan, extreme, factorial, factorial_f64, factorize, falling,
+    falling_f64, fibonacci, fibonacci_f64, find_root, gamma, gcd, integrate, is_gamma_pole,
+    is_prime, lcm, linear_regression, ln_gamma, mean, median, mod_inv, mod_pow, mode, next_prime,
+    quantile, rationalize, simplify, std_dev, stirling1, stirling2, totient, variance, zeta,
+    AngleMeasure, Context, Data, Error, Expression, Matrix, Quantity, Rounding, Span, Symbolic,
+    Value, MAX_BESSEL_ORDER,
+};
 
 #[cfg(test)]
 mod tests;
 
-/// A built-in mathematical constant, such as pi.
+/// A built-in mathematical or physical constant, such as pi.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Constant {
     /// Pi, which is the ratio of a circle's semicircumference to its radius.
     Pi,
     /// E, also known as Euler's number, which is the base of the natural
     /// logarithm.
     E,
+    /// Tau, which is the ratio of a circle's circumference to its radius.
+    Tau,
+    /// Phi, the golden ratio.
+    Phi,
+    /// The Euler-Mascheroni constant, written `egamma` because `gamma` is the
+    /// gamma function.
+    EulerGamma,
+    /// The square root of two.
+    Sqrt2,
+    /// Positive infinity.
+    Inf,
+    /// Not a number.
+    Nan,
+
+    /// The speed of light in vacuum.
+    SpeedOfLight,
+    /// The Planck constant.
+    Planck,
+    /// The reduced Planck constant.
+    ReducedPlanck,
+    /// The Boltzmann constant.
+    Boltzmann,
+    /// The Avogadro constant.
+    Avogadro,
+    /// The elementary charge.
+    ElementaryCharge,
+    /// The Newtonian constant of gravitation.
+    Gravitation,
+    /// The mass of an electron.
+    ElectronMass,
+    /// The mass of a proton.
+    ProtonMass,
+    /// The electric constant, also known as the vacuum permittivity.
+    VacuumPermittivity,
+    /// The magnetic constant, also known as the vacuum permeability.
+    VacuumPermeability,
+    /// The molar gas constant.
+    GasConstant,
+    /// The standard acceleration of gravity.
+    StandardGravity,
+}
+
+/// Create a physical quantity in SI base units from its value and the
+/// exponents of metres, kilograms, seconds, amperes, kelvins, and moles.
+fn physical(value: f64, dim: [i8; 6]) -> Value {
+    let [m, kg, s, a, k, mol] = dim;
+    Value::Quantity(Quantity {
+        value,
+        dim: [m, kg, s, a, k, mol, 0],
+        unit: None,
+    })
 }
 
 impl Constant {
+    /// All of the constants, in the order that they should be listed.
+    pub const ALL: [Constant; 21] = [
+        Constant::Pi,
+        Constant::E,
+        Constant::Tau,
+        Constant::Phi,
+        Constant::EulerGamma,
+        Constant::Sqrt2,
+        Constant::Inf,
+        Constant::Nan,
+        Constant::SpeedOfLight,
+        Constant::Planck,
+        Constant::ReducedPlanck,
+        Constant::Boltzmann,
+        Constant::Avogadro,
+        Constant::ElementaryCharge,
+        Constant::Gravitation,
+        Constant::ElectronMass,
+        Constant::ProtonMass,
+        Constant::VacuumPermittivity,
+        Constant::VacuumPermeability,
+        Constant::GasConstant,
+        Constant::StandardGravity,
+    ];
+
     /// Get the value of the constant.
+    ///
+    /// Pi, e, and tau are exact symbolic values. Physical constants are
+    /// quantities in SI units, with values from the 2018 CODATA recommended
+    /// values.
     pub fn value(self) -> Value {
+        use Constant::*;
+        match self {
+            Pi | E => Symbolic::constant(self).into(),
+            Tau => Symbolic {
+                coef: 2.into(),
+                ..Symbolic::constant(Pi)
+            }
+            .into(),
+            Phi => 1.618_033_988_749_895.into(),
+            EulerGamma => 0.577_215_664_901_532_9.into(),
+            Sqrt2 => consts::SQRT_2.into(),
+            Inf => f64::INFINITY.into(),
+            Nan => f64::NAN.into(),
+            SpeedOfLight => physical(299_792_458.0, [1, 0, -1, 0, 0, 0]),
+            Planck => physical(6.626_070_15e-34, [2, 1, -1, 0, 0, 0]),
+            ReducedPlanck => physical(6.626_070_15e-34 / (2.0 * consts::PI), [2, 1, -1, 0, 0, 0]),
+            Boltzmann => physical(1.380_649e-23, [2, 1, -2, 0, -1, 0]),
+            Avogadro => physical(6.022_140_76e23, [0, 0, 0, 0, 0, -1]),
+            ElementaryCharge => physical(1.602_176_634e-19, [0, 0, 1, 1, 0, 0]),
+            Gravitation => physical(6.674_30e-11, [3, -1, -2, 0, 0, 0]),
+            ElectronMass => physical(9.109_383_701_5e-31, [0, 1, 0, 0, 0, 0]),
+            ProtonMass => physical(1.672_621_923_69e-27, [0, 1, 0, 0, 0, 0]),
+            VacuumPermittivity => physical(8.854_187_812_8e-12, [-3, -1, 4, 2, 0, 0]),
+            VacuumPermeability => physical(1.256_637_062_12e-6, [1, 1, -2, -2, 0, 0]),
+            GasConstant => physical(8.314_462_618, [2, 1, -2, 0, -1, -1]),
+            StandardGravity => physical(9.806_65, [1, 0, -2, 0, 0, 0]),
+        }
+    }
+
+    /// Get the numeric value of the constant as a float, without any unit.
+    pub fn to_f64(self) -> f64 {
         match self {
             Constant::Pi => consts::PI,
             Constant::E => consts::E,
+            _ => match self.value() {
+                Value::Quantity(q) => q.value,
+                v => v.as_f64().expect("constant is not a number"),
+            },
+        }
+    }
+
+    /// Get a short description of the constant.
+    pub fn description(self) -> &'static str {
+        use Constant::*;
+        match self {
+            Pi => "ratio of a circle's circumference to its diameter",
+            E => "base of the natural logarithm",
+            Tau => "ratio of a circle's circumference to its radius",
+            Phi => "golden ratio",
+            EulerGamma => "Euler-Mascheroni constant",
+            Sqrt2 => "square root of two",
+            Inf => "positive infinity",
+            Nan => "not a number",
+            SpeedOfLight => "speed of light in vacuum",
+            Planck => "Planck constant",
+            ReducedPlanck => "reduced Planck constant",
+            Boltzmann => "Boltzmann constant",
+            Avogadro => "Avogadro constant",
+            ElementaryCharge => "elementary charge",
+            Gravitation => "Newtonian constant of gravitation",
+            ElectronMass => "electron mass",
+            ProtonMass => "proton mass",
+            VacuumPermittivity => "vacuum electric permittivity",
+            VacuumPermeability => "vacuum magnetic permeability",
+            GasConstant => "molar gas constant",
+            StandardGravity => "standard acceleration of gravity",
+        }
+    }
+
+    /// Get the conventional symbol for the constant, such as `π`, with any
+    /// subscript after an underscore, as in `k_B`.
+    pub fn symbol(self) -> &'static str {
+        use Constant::*;
+        match self {
+            Pi => "\u{3c0}",
+            E | ElementaryCharge => "e",
+            Tau => "\u{3c4}",
+            Phi => "\u{3c6}",
+            EulerGamma => "\u{3b3}",
+            Sqrt2 => "\u{221a}2",
+            Inf => "\u{221e}",
+            Nan => "NaN",
+            SpeedOfLight => "c",
+            Planck => "h",
+            ReducedPlanck => "\u{127}",
+            Boltzmann => "k_B",
+            Avogadro => "N_A",
+            Gravitation => "G",
+            ElectronMass => "m_e",
+            ProtonMass => "m_p",
+            VacuumPermittivity => "\u{3b5}_0",
+            VacuumPermeability => "\u{3bc}_0",
+            GasConstant => "R",
+            StandardGravity => "g_0",
         }
-        .into()
     }
 }
 
@@ -37,19 +224,39 @@ impl FromStr for Constant {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Constant, ()> {
-        match s {
-            "pi" => Ok(Constant::Pi),
-            "e" => Ok(Constant::E),
-            _ => Err(()),
-        }
+        Constant::ALL
+            .iter()
+            .find(|c| c.to_string() == s)
+            .copied()
+            .ok_or(())
     }
 }
 
 impl Display for Constant {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use Constant::*;
         let s = match self {
-            Constant::Pi => "pi",
-            Constant::E => "e",
+            Pi => "pi",
+            E => "e",
+            Tau => "tau",
+            Phi => "phi",
+            EulerGamma => "egamma",
+            Sqrt2 => "sqrt2",
+            Inf => "inf",
+            Nan => "nan",
+            SpeedOfLight => "phys.c",
+            Planck => "phys.h",
+            ReducedPlanck => "phys.hbar",
+            Boltzmann => "phys.k",
+            Avogadro => "phys.NA",
+            ElementaryCharge => "phys.e",
+            Gravitation => "phys.G",
+            ElectronMass => "phys.me",
+            ProtonMass => "phys.mp",
+            VacuumPermittivity => "phys.eps0",
+            VacuumPermeability => "phys.mu0",
+            GasConstant => "phys.R",
+            StandardGravity => "phys.g0",
         };
         write!(f, "{}", s)
     }
@@ -84,6 +291,147 @@ pub enum Function {
     Acosh,
     /// Inverse hyperbolic tangent.
     Atanh,
+    /// Natural logarithm.
+    Ln,
+    /// Rational approximation within a tolerance.
+    Rationalize,
+    /// Best rational approximation with a bounded denominator.
+    Approx,
+    /// Greatest integer less than or equal to a number.
+    Floor,
+    /// Least integer greater than or equal to a number.
+    Ceil,
+    /// Rounding to the nearest integer, or to a number of decimal places,
+    /// using the context's `round_ties` mode to break ties.
+    Round,
+    /// Integer part, i.e., rounding toward zero.
+    Trunc,
+    /// Fractional part, which has the same sign as the number.
+    Frac,
+    /// Sign of a number: -1, 0, or 1.
+    Sign,
+    /// Greatest common divisor of integers.
+    Gcd,
+    /// Least common multiple of integers.
+    Lcm,
+    /// Primality test, giving 1 for primes and 0 otherwise.
+    IsPrime,
+    /// Smallest prime greater than an integer.
+    NextPrime,
+    /// Prime factorisation of an integer.
+    Factor,
+    /// Euler's totient function.
+    Totient,
+    /// Modular exponentiation.
+    ModPow,
+    /// Modular multiplicative inverse.
+    ModInv,
+    /// Factorial.
+    Fact,
+    /// Double factorial.
+    DoubleFact,
+    /// Binomial coefficient.
+    Choose,
+    /// Number of permutations of `k` of `n` items.
+    Perm,
+    /// Fibonacci number.
+    Fib,
+    /// Catalan number.
+    Catalan,
+    /// Unsigned Stirling number of the first kind.
+    Stirling1,
+    /// Stirling number of the second kind.
+    Stirling2,
+    /// Gamma function.
+    Gamma,
+    /// Natural logarithm of the absolute value of the gamma function.
+    LnGamma,
+    /// Beta function.
+    Beta,
+    /// Error function.
+    Erf,
+    /// Complementary error function.
+    Erfc,
+    /// Riemann zeta function.
+    Zeta,
+    /// Bessel function of the first kind of integer order.
+    BesselJ,
+    /// Bessel function of the second kind of integer order.
+    BesselY,
+    /// Number of elements in a list.
+    Len,
+    /// Sum of the elements of a list.
+    Sum,
+    /// Product of the elements of a list.
+    Prod,
+    /// Dot product of two lists.
+    Dot,
+    /// Cross product of two lists of three elements.
+    Cross,
+    /// Euclidean norm of a list.
+    Norm,
+    /// Transpose of a matrix.
+    Transpose,
+    /// Determinant of a square matrix.
+    Det,
+    /// Inverse of a square matrix.
+    Inv,
+    /// Rank of a matrix.
+    Rank,
+    /// Solution of a linear system, or a root of a function.
+    Solve,
+    /// Identity matrix of a given size.
+    Identity,
+    /// Arithmetic mean of a list.
+    Mean,
+    /// Median of a list.
+    Median,
+    /// Most common number in a list.
+    Mode,
+    /// Sample variance of a list.
+    Var,
+    /// Population variance of a list.
+    PopVar,
+    /// Sample standard deviation of a list.
+    Stdev,
+    /// Population standard deviation of a list.
+    PopStdev,
+    /// Quantile of a list, such as 0.25 for the first quartile.
+    Quantile,
+    /// Smallest number in a list, or of several numbers.
+    Min,
+    /// Largest number in a list, or of several numbers.
+    Max,
+    /// Sample covariance of two lists.
+    Cov,
+    /// Pearson correlation coefficient of two lists.
+    Corr,
+    /// Least-squares line through points given as lists of x and y values,
+    /// as a list of its slope and intercept.
+    LinReg,
+    /// List of numbers from a start to an end inclusive, with an optional
+    /// step.
+    Range,
+    /// List of the values of a function over a range.
+    Seq,
+    /// List of the values of a function at each element of a list.
+    Map,
+    /// List of the elements of a list at which a function is non-zero.
+    Filter,
+    /// Combination of the elements of a list with a function of two
+    /// arguments, from the left.
+    Reduce,
+    /// Composition of two functions.
+    Compose,
+    /// Definite integral of a function.
+    Integrate,
+    /// Definite integral of a function and an estimate of its absolute
+    /// error, as a list.
+    Quad,
+    /// Derivative of a function of one argument.
+    Diff,
+    /// Algebraic simplification of a function.
+    Simplify,
 }
 
 impl Function {
@@ -92,20 +440,159 @@ impl Function {
     /// The context is primarily used to determine the angle with which the
     /// calculation should be performed (i.e., degrees or radians).
     pub fn apply(self, x: Value, ctx: &Context, span: &Span) -> crate::Result<Value> {
+        self.apply_args(vec![(x, span.clone())], ctx, span)
+    }
+
+    /// Apply the function to a list of arguments given a context.
+    ///
+    /// Each argument is paired with its span, which is used to report errors
+    /// concerning that particular argument.
+    pub fn apply_args(
+        self,
+        args: Vec<(Value, Span)>,
+        ctx: &Context,
+        span: &Span,
+    ) -> crate::Result<Value> {
+        let mut args: Vec<_> = args.into_iter().map(|(x, s)| (x.expand(), s)).collect();
+        match self {
+            Function::Rationalize | Function::Approx => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err((Error::Syntax, span.clone()));
+                }
+                let param = if args.len() == 2 { args.pop() } else { None };
+                let (x, x_span) = args.pop().unwrap();
+                if self == Function::Rationalize {
+                    do_rationalize(x, x_span, param)
+                } else {
+                    do_approx(x, x_span, param)
+                }
+            }
+            Function::Round => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err((Error::Syntax, span.clone()));
+                }
+                let digits = if args.len() == 2 { args.pop() } else { None };
+                let (x, x_span) = args.pop().unwrap();
+                do_round(x, x_span, digits, ctx.round_ties)
+            }
+            Function::Gcd
+            | Function::Lcm
+            | Function::IsPrime
+            | Function::NextPrime
+            | Function::Factor
+            | Function::Totient
+            | Function::ModPow
+            | Function::ModInv => self.apply_integers(&args, span),
+            Function::Fact
+            | Function::DoubleFact
+            | Function::Choose
+            | Function::Perm
+            | Function::Fib
+            | Function::Catalan
+            | Function::Stirling1
+            | Function::Stirling2 => self.apply_combinatorial(&args, span),
+            Function::Gamma
+            | Function::LnGamma
+            | Function::Beta
+            | Function::Erf
+            | Function::Erfc
+            | Function::Zeta
+            | Function::BesselJ
+            | Function::BesselY => self.apply_special(&args, span),
+            Function::Len
+            | Function::Sum
+            | Function::Prod
+            | Function::Dot
+            | Function::Cross
+            | Function::Norm => self.apply_list(&args, span),
+            Function::Solve
+                if matches!(args.first(), Some((Value::Func(..) | Value::Builtin(_), _))) =>
+            {
+                self.apply_higher(&args, ctx, span)
+            }
+            Function::Transpose
+            | Function::Det
+            | Function::Inv
+            | Function::Rank
+            | Function::Solve
+            | Function::Identity => self.apply_matrix(&args, span),
+            Function::Mean
+            | Function::Median
+            | Function::Mode
+            | Function::Var
+            | Function::PopVar
+            | Function::Stdev
+            | Function::PopStdev
+            | Function::Quantile
+            | Function::Min
+            | Function::Max
+            | Function::Cov
+            | Function::Corr
+            | Function::LinReg => self.apply_stats(&args, span),
+            Function::Range => range(&args, span),
+            Function::Seq
+            | Function::Map
+            | Function::Filter
+            | Function::Reduce
+            | Function::Compose
+            | Function::Integrate
+            | Function::Quad
+            | Function::Diff
+            | Function::Simplify => self.apply_higher(&args, ctx, span),
+            _ => {
+                if args.len() != 1 {
+                    return Err((Error::Syntax, span.clone()));
+                }
+                let (x, _) = args.pop().unwrap();
+                self.apply_unary(x, ctx, span)
+            }
+        }
+    }
+
+    fn apply_unary(self, x: Value, ctx: &Context, span: &Span) -> crate::Result<Value> {
+        if let Value::List(items) = x {
+            return items
+                .into_iter()
+                .map(|x| self.apply_unary(x.expand(), ctx, span))
+                .collect::<crate::Result<_>>()
+                .map(Value::List);
+        } else if let Value::Matrix(m) = x {
+            return m
+                .try_map(|x| self.apply_unary(x.clone(), ctx, span))
+                .map(Value::Matrix);
+        }
         if self == Function::Abs {
             match x {
                 Value::Float(f) => Ok(f.abs().into()),
                 Value::Ratio(f) => Ok(f.abs().into()),
+                Value::BigRatio(f) => Ok(Value::BigRatio(f.abs())),
+                Value::Symbolic(s) => Ok(s.abs().into()),
+                Value::Quantity(q) => Ok(Value::Quantity(Quantity {
+                    value: q.value.abs(),
+                    ..q
+                })),
                 _ => Err((Error::Type, span.clone())),
             }
+        } else if let Some(mode) = self.rounding() {
+            round_int(x, span, mode)
+        } else if self == Function::Frac {
+            do_frac(x, span)
+        } else if self == Function::Sign {
+            do_sign(x, span)
         } else {
-            let x = match x {
-                Value::Float(f) => f,
-                Value::Ratio(f) => *f.numer() as f64 / *f.denom() as f64,
-                _ => return Err((Error::Type, span.clone())),
+            if let Some(res) = self.exact_trig(&x, ctx) {
+                return res.ok_or_else(|| (Error::Domain, span.clone()));
+            }
+            if self == Function::Ln {
+                if let Some(res) = exact_ln(&x) {
+                    return Ok(res);
+                }
+            }
+            let x = match x.as_f64() {
+                Some(f) => f,
+                None => return Err((Error::Type, span.clone())),
             };
             match self {
-                Function::Abs => unreachable!(),
                 Function::Sin => Ok(ctx.angle.to_rad(x).sin().into()),
                 Function::Cos => Ok(ctx.angle.to_rad(x).cos().into()),
                 Function::Tan => Ok(ctx.angle.to_rad(x).tan().into()),
@@ -118,8 +605,801 @@ impl Function {
                 Function::Asinh => Ok(x.asinh().into()),
                 Function::Acosh => Ok(x.acosh().into()),
                 Function::Atanh => Ok(x.atanh().into()),
+                Function::Ln if x < 0.0 => Err((Error::Domain, span.clone())),
+                Function::Ln => Ok(x.ln().into()),
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+impl Function {
+    /// Get the rounding mode used by an integer-rounding function.
+    fn rounding(self) -> Option<Rounding> {
+        match self {
+            Function::Floor => Some(Rounding::Floor),
+            Function::Ceil => Some(Rounding::Ceiling),
+            Function::Trunc => Some(Rounding::TowardZero),
+            _ => None,
+        }
+    }
+
+    /// Apply a number theory function, whose arguments must all be exact
+    /// integers.
+    fn apply_integers(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        let arity = match self {
+            Function::Gcd | Function::Lcm => 1..=usize::MAX,
+            Function::ModPow => 3..=3,
+            Function::ModInv => 2..=2,
+            _ => 1..=1,
+        };
+        if !arity.contains(&args.len()) {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let ints = args
+            .iter()
+            .map(|(x, s)| arg_int(x, s))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let n = ints[0];
+        let err_span = match self {
+            Function::NextPrime | Function::Factor | Function::Totient => &args[0].1,
+            Function::ModPow | Function::ModInv if *ints.last().unwrap() <= 0 => {
+                &args.last().unwrap().1
+            }
+            _ => span,
+        };
+        let result = match self {
+            Function::Gcd => ints.iter().try_fold(0, |a, &b| gcd(a, b)),
+            Function::Lcm => ints.iter().try_fold(1, |a, &b| lcm(a, b)),
+            Function::IsPrime => Some(is_prime(n) as i64),
+            Function::NextPrime => next_prime(n),
+            Function::Factor => {
+                return factorize(n)
+                    .map(Value::Factors)
+                    .ok_or_else(|| (Error::Domain, err_span.clone()))
+            }
+            Function::Totient => totient(n),
+            Function::ModPow => mod_pow(n, ints[1], ints[2]),
+            Function::ModInv => mod_inv(n, ints[1]),
+            _ => unreachable!(),
+        };
+        result
+            .map(Value::from)
+            .ok_or_else(|| (Error::Domain, err_span.clone()))
+    }
+
+    /// Apply a combinatorial function, which is exact for integer arguments
+    /// and uses the gamma function for other arguments.
+    fn apply_combinatorial(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        let arity = match self {
+            Function::Choose | Function::Perm | Function::Stirling1 | Function::Stirling2 => 2,
+            _ => 1,
+        };
+        if args.len() != arity {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let is_integer = |(x, _): &(Value, Span)| match x {
+            Value::Ratio(r) => r.is_integer(),
+            Value::BigRatio(r) => r.is_integer(),
+            _ => false,
+        };
+        if args.iter().all(is_integer) {
+            // Integers too large for an `i64` are too large to be done exactly
+            let ints = args
+                .iter()
+                .map(|(x, x_span)| arg_int(x, x_span))
+                .collect::<crate::Result<Vec<_>>>()?;
+            return self.exact_combinatorial(&ints, args);
+        }
+        if let Function::Stirling1 | Function::Stirling2 = self {
+            for (x, x_span) in args {
+                arg_int(x, x_span)?;
+            }
+            return Err((Error::Domain, span.clone()));
+        }
+        let xs = args
+            .iter()
+            .map(|(x, x_span)| arg_f64(x, x_span))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let result = match self {
+            Function::Fact => factorial_f64(xs[0]),
+            Function::DoubleFact => double_factorial_f64(xs[0]),
+            Function::Choose => binomial_f64(xs[0], xs[1]),
+            Function::Perm => falling_f64(xs[0], xs[1]),
+            Function::Fib => fibonacci_f64(xs[0]),
+            Function::Catalan => catalan_f64(xs[0]),
+            _ => unreachable!(),
+        };
+        Ok(result.into())
+    }
+
+    /// Apply a combinatorial function to integers exactly, reporting a domain
+    /// error if the calculation is too large to be done exactly.
+    fn exact_combinatorial(self, ints: &[i64], args: &[(Value, Span)]) -> crate::Result<Value> {
+        let n = ints[0];
+        let pole = match self {
+            Function::Fact | Function::Catalan => n < 0,
+            Function::DoubleFact => n < -1 && n % 2 == 0,
+            Function::Stirling1 | Function::Stirling2 => n < 0 || ints[1] < 0,
+            Function::Perm => ints[1] < 0,
+            _ => false,
+        };
+        if pole {
+            let i = match self {
+                Function::Perm => 1,
+                Function::Stirling1 | Function::Stirling2 if n >= 0 => 1,
+                _ => 0,
+            };
+            return Err((Error::Domain, args[i].1.clone()));
+        }
+        let size = match self {
+            Function::Choose if ints[1] < 0 => 0,
+            Function::Choose if n >= 0 => ints[1].min(n - ints[1]).max(0),
+            Function::Choose | Function::Perm => ints[1],
+            _ => n,
+        };
+        let limit = match self {
+            Function::Stirling1 | Function::Stirling2 => MAX_STIRLING_SIZE,
+            _ => MAX_EXACT_SIZE,
+        };
+        if size.unsigned_abs() > limit {
+            let i = match self {
+                Function::Choose | Function::Perm => 1,
+                _ => 0,
+            };
+            return Err((Error::Domain, args[i].1.clone()));
+        }
+        let result: Value = match self {
+            Function::Fact => factorial(n as u64).into(),
+            Function::DoubleFact => double_factorial(n).unwrap().into(),
+            Function::Choose => binomial(n, ints[1]).into(),
+            Function::Perm => falling(n, ints[1] as u64).into(),
+            Function::Fib => fibonacci(n).into(),
+            Function::Catalan => catalan(n as u64).into(),
+            Function::Stirling1 => stirling1(n as u64, ints[1] as u64).into(),
+            Function::Stirling2 => stirling2(n as u64, ints[1] as u64).into(),
+            _ => unreachable!(),
+        };
+        Ok(result)
+    }
+
+    /// Apply a special function, reporting a domain error at its poles.
+    fn apply_special(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        let arity = match self {
+            Function::Beta | Function::BesselJ | Function::BesselY => 2,
+            _ => 1,
+        };
+        if args.len() != arity {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let x = arg_f64(&args[arity - 1].0, &args[arity - 1].1)?;
+        let domain = |i: usize| Err((Error::Domain, args[i].1.clone()));
+        let result = match self {
+            Function::Gamma | Function::LnGamma if is_gamma_pole(x) => return domain(0),
+            Function::Gamma => gamma(x),
+            Function::LnGamma => ln_gamma(x),
+            Function::Beta => {
+                let a = arg_f64(&args[0].0, &args[0].1)?;
+                if is_gamma_pole(a) {
+                    return domain(0);
+                } else if is_gamma_pole(x) {
+                    return domain(1);
+                }
+                beta(a, x)
+            }
+            Function::Erf => erf(x),
+            Function::Erfc => erfc(x),
+            Function::Zeta if x == 1.0 => return domain(0),
+            Function::Zeta => zeta(x),
+            Function::BesselJ | Function::BesselY
+                if arg_int(&args[0].0, &args[0].1)?.unsigned_abs() > MAX_BESSEL_ORDER =>
+            {
+                return domain(0)
+            }
+            Function::BesselJ => bessel_j(arg_int(&args[0].0, &args[0].1)?, x),
+            Function::BesselY => {
+                let n = arg_int(&args[0].0, &args[0].1)?;
+                if x <= 0.0 {
+                    return domain(1);
+                }
+                bessel_y(n, x)
+            }
+            _ => unreachable!(),
+        };
+        Ok(result.into())
+    }
+
+    /// Apply a function whose arguments are lists.
+    fn apply_list(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        let arity = match self {
+            Function::Dot | Function::Cross => 2,
+            _ => 1,
+        };
+        if args.len() != arity {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let lists = args
+            .iter()
+            .map(|(x, x_span)| match x {
+                Value::List(items) => Ok(items.iter().cloned().map(Value::expand).collect()),
+                _ => Err((Error::Type, x_span.clone())),
+            })
+            .collect::<crate::Result<Vec<Vec<_>>>>()?;
+        let v = &lists[0];
+        match self {
+            Function::Len => Ok((v.len() as i64).into()),
+            Function::Sum => fold(Op::Add, v, 0, span),
+            Function::Prod => fold(Op::Mul, v, 1, span),
+            Function::Dot => dot(v, &lists[1], &args[1].1),
+            Function::Cross => {
+                for (list, (_, list_span)) in lists.iter().zip(args) {
+                    if list.len() != 3 {
+                        return Err((Error::Domain, list_span.clone()));
+                    }
+                }
+                let w = &lists[1];
+                let term = |i: usize, j: usize| {
+                    let a = binary(Op::Mul, &v[i], &w[j], span)?;
+                    let b = binary(Op::Mul, &v[j], &w[i], span)?;
+                    binary(Op::Sub, &a, &b, span)
+                };
+                Ok(Value::List(vec![term(1, 2)?, term(2, 0)?, term(0, 1)?]))
+            }
+            Function::Norm => do_exp(dot(v, v, span)?, (1, 2).into(), span),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Apply a linear algebra function.
+    fn apply_matrix(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        let arity = if self == Function::Solve { 2 } else { 1 };
+        if args.len() != arity {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let (x, x_span) = &args[0];
+        match self {
+            Function::Identity => {
+                let n = arg_int(x, x_span)?;
+                if !(1..=MAX_IDENTITY_SIZE).contains(&n) {
+                    return Err((Error::Domain, x_span.clone()));
+                }
+                return Ok(Matrix::identity(n as usize).into());
+            }
+            Function::Transpose => {
+                if let Value::List(items) = x {
+                    return Matrix::from_rows(vec![items.clone()])
+                        .map(|m| m.transpose().into())
+                        .ok_or_else(|| (Error::Shape, x_span.clone()));
+                }
+            }
+            _ => (),
+        }
+        let m = match x {
+            Value::Matrix(m) => m,
+            _ => return Err((Error::Type, x_span.clone())),
+        };
+        let not_number = || (Error::Type, x_span.clone());
+        if !m.is_square() && self != Function::Transpose && self != Function::Rank {
+            return Err((Error::Shape, x_span.clone()));
+        }
+        match self {
+            Function::Transpose => Ok(m.transpose().into()),
+            Function::Det => m.det().ok_or_else(not_number),
+            Function::Inv => match m.inverse().ok_or_else(not_number)? {
+                Some(inv) => Ok(inv.into()),
+                None => Err((Error::Domain, x_span.clone())),
+            },
+            Function::Rank => Ok((m.rank().ok_or_else(not_number)? as i64).into()),
+            Function::Solve => {
+                let (b, b_span) = &args[1];
+                let (b, is_list) = match b {
+                    Value::List(items) => (Matrix::column(items.clone()), true),
+                    Value::Matrix(b) => (Some(b.clone()), false),
+                    _ => return Err((Error::Type, b_span.clone())),
+                };
+                let b = match b {
+                    Some(b) if b.rows() == m.rows() => b,
+                    _ => return Err((Error::Shape, b_span.clone())),
+                };
+                match m.solve(&b).ok_or_else(|| (Error::Type, span.clone()))? {
+                    Some(x) if is_list => Ok(Value::List(x.into_entries())),
+                    Some(x) => Ok(x.into()),
+                    None => Err((Error::Domain, x_span.clone())),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Apply a statistical function, whose first argument is a list of
+    /// numbers.
+    fn apply_stats(self, args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+        if let (Function::Min | Function::Max, 2..) = (self, args.len()) {
+            // The numbers are given as separate arguments
+            let items: Vec<Value> = args.iter().map(|(x, _)| x.clone()).collect();
+            let data = Data::new(&items).ok_or_else(|| (Error::Type, span.clone()))?;
+            let i = extreme(&data, self == Function::Max).unwrap();
+            return Ok(items[i].clone());
+        }
+        let arity = match self {
+            Function::Quantile | Function::Cov | Function::Corr | Function::LinReg => 2,
+            _ => 1,
+        };
+        if args.len() != arity {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let lists = args
+            .iter()
+            .take(if self == Function::Quantile { 1 } else { arity })
+            .map(|(x, x_span)| match x {
+                Value::List(items) => {
+                    let items: Vec<_> = items.iter().cloned().map(Value::expand).collect();
+                    let data = Data::new(&items).ok_or_else(|| (Error::Type, x_span.clone()))?;
+                    Ok((items, data))
+                }
+                _ => Err((Error::Type, x_span.clone())),
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let (items, data) = &lists[0];
+        if lists.len() == 2 && lists[1].1.len() != data.len() {
+            return Err((Error::Length, args[1].1.clone()));
+        }
+        let result = match self {
+            Function::Mean => mean(data),
+            Function::Median => median(data),
+            Function::Mode => mode(data),
+            Function::Var => variance(data, 1),
+            Function::PopVar => variance(data, 0),
+            Function::Stdev => std_dev(data, 1).map(Value::from),
+            Function::PopStdev => std_dev(data, 0).map(Value::from),
+            Function::Quantile => {
+                let (q, q_span) = &args[1];
+                arg_f64(q, q_span)?;
+                if data.is_empty() {
+                    return Err((Error::Domain, args[0].1.clone()));
+                }
+                let q = q.clone().expand();
+                return quantile(data, &q).ok_or_else(|| (Error::Domain, q_span.clone()));
+            }
+            Function::Min | Function::Max => {
+                extreme(data, self == Function::Max).map(|i| items[i].clone())
+            }
+            Function::Cov => covariance(data, &lists[1].1, 1),
+            Function::Corr => correlation(data, &lists[1].1).map(Value::from),
+            Function::LinReg => linear_regression(data, &lists[1].1)
+                .map(|(slope, intercept)| Value::List(vec![slope, intercept])),
+            _ => unreachable!(),
+        };
+        result.ok_or_else(|| (Error::Domain, args[0].1.clone()))
+    }
+
+    /// Apply a function whose first argument is a function.
+    fn apply_higher(
+        self,
+        args: &[(Value, Span)],
+        ctx: &Context,
+        span: &Span,
+    ) -> crate::Result<Value> {
+        let arity = match self {
+            Function::Seq => 3..=4,
+            Function::Reduce => 2..=3,
+            Function::Integrate | Function::Quad => 3..=3,
+            Function::Solve => 1..=3,
+            Function::Diff | Function::Simplify => 1..=1,
+            _ => 2..=2,
+        };
+        if !arity.contains(&args.len()) {
+            return Err((Error::Syntax, span.clone()));
+        }
+        let (f, f_span) = &args[0];
+        if !matches!(f, Value::Func(_, _) | Value::Builtin(_)) {
+            return Err((Error::Type, f_span.clone()));
+        }
+        let call = |args: Vec<Value>| {
+            let args = args.into_iter().map(|x| (x, f_span.clone())).collect();
+            call_value(f, args, ctx, f_span)
+        };
+        if self == Function::Compose {
+            return compose(&args[0], &args[1]);
+        }
+        if self == Function::Diff {
+            return diff_value(f, ctx, f_span);
+        }
+        if let (Function::Simplify, Value::Func(params, body)) = (self, f) {
+            return Ok(Value::Func(params.clone(), Box::new(simplify(body))));
+        } else if self == Function::Simplify {
+            return Ok(f.clone());
+        }
+        if self == Function::Integrate || self == Function::Quad {
+            let a = arg_f64(&args[1].0, &args[1].1)?;
+            let b = arg_f64(&args[2].0, &args[2].1)?;
+            let f = |x: f64| arg_f64(&call(vec![x.into()])?, f_span);
+            return match integrate(f, a, b)? {
+                Some(integral) if self == Function::Quad => Ok(Value::List(vec![
+                    integral.value.into(),
+                    integral.error.into(),
+                ])),
+                Some(integral) => Ok(integral.value.into()),
+                None => Err((Error::Convergence, span.clone())),
+            };
+        }
+        if self == Function::Solve {
+            let guesses = args[1..]
+                .iter()
+                .map(|(x, x_span)| arg_f64(x, x_span))
+                .collect::<crate::Result<Vec<_>>>()?;
+            let f = |x: f64| arg_f64(&call(vec![x.into()])?, f_span);
+            return match find_root(f, &guesses)? {
+                Some(root) => Ok(root.into()),
+                // The bracket doesn't contain a sign change
+                None if guesses.len() == 2 => Err((Error::Domain, span.clone())),
+                None => Err((Error::Convergence, span.clone())),
+            };
+        }
+        let items = match self {
+            Function::Seq => match range(&args[1..], span)? {
+                Value::List(items) => items,
+                _ => unreachable!(),
+            },
+            _ => match &args[1] {
+                (Value::Matrix(m), _) if self == Function::Map => {
+                    return m.try_map(|x| call(vec![x.clone()])).map(Value::Matrix);
+                }
+                (Value::List(items), _) => items.iter().cloned().map(Value::expand).collect(),
+                (_, list_span) => return Err((Error::Type, list_span.clone())),
+            },
+        };
+        match self {
+            Function::Seq | Function::Map => items
+                .into_iter()
+                .map(|x| call(vec![x]))
+                .collect::<crate::Result<_>>()
+                .map(Value::List),
+            Function::Filter => {
+                let mut kept = vec![];
+                for x in items {
+                    let keep = call(vec![x.clone()])?;
+                    if arg_f64(&keep, f_span)? != 0.0 {
+                        kept.push(x);
+                    }
+                }
+                Ok(Value::List(kept))
+            }
+            Function::Reduce => {
+                let mut items = items.into_iter();
+                let init = match args.get(2) {
+                    Some((init, _)) => init.clone(),
+                    None => items
+                        .next()
+                        .ok_or_else(|| (Error::Domain, args[1].1.clone()))?,
+                };
+                items.try_fold(init, |acc, x| call(vec![acc, x]))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Evaluate a trigonometric function exactly, if `x` is a rational
+    /// multiple of pi (or of a degree) with a rational result.
+    ///
+    /// Returns `Some(None)` if the function is undefined at `x`.
+    fn exact_trig(self, x: &Value, ctx: &Context) -> Option<Option<Value>> {
+        let turns = match (ctx.angle, x) {
+            (AngleMeasure::Radians, Value::Symbolic(s))
+                if s.base == Constant::Pi && s.power.is_one() =>
+            {
+                s.coef
+            }
+            (AngleMeasure::Degrees, Value::Ratio(r)) => r.checked_div(&180.into())?,
+            _ => return None,
+        };
+        let result = match self {
+            Function::Sin => Some(exact_sin(turns)?),
+            Function::Cos => Some(exact_cos(turns)?),
+            Function::Tan => exact_tan(turns)?,
+            _ => return None,
+        };
+        Some(result.map(Value::Ratio))
+    }
+}
+
+/// Get the natural logarithm of one or of a rational power of e exactly.
+fn exact_ln(x: &Value) -> Option<Value> {
+    match x {
+        Value::Ratio(r) if r.is_one() => Some(0.into()),
+        Value::Symbolic(s) if s.base == Constant::E && s.coef.is_one() => Some(s.power.into()),
+        _ => None,
+    }
+}
+
+/// Differentiate a function of one parameter, giving another function. A
+/// built-in function is differentiated as a function of `x`.
+fn diff_value(f: &Value, ctx: &Context, span: &Span) -> crate::Result<Value> {
+    let (param, body) = match f {
+        Value::Func(params, body) if params.len() == 1 => {
+            (params[0].clone(), body.as_ref().clone())
+        }
+        Value::Builtin(g) => {
+            let x = (Expression::Var("x".to_string()), span.clone());
+            (
+                "x".to_string(),
+                (Expression::Func(*g, Box::new(x)), span.clone()),
+            )
+        }
+        _ => return Err((Error::Type, span.clone())),
+    };
+    let body = simplify(&differentiate(&body, &param, ctx)?);
+    Ok(Value::Func(vec![param], Box::new(body)))
+}
+
+/// Combine the elements of a list with an operator, giving `empty` for an
+/// empty list.
+fn fold(op: Op, v: &[Value], empty: i64, span: &Span) -> crate::Result<Value> {
+    match v.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .try_fold(first.clone(), |acc, x| binary(op, &acc, x, span)),
+        None => Ok(empty.into()),
+    }
+}
+
+/// Get the dot product of two lists, reporting a length mismatch at `span`.
+fn dot(v: &[Value], w: &[Value], span: &Span) -> crate::Result<Value> {
+    if v.len() != w.len() {
+        return Err((Error::Length, span.clone()));
+    }
+    let products = v
+        .iter()
+        .zip(w)
+        .map(|(a, b)| binary(Op::Mul, a, b, span))
+        .collect::<crate::Result<Vec<_>>>()?;
+    fold(Op::Add, &products, 0, span)
+}
+
+/// Get the list of numbers from `start` to `end` inclusive, counting by
+/// `step` or by one, given as `args`. The numbers are exact if the arguments
+/// are all rational.
+fn range(args: &[(Value, Span)], span: &Span) -> crate::Result<Value> {
+    if !(2..=3).contains(&args.len()) {
+        return Err((Error::Syntax, span.clone()));
+    }
+    let step = args.get(2).cloned().unwrap_or((1.into(), span.clone()));
+    let bounds = [&args[0], &args[1], &step];
+    for (x, x_span) in bounds {
+        arg_f64(x, x_span)?;
+    }
+    let (step, step_span) = &step;
+    let exact: Option<Vec<_>> = bounds.iter().map(|(x, _)| x.to_big()).collect();
+    let items = if let Some(exact) = exact {
+        let (start, end, step) = (&exact[0], &exact[1], &exact[2]);
+        if step.is_zero() {
+            return Err((Error::Domain, step_span.clone()));
+        }
+        let n = ((end - start) / step).floor().to_integer();
+        let n = if n.is_negative() {
+            0
+        } else {
+            n.to_usize().unwrap_or(usize::MAX).saturating_add(1)
+        };
+        if n > MAX_RANGE_LEN {
+            return Err((Error::Domain, span.clone()));
+        }
+        (0..n)
+            .map(|i| (start + step * BigRational::from_integer(i.into())).into())
+            .collect()
+    } else {
+        let start = args[0].0.as_f64().unwrap();
+        let end = args[1].0.as_f64().unwrap();
+        let step = step.as_f64().unwrap();
+        if step == 0.0 {
+            return Err((Error::Domain, step_span.clone()));
+        }
+        // Allow for rounding error, so that `range(0, 0.3, 0.1)` includes 0.3
+        let n = ((end - start) / step + RANGE_TOLERANCE).floor();
+        if n.is_nan() || n >= MAX_RANGE_LEN as f64 {
+            return Err((Error::Domain, span.clone()));
+        }
+        let n = if n < 0.0 { 0 } else { n as usize + 1 };
+        (0..n).map(|i| (start + step * i as f64).into()).collect()
+    };
+    Ok(Value::List(items))
+}
+
+/// Get the composition of the function `f` with the function `g`, which
+/// applies `g` to its arguments and then `f` to the result.
+fn compose((f, f_span): &(Value, Span), (g, g_span): &(Value, Span)) -> crate::Result<Value> {
+    let (params, inner) = match g {
+        Value::Func(params, body) => (params.clone(), body.as_ref().clone()),
+        Value::Builtin(func) => {
+            let x = (Expression::Var("x".to_string()), g_span.clone());
+            (
+                vec!["x".to_string()],
+                (Expression::Func(*func, Box::new(x)), g_span.clone()),
+            )
+        }
+        _ => return Err((Error::Type, g_span.clone())),
+    };
+    let body = match f {
+        Value::Func(outer, body) if outer.len() == 1 => {
+            Expression::substitute(body, &outer[0], &inner)
+        }
+        Value::Builtin(func) => (Expression::Func(*func, Box::new(inner)), f_span.clone()),
+        _ => return Err((Error::Type, f_span.clone())),
+    };
+    Ok(Value::Func(params, Box::new(body)))
+}
+
+/// Round a number to an integer.
+///
+/// Rationals are rounded exactly, and symbolic values produce an exact
+/// integer if it is small enough to be represented.
+fn round_int(x: Value, span: &Span, mode: Rounding) -> crate::Result<Value> {
+    match x {
+        Value::Ratio(r) => match mode.round_ratio(r) {
+            Some(n) => Ok(n.into()),
+            None => Ok(mode.round_f64(arg_f64(&x, span)?).into()),
+        },
+        Value::Symbolic(s) => {
+            let n = mode.round_f64(s.to_f64());
+            if n.abs() < MAX_EXACT_FLOAT {
+                Ok((n as i64).into())
+            } else {
+                Ok(n.into())
+            }
+        }
+        _ => Ok(mode.round_f64(arg_f64(&x, span)?).into()),
+    }
+}
+
+/// The magnitude below which every integer is exactly representable as a
+/// float.
+const MAX_EXACT_FLOAT: f64 = 9_007_199_254_740_992.0;
+
+/// Round a number to an integer, or to a number of decimal places if
+/// `digits` is given. A negative number of places rounds to a multiple of a
+/// power of ten.
+fn do_round(
+    x: Value,
+    span: Span,
+    digits: Option<(Value, Span)>,
+    mode: Rounding,
+) -> crate::Result<Value> {
+    let digits = match digits {
+        None => return round_int(x, &span, mode),
+        Some((Value::Ratio(n), _)) if n.is_integer() => *n.numer(),
+        Some((_, d_span)) => return Err((Error::Type, d_span)),
+    };
+    if_chain! {
+        if let Some(r) = x.to_big();
+        if digits.unsigned_abs() <= MAX_EXACT_SIZE;
+        then {
+            let scale = BigRational::from_integer(pow(BigInt::from(10), digits.unsigned_abs() as usize));
+            let scale = if digits < 0 { scale.recip() } else { scale };
+            return Ok((mode.round_big(r * &scale) / scale).into());
+        }
+    }
+    let x = arg_f64(&x, &span)?;
+    // Powers of ten beyond the range of floats are infinite
+    let scale = 10.0_f64.powi(digits.unsigned_abs().min(400) as i32);
+    if digits >= 0 {
+        let scaled = x * scale;
+        if !scaled.is_finite() {
+            // There are no digits to round in such a large float
+            return Ok(x.into());
+        }
+        Ok((mode.round_f64(scaled) / scale).into())
+    } else {
+        let n = mode.round_f64(x / scale);
+        Ok(if n == 0.0 { n } else { n * scale }.into())
+    }
+}
+
+/// Get the fractional part of a number.
+fn do_frac(x: Value, span: &Span) -> crate::Result<Value> {
+    if let Value::Ratio(r) = x {
+        if let Some(f) = r.checked_sub(&r.trunc()) {
+            return Ok(f.into());
+        }
+    }
+    Ok(arg_f64(&x, span)?.fract().into())
+}
+
+/// Get the sign of a number.
+fn do_sign(x: Value, span: &Span) -> crate::Result<Value> {
+    match x {
+        Value::Ratio(r) => Ok(r.signum().into()),
+        Value::Symbolic(s) => Ok(s.coef.signum().into()),
+        Value::Quantity(q) => Ok(float_sign(q.value).into()),
+        _ => Ok(float_sign(arg_f64(&x, span)?).into()),
+    }
+}
+
+/// Get the sign of a float, which is zero for zero and NaN for NaN.
+fn float_sign(x: f64) -> f64 {
+    if x == 0.0 || x.is_nan() {
+        x
+    } else {
+        x.signum()
+    }
+}
+
+/// The largest size of a combinatorial calculation on integers.
+const MAX_EXACT_SIZE: u64 = 10_000;
+
+/// The largest `n` for which Stirling numbers are calculated.
+const MAX_STIRLING_SIZE: u64 = 1_000;
+
+/// The largest size of an identity matrix.
+const MAX_IDENTITY_SIZE: i64 = 1_000;
+
+/// The largest number of elements in a range.
+const MAX_RANGE_LEN: usize = 1_000_000;
+
+/// How far short of a whole number of steps the end of a range of floats may
+/// be while still being included.
+const RANGE_TOLERANCE: f64 = 1e-9;
+
+/// Default maximum denominator for `approx`.
+const APPROX_MAX_DEN: i64 = 1000;
+
+/// Convert an argument to a float, reporting a type error at its span.
+fn arg_f64(x: &Value, span: &Span) -> crate::Result<f64> {
+    x.as_f64().ok_or_else(|| (Error::Type, span.clone()))
+}
+
+/// Convert an argument to an exact integer, reporting a type error at its
+/// span.
+fn arg_int(x: &Value, span: &Span) -> crate::Result<i64> {
+    match x {
+        Value::Ratio(r) if r.is_integer() => Ok(*r.numer()),
+        Value::BigRatio(r) if r.is_integer() => Err((Error::Domain, span.clone())),
+        _ => Err((Error::Type, span.clone())),
+    }
+}
+
+/// Find a rational number within a tolerance of `x`.
+///
+/// If no tolerance is given, `x` is assumed to be accurate to about twelve
+/// significant digits.
+fn do_rationalize(x: Value, span: Span, tol: Option<(Value, Span)>) -> crate::Result<Value> {
+    if let Value::Ratio(_) = x {
+        return Ok(x);
+    }
+    let x = arg_f64(&x, &span)?;
+    let tol = match tol {
+        Some((tol, tol_span)) => {
+            let tol = arg_f64(&tol, &tol_span)?;
+            if tol.is_nan() || tol < 0.0 {
+                return Err((Error::Domain, tol_span));
+            }
+            tol
+        }
+        None => 1e-12 * x.abs().max(1.0),
+    };
+    match rationalize(x, tol) {
+        Some(r) => Ok(r.into()),
+        None => Err((Error::Domain, span)),
+    }
+}
+
+/// Find the best rational approximation of `x` with a bounded denominator.
+fn do_approx(x: Value, span: Span, max_den: Option<(Value, Span)>) -> crate::Result<Value> {
+    let max_den = match max_den {
+        Some((Value::Ratio(n), n_span)) => {
+            if !n.is_integer() {
+                return Err((Error::Type, n_span));
+            } else if *n.numer() < 1 {
+                return Err((Error::Domain, n_span));
             }
+            *n.numer()
         }
+        Some((_, n_span)) => return Err((Error::Type, n_span)),
+        None => APPROX_MAX_DEN,
+    };
+    let x = arg_f64(&x, &span)?;
+    match approximate(x, max_den) {
+        Some(r) => Ok(r.into()),
+        None => Err((Error::Domain, span)),
     }
 }
 
@@ -142,6 +1422,74 @@ impl FromStr for Function {
             "asinh" => Ok(Asinh),
             "acosh" => Ok(Acosh),
             "atanh" => Ok(Atanh),
+            "ln" => Ok(Ln),
+            "rationalize" => Ok(Rationalize),
+            "approx" => Ok(Approx),
+            "floor" => Ok(Floor),
+            "ceil" => Ok(Ceil),
+            "round" => Ok(Round),
+            "trunc" => Ok(Trunc),
+            "frac" => Ok(Frac),
+            "sign" => Ok(Sign),
+            "gcd" => Ok(Gcd),
+            "lcm" => Ok(Lcm),
+            "isprime" => Ok(IsPrime),
+            "nextprime" => Ok(NextPrime),
+            "factor" => Ok(Factor),
+            "totient" => Ok(Totient),
+            "modpow" => Ok(ModPow),
+            "modinv" => Ok(ModInv),
+            "fact" => Ok(Fact),
+            "dfact" => Ok(DoubleFact),
+            "choose" => Ok(Choose),
+            "perm" => Ok(Perm),
+            "fib" => Ok(Fib),
+            "catalan" => Ok(Catalan),
+            "stirling1" => Ok(Stirling1),
+            "stirling2" => Ok(Stirling2),
+            "gamma" => Ok(Gamma),
+            "lgamma" => Ok(LnGamma),
+            "beta" => Ok(Beta),
+            "erf" => Ok(Erf),
+            "erfc" => Ok(Erfc),
+            "zeta" => Ok(Zeta),
+            "besselj" => Ok(BesselJ),
+            "bessely" => Ok(BesselY),
+            "len" => Ok(Len),
+            "sum" => Ok(Sum),
+            "prod" => Ok(Prod),
+            "dot" => Ok(Dot),
+            "cross" => Ok(Cross),
+            "norm" => Ok(Norm),
+            "transpose" => Ok(Transpose),
+            "det" => Ok(Det),
+            "inv" => Ok(Inv),
+            "rank" => Ok(Rank),
+            "solve" => Ok(Solve),
+            "identity" => Ok(Identity),
+            "mean" => Ok(Mean),
+            "median" => Ok(Median),
+            "mode" => Ok(Mode),
+            "var" => Ok(Var),
+            "pvar" => Ok(PopVar),
+            "stdev" => Ok(Stdev),
+            "pstdev" => Ok(PopStdev),
+            "quantile" => Ok(Quantile),
+            "min" => Ok(Min),
+            "max" => Ok(Max),
+            "cov" => Ok(Cov),
+            "corr" => Ok(Corr),
+            "linreg" => Ok(LinReg),
+            "range" => Ok(Range),
+            "seq" => Ok(Seq),
+            "map" => Ok(Map),
+            "filter" => Ok(Filter),
+            "reduce" => Ok(Reduce),
+            "compose" => Ok(Compose),
+            "integrate" => Ok(Integrate),
+            "quad" => Ok(Quad),
+            "diff" => Ok(Diff),
+            "simplify" => Ok(Simplify),
             _ => Err(()),
         }
     }
@@ -163,6 +1511,74 @@ impl Display for Function {
             Function::Asinh => "asinh",
             Function::Acosh => "acosh",
             Function::Atanh => "atanh",
+            Function::Ln => "ln",
+            Function::Rationalize => "rationalize",
+            Function::Approx => "approx",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Round => "round",
+            Function::Trunc => "trunc",
+            Function::Frac => "frac",
+            Function::Sign => "sign",
+            Function::Gcd => "gcd",
+            Function::Lcm => "lcm",
+            Function::IsPrime => "isprime",
+            Function::NextPrime => "nextprime",
+            Function::Factor => "factor",
+            Function::Totient => "totient",
+            Function::ModPow => "modpow",
+            Function::ModInv => "modinv",
+            Function::Fact => "fact",
+            Function::DoubleFact => "dfact",
+            Function::Choose => "choose",
+            Function::Perm => "perm",
+            Function::Fib => "fib",
+            Function::Catalan => "catalan",
+            Function::Stirling1 => "stirling1",
+            Function::Stirling2 => "stirling2",
+            Function::Gamma => "gamma",
+            Function::LnGamma => "lgamma",
+            Function::Beta => "beta",
+            Function::Erf => "erf",
+            Function::Erfc => "erfc",
+            Function::Zeta => "zeta",
+            Function::BesselJ => "besselj",
+            Function::BesselY => "bessely",
+            Function::Len => "len",
+            Function::Sum => "sum",
+            Function::Prod => "prod",
+            Function::Dot => "dot",
+            Function::Cross => "cross",
+            Function::Norm => "norm",
+            Function::Transpose => "transpose",
+            Function::Det => "det",
+            Function::Inv => "inv",
+            Function::Rank => "rank",
+            Function::Solve => "solve",
+            Function::Identity => "identity",
+            Function::Mean => "mean",
+            Function::Median => "median",
+            Function::Mode => "mode",
+            Function::Var => "var",
+            Function::PopVar => "pvar",
+            Function::Stdev => "stdev",
+            Function::PopStdev => "pstdev",
+            Function::Quantile => "quantile",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Cov => "cov",
+            Function::Corr => "corr",
+            Function::LinReg => "linreg",
+            Function::Range => "range",
+            Function::Seq => "seq",
+            Function::Map => "map",
+            Function::Filter => "filter",
+            Function::Reduce => "reduce",
+            Function::Compose => "compose",
+            Function::Integrate => "integrate",
+            Function::Quad => "quad",
+            Function::Diff => "diff",
+            Function::Simplify => "simplify",
         };
         write!(f, "{}", s)
     }
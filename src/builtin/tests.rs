@@ -3,7 +3,9 @@ use std::f64::consts;
 use crate::Constant::*;
 use crate::Function::*;
 use crate::Value::*;
-use crate::{AngleMeasure, Context, Span};
+use crate::{
+    AngleMeasure, Constant, Context, Error, Expression, Function, Rounding, Span, Symbolic, Value,
+};
 
 fn sp() -> Span {
     Span {
@@ -14,16 +16,65 @@ fn sp() -> Span {
     }
 }
 
+fn spa(start: u32) -> Span {
+    Span {
+        file: None,
+        line: 1,
+        start,
+        end: start,
+    }
+}
+
 // Constants
 
 #[test]
 fn pi() {
-    assert_eq!(Pi.value(), Float(consts::PI));
+    assert_eq!(Pi.value(), Symbolic::constant(Pi).into());
+    assert_eq!(Pi.to_f64(), consts::PI);
 }
 
 #[test]
 fn e() {
-    assert_eq!(E.value(), Float(consts::E));
+    assert_eq!(E.value(), Symbolic::constant(E).into());
+    assert_eq!(E.to_f64(), consts::E);
+}
+
+#[test]
+fn math_constants() {
+    assert_eq!(Tau.to_f64(), 2.0 * consts::PI);
+    assert_eq!(Phi.value(), Float((1.0 + 5.0_f64.sqrt()) / 2.0));
+    assert_eq!(Sqrt2.value(), Float(2.0_f64.sqrt()));
+    assert_eq!(Inf.value(), Float(f64::INFINITY));
+    match Nan.value() {
+        Float(f) => assert!(f.is_nan()),
+        v => panic!("nan is {:?}", v),
+    }
+}
+
+#[test]
+fn physical_constants() {
+    match SpeedOfLight.value() {
+        Quantity(q) => {
+            assert_eq!(q.value, 299_792_458.0);
+            assert_eq!(q.dim, [1, 0, -1, 0, 0, 0, 0]);
+        }
+        v => panic!("phys.c is {:?}", v),
+    }
+    match Boltzmann.value() {
+        Quantity(q) => assert_eq!(q.dim, [2, 1, -2, 0, -1, 0, 0]),
+        v => panic!("phys.k is {:?}", v),
+    }
+}
+
+#[test]
+fn constant_names() {
+    for con in Constant::ALL.iter() {
+        assert_eq!(con.to_string().parse(), Ok(*con));
+        assert!(!con.description().is_empty());
+    }
+    assert_eq!("egamma".parse(), Ok(EulerGamma));
+    assert_eq!("phys.hbar".parse(), Ok(ReducedPlanck));
+    assert_eq!("phys.x".parse::<Constant>(), Err(()));
 }
 
 // Functions
@@ -55,6 +106,27 @@ fn sin() {
     );
 }
 
+#[test]
+fn exact_trig() {
+    let half_pi = Symbolic {
+        coef: (1, 2).into(),
+        ..Symbolic::constant(Pi)
+    };
+    assert_eq!(Sin.apply(half_pi.into(), &rad(), &sp()), Ok(1.into()));
+    assert_eq!(Cos.apply(half_pi.into(), &rad(), &sp()), Ok(0.into()));
+    assert_eq!(
+        Tan.apply(half_pi.into(), &rad(), &sp()),
+        Err((Error::Domain, sp()))
+    );
+    assert_eq!(Sin.apply(30.into(), &deg(), &sp()), Ok((1, 2).into()));
+    assert_eq!(Cos.apply(180.into(), &deg(), &sp()), Ok((-1).into()));
+    assert_eq!(Sin.apply(0.into(), &rad(), &sp()), Ok(0.0.into()));
+    assert_eq!(
+        Abs.apply((-half_pi).into(), &rad(), &sp()),
+        Ok(half_pi.into())
+    );
+}
+
 #[test]
 fn cos() {
     assert_eq!(
@@ -218,3 +290,843 @@ fn atanh() {
         Ok(0.31_f64.atanh().into())
     );
 }
+
+#[test]
+fn ln() {
+    assert_eq!(Ln.apply(2.into(), &rad(), &sp()), Ok(consts::LN_2.into()));
+    assert_eq!(Ln.apply(1.into(), &rad(), &sp()), Ok(0.into()));
+    assert_eq!(Ln.apply(E.value(), &rad(), &sp()), Ok(1.into()));
+    assert_eq!(
+        Ln.apply(
+            Symbolic::constant(E).pow((1, 2).into()).unwrap().into(),
+            &rad(),
+            &sp()
+        ),
+        Ok((1, 2).into())
+    );
+    assert_eq!(
+        Ln.apply((-1).into(), &rad(), &sp()),
+        Err((Error::Domain, sp()))
+    );
+}
+
+// Rational approximation
+
+#[test]
+fn rounding() {
+    let x = || Value::from((-7, 2));
+    assert_eq!(Floor.apply(x(), &rad(), &sp()), Ok((-4).into()));
+    assert_eq!(Ceil.apply(x(), &rad(), &sp()), Ok((-3).into()));
+    assert_eq!(Trunc.apply(x(), &rad(), &sp()), Ok((-3).into()));
+    assert_eq!(Round.apply(x(), &rad(), &sp()), Ok((-4).into()));
+    assert_eq!(Round.apply((5, 2).into(), &rad(), &sp()), Ok(2.into()));
+    let half_away = Context {
+        round_ties: Rounding::HalfAwayFromZero,
+        ..Default::default()
+    };
+    assert_eq!(Round.apply((5, 2).into(), &half_away, &sp()), Ok(3.into()));
+    // The display rounding mode does not affect `round`
+    let floor = Context {
+        rounding: Rounding::Floor,
+        ..Default::default()
+    };
+    assert_eq!(Round.apply((7, 2).into(), &floor, &sp()), Ok(4.into()));
+    assert_eq!(Floor.apply((-2.5).into(), &rad(), &sp()), Ok((-3.0).into()));
+    assert_eq!(Floor.apply(Pi.value(), &rad(), &sp()), Ok(3.into()));
+}
+
+#[test]
+fn round_digits() {
+    let round = |x: Value, d: Value| Round.apply_args(vec![(x, sp()), (d, spa(5))], &rad(), &sp());
+    assert_eq!(round((1, 3).into(), 2.into()), Ok((33, 100).into()));
+    assert_eq!(round((-1, 3).into(), 2.into()), Ok((-33, 100).into()));
+    assert_eq!(round(1234.into(), (-2).into()), Ok(1200.into()));
+    assert_eq!(round(1.23456.into(), 3.into()), Ok(1.235.into()));
+    assert_eq!(round(1.0.into(), 0.5.into()), Err((Error::Type, spa(5))));
+}
+
+#[test]
+fn frac_sign() {
+    assert_eq!(
+        Frac.apply((-7, 2).into(), &rad(), &sp()),
+        Ok((-1, 2).into())
+    );
+    assert_eq!(Frac.apply(2.25.into(), &rad(), &sp()), Ok(0.25.into()));
+    assert_eq!(Sign.apply((-7, 2).into(), &rad(), &sp()), Ok((-1).into()));
+    assert_eq!(Sign.apply(0.into(), &rad(), &sp()), Ok(0.into()));
+    assert_eq!(Sign.apply(0.0.into(), &rad(), &sp()), Ok(0.0.into()));
+    assert_eq!(Sign.apply(2.5.into(), &rad(), &sp()), Ok(1.0.into()));
+}
+
+#[test]
+fn number_theory() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    assert_eq!(
+        call(Gcd, vec![12.into(), 18.into(), 8.into()]),
+        Ok(2.into())
+    );
+    assert_eq!(call(Lcm, vec![4.into(), 6.into()]), Ok(12.into()));
+    assert_eq!(call(IsPrime, vec![97.into()]), Ok(1.into()));
+    assert_eq!(call(IsPrime, vec![91.into()]), Ok(0.into()));
+    assert_eq!(call(NextPrime, vec![97.into()]), Ok(101.into()));
+    assert_eq!(
+        call(Factor, vec![360.into()]),
+        Ok(Factors(vec![(2, 3), (3, 2), (5, 1)]))
+    );
+    assert_eq!(call(Totient, vec![36.into()]), Ok(12.into()));
+    assert_eq!(
+        call(ModPow, vec![4.into(), 13.into(), 497.into()]),
+        Ok(445.into())
+    );
+    assert_eq!(call(ModInv, vec![3.into(), 11.into()]), Ok(4.into()));
+    assert_eq!(
+        call(Gcd, vec![12.into(), 1.5.into()]),
+        Err((Error::Type, spa(1)))
+    );
+    assert_eq!(
+        call(Lcm, vec![(1, 2).into(), 3.into()]),
+        Err((Error::Type, spa(0)))
+    );
+    assert_eq!(call(Factor, vec![0.into()]), Err((Error::Domain, spa(0))));
+    assert_eq!(
+        call(ModInv, vec![6.into(), 9.into()]),
+        Err((Error::Domain, sp()))
+    );
+    assert_eq!(
+        call(ModPow, vec![2.into(), 3.into(), 0.into()]),
+        Err((Error::Domain, spa(2)))
+    );
+    assert_eq!(call(ModInv, vec![6.into()]), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn combinatorics() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    assert_eq!(call(Fact, vec![5.into()]), Ok(120.into()));
+    assert_eq!(call(DoubleFact, vec![7.into()]), Ok(105.into()));
+    assert_eq!(call(DoubleFact, vec![(-5).into()]), Ok((1, 3).into()));
+    assert_eq!(call(Choose, vec![10.into(), 3.into()]), Ok(120.into()));
+    assert_eq!(call(Perm, vec![10.into(), 3.into()]), Ok(720.into()));
+    assert_eq!(
+        call(Fib, vec![90.into()]),
+        Ok(2_880_067_194_370_816_120.into())
+    );
+    assert_eq!(call(Catalan, vec![10.into()]), Ok(16796.into()));
+    assert_eq!(call(Stirling1, vec![5.into(), 2.into()]), Ok(50.into()));
+    assert_eq!(call(Stirling2, vec![5.into(), 2.into()]), Ok(15.into()));
+    match call(Fact, vec![25.into()]) {
+        Ok(BigRatio(r)) => assert_eq!(r.to_string(), "15511210043330985984000000"),
+        x => panic!("expected a big rational, got {:?}", x),
+    }
+    let half = call(Fact, vec![0.5.into()]).unwrap().as_f64().unwrap();
+    assert!((half - consts::PI.sqrt() / 2.0).abs() < 1e-14);
+    assert_eq!(call(Fact, vec![(-1).into()]), Err((Error::Domain, spa(0))));
+    assert_eq!(
+        call(DoubleFact, vec![(-2).into()]),
+        Err((Error::Domain, spa(0)))
+    );
+    assert_eq!(
+        call(Perm, vec![5.into(), (-1).into()]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(
+        call(Stirling2, vec![5.into(), 1.5.into()]),
+        Err((Error::Type, spa(1)))
+    );
+    assert_eq!(
+        call(Fact, vec![20_000.into()]),
+        Err((Error::Domain, spa(0)))
+    );
+    assert_eq!(call(Choose, vec![5.into()]), Err((Error::Syntax, sp())));
+}
+
+/// Apply a special function to floats, returning a float.
+fn special(f: Function, args: &[f64]) -> f64 {
+    let args = args
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let x = if x.fract() == 0.0 {
+                Value::from(x as i64)
+            } else {
+                Value::from(x)
+            };
+            (x, spa(i as u32))
+        })
+        .collect();
+    f.apply_args(args, &rad(), &sp())
+        .expect("special function failed")
+        .as_f64()
+        .unwrap()
+}
+
+/// Check a special function against a table of reference values, with the
+/// given relative tolerance, or absolute tolerance for values below 1.
+fn check_table(f: Function, table: &[(&[f64], f64)], tol: f64) {
+    for &(args, expected) in table {
+        let actual = special(f, args);
+        let err = (actual - expected).abs() / expected.abs().max(1.0);
+        assert!(
+            err <= tol,
+            "{}{:?} = {}, expected {} (error {:e})",
+            f,
+            args,
+            actual,
+            expected,
+            err
+        );
+    }
+}
+
+#[test]
+fn gamma_table() {
+    let table: &[(&[f64], f64)] = &[
+        (&[0.5], 1.772453850905516),
+        (&[1.5], 0.886226925452758),
+        (&[2.5], 1.329340388179137),
+        (&[-0.5], -3.544907701811032),
+        (&[-1.5], 2.363271801207355),
+        (&[3.7], 4.170651783796603),
+        (&[10.1], 454760.75144158595),
+        (&[50.5], 4.29046291235196e+63),
+        (&[0.001], 999.4237724845955),
+        (&[-2.3], -1.4471073942559172),
+        (&[170.5], 5.56209241456e+305),
+        (&[6.0], 120.0),
+    ];
+    check_table(Gamma, table, 2e-13);
+    let table: &[(&[f64], f64)] = &[
+        (&[0.5], 0.5723649429247001),
+        (&[1.0], 0.0),
+        (&[1.5], -0.12078223763524522),
+        (&[3.0], consts::LN_2),
+        (&[10.1], 13.027526738633238),
+        (&[100.0], 359.1342053695754),
+        (&[1000.5], 5908.674175848678),
+        (&[-2.5], -0.056243716497674054),
+        (&[1e-5], 11.512919692895826),
+    ];
+    check_table(LnGamma, table, 1e-14);
+    let table: &[(&[f64], f64)] = &[
+        (&[2.0, 3.0], 0.08333333333333333),
+        (&[0.5, 0.5], consts::PI),
+        (&[1.5, 2.5], 0.19634954084936207),
+        (&[100.0, 100.0], 2.2087606931995024e-61),
+        (&[-0.5, 2.0], -4.0),
+        (&[-1.5, -0.5], 0.0),
+    ];
+    check_table(Beta, table, 1e-13);
+}
+
+#[test]
+fn erf_table() {
+    let table = [
+        (0.0, 0.0, 1.0),
+        (0.1, 0.1124629160182849, 0.8875370839817152),
+        (0.5, 0.5204998778130465, 0.4795001221869535),
+        (1.0, 0.8427007929497149, 0.15729920705028513),
+        (1.5, 0.9661051464753108, 0.033894853524689274),
+        (1.99, 0.995111413199617, 0.004888586800383002),
+        (2.0, 0.9953222650189527, 0.004677734981047266),
+        (2.5, 0.999593047982555, 0.0004069520174449589),
+        (3.0, 0.9999779095030014, 2.209049699858544e-05),
+        (5.0, 0.9999999999984626, 1.537459794428035e-12),
+        (-1.0, -0.8427007929497149, 1.8427007929497148),
+        (-3.0, -0.9999779095030014, 1.9999779095030015),
+        (6.0, 1.0, 2.1519736712498913e-17),
+        (10.0, 1.0, 2.088487583762545e-45),
+        (20.0, 1.0, 5.395865611607901e-176),
+    ];
+    for &(x, erf, erfc) in &table {
+        let rel = |a: f64, b: f64| {
+            if b == 0.0 {
+                a.abs()
+            } else {
+                (a - b).abs() / b.abs()
+            }
+        };
+        assert!(rel(special(Erf, &[x]), erf) <= 1e-14, "erf({})", x);
+        assert!(rel(special(Erfc, &[x]), erfc) <= 1e-13, "erfc({})", x);
+    }
+}
+
+#[test]
+fn zeta_table() {
+    let table: &[(&[f64], f64)] = &[
+        (&[2.0], 1.6449340668482264),
+        (&[3.0], 1.2020569031595942),
+        (&[0.5], -1.4603545088095868),
+        (&[0.0], -0.5),
+        (&[-1.0], -0.08333333333333333),
+        (&[-2.0], 0.0),
+        (&[-3.5], 0.004441011335479432),
+        (&[1.5], 2.612375348685488),
+        (&[10.0], 1.000994575127818),
+        (&[50.0], 1.0000000000000009),
+        (&[-0.5], -0.20788622497735457),
+        (&[20.5], 1.0000006745156182),
+        (&[-10.5], 0.011146122473942813),
+    ];
+    check_table(Zeta, table, 1e-14);
+}
+
+#[test]
+fn bessel_table() {
+    // (n, x, J_n(x), Y_n(x))
+    let table = [
+        (0, 0.1, 0.99750156206604, -1.5342386513503667),
+        (0, 1.0, 0.7651976865579666, 0.08825696421567696),
+        (0, 2.5, -0.048383776468198, 0.4980703596152319),
+        (0, 10.0, -0.24593576445134835, 0.055671167283599395),
+        (0, 24.9, 0.08324596835301568, -0.1364991839967651),
+        (0, 25.0, 0.09626678327595811, -0.12724943226800614),
+        (0, 100.0, 0.019985850304223122, -0.07724431336508315),
+        (0, 1000.0, 0.024786686152420176, 0.0047159179776228135),
+        (1, 0.1, 0.049937526036242, -6.4589510947020266),
+        (1, 1.0, 0.4400505857449335, -0.7812128213002887),
+        (1, 10.0, 0.04347274616886144, 0.24901542420695388),
+        (1, 24.9, -0.13485569953140875, -0.08600255759555445),
+        (1, 30.0, -0.11875106261662294, 0.08442557066174723),
+        (2, 0.1, 0.0012489586587999188, -127.64478324269017),
+        (2, 2.5, 0.44605905843961724, -0.38133584924180325),
+        (2, 25.0, -0.1062948032423813, 0.11934303508534715),
+        (5, 0.1, 2.603081790964441e-09, -24461484.502303917),
+        (5, 1.0, 0.00024975773021123444, -260.4058666258122),
+        (5, 10.0, -0.23406152818679363, 0.13540304768936232),
+        (5, 30.0, -0.14324029551207706, 0.03162735928926443),
+        (5, 1000.0, 0.0050254069452331865, -0.02472595671974069),
+        (-3, 2.5, -0.21660039103911352, 0.756055496753671),
+        (-3, 100.0, -0.07628420172033194, -0.02344578668776091),
+    ];
+    for &(n, x, j, y) in &table {
+        let n = n as f64;
+        let err = |a: f64, b: f64| (a - b).abs() / b.abs().max(1.0);
+        assert!(err(special(BesselJ, &[n, x]), j) <= 1e-14, "J_{}({})", n, x);
+        assert!(err(special(BesselY, &[n, x]), y) <= 1e-14, "Y_{}({})", n, x);
+    }
+    let table: &[(&[f64], f64)] = &[
+        (&[30.0, 10.0], 1.551096078257467e-12),
+        (&[40.0, 50.0], -0.13817628120116143),
+        (&[60.0, 50.0], 0.0010485195995314181),
+        (&[3.0, -2.0], -0.12894324947440206),
+    ];
+    check_table(BesselJ, table, 1e-14);
+    assert!((special(BesselY, &[10.0, 3.0]) / -2582.6071294842995 - 1.0).abs() <= 1e-13);
+}
+
+#[test]
+fn special_poles() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    assert_eq!(call(Gamma, vec![0.into()]), Err((Error::Domain, spa(0))));
+    assert_eq!(
+        call(Gamma, vec![(-3.0).into()]),
+        Err((Error::Domain, spa(0)))
+    );
+    assert_eq!(
+        call(LnGamma, vec![(-1).into()]),
+        Err((Error::Domain, spa(0)))
+    );
+    assert_eq!(
+        call(Beta, vec![1.into(), (-2).into()]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(call(Zeta, vec![1.into()]), Err((Error::Domain, spa(0))));
+    assert_eq!(
+        call(BesselY, vec![0.into(), 0.into()]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(
+        call(BesselJ, vec![0.5.into(), 1.into()]),
+        Err((Error::Type, spa(0)))
+    );
+    assert_eq!(
+        call(Erf, vec![1.into(), 2.into()]),
+        Err((Error::Syntax, sp()))
+    );
+}
+
+#[test]
+fn lists() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    let list = |v: &[i64]| List(v.iter().map(|&x| x.into()).collect());
+    assert_eq!(call(Len, vec![list(&[1, 2, 3])]), Ok(3.into()));
+    assert_eq!(call(Sum, vec![list(&[1, 2, 3])]), Ok(6.into()));
+    assert_eq!(call(Sum, vec![list(&[])]), Ok(0.into()));
+    assert_eq!(call(Prod, vec![list(&[2, 3, 4])]), Ok(24.into()));
+    assert_eq!(call(Prod, vec![list(&[])]), Ok(1.into()));
+    assert_eq!(
+        call(Sum, vec![List(vec![(1, 2).into(), (1, 3).into()])]),
+        Ok((5, 6).into())
+    );
+    assert_eq!(
+        call(Dot, vec![list(&[1, 2, 3]), list(&[4, 5, 6])]),
+        Ok(32.into())
+    );
+    assert_eq!(
+        call(Cross, vec![list(&[1, 2, 3]), list(&[4, 5, 6])]),
+        Ok(list(&[-3, 6, -3]))
+    );
+    assert_eq!(call(Norm, vec![list(&[3, 4])]), Ok(5.0.into()));
+    assert_eq!(Abs.apply(list(&[-1, 2]), &rad(), &sp()), Ok(list(&[1, 2])));
+    assert_eq!(
+        call(Dot, vec![list(&[1, 2]), list(&[1, 2, 3])]),
+        Err((Error::Length, spa(1)))
+    );
+    assert_eq!(
+        call(Cross, vec![list(&[1, 2, 3]), list(&[1, 2])]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(call(Sum, vec![1.into()]), Err((Error::Type, spa(0))));
+    assert_eq!(
+        call(Len, vec![list(&[]), list(&[])]),
+        Err((Error::Syntax, sp()))
+    );
+}
+
+#[test]
+fn matrices() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    let ints = |rows: &[&[i64]]| {
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().map(|&x| x.into()).collect())
+            .collect();
+        Value::Matrix(crate::Matrix::from_rows(rows).unwrap())
+    };
+    let list = |v: &[i64]| List(v.iter().map(|&x| x.into()).collect());
+    let a = || ints(&[&[1, 2], &[3, 4]]);
+    assert_eq!(call(Det, vec![a()]), Ok((-2).into()));
+    assert_eq!(call(Rank, vec![a()]), Ok(2.into()));
+    assert_eq!(call(Transpose, vec![a()]), Ok(ints(&[&[1, 3], &[2, 4]])));
+    assert_eq!(
+        call(Transpose, vec![list(&[1, 2])]),
+        Ok(ints(&[&[1], &[2]]))
+    );
+    assert_eq!(
+        call(Identity, vec![2.into()]),
+        Ok(ints(&[&[1, 0], &[0, 1]]))
+    );
+    assert_eq!(
+        call(Inv, vec![ints(&[&[2, 0], &[0, 4]])]),
+        Ok(Value::Matrix(
+            crate::Matrix::from_rows(vec![
+                vec![(1, 2).into(), 0.into()],
+                vec![0.into(), (1, 4).into()],
+            ])
+            .unwrap()
+        ))
+    );
+    assert_eq!(call(Solve, vec![a(), list(&[5, 11])]), Ok(list(&[1, 2])));
+    assert_eq!(
+        call(Solve, vec![a(), ints(&[&[5], &[11]])]),
+        Ok(ints(&[&[1], &[2]]))
+    );
+    assert_eq!(
+        Abs.apply(ints(&[&[-1, 2]]), &rad(), &sp()),
+        Ok(ints(&[&[1, 2]]))
+    );
+    let singular = || ints(&[&[1, 2], &[2, 4]]);
+    assert_eq!(call(Inv, vec![singular()]), Err((Error::Domain, spa(0))));
+    assert_eq!(
+        call(Solve, vec![singular(), list(&[1, 2])]),
+        Err((Error::Domain, spa(0)))
+    );
+    assert_eq!(
+        call(Solve, vec![a(), list(&[1, 2, 3])]),
+        Err((Error::Shape, spa(1)))
+    );
+    assert_eq!(
+        call(Det, vec![ints(&[&[1, 2, 3]])]),
+        Err((Error::Shape, spa(0)))
+    );
+    assert_eq!(call(Det, vec![list(&[1])]), Err((Error::Type, spa(0))));
+    assert_eq!(call(Identity, vec![0.into()]), Err((Error::Domain, spa(0))));
+}
+
+#[test]
+fn stats() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    let list = |v: &[i64]| List(v.iter().map(|&x| x.into()).collect());
+    assert_eq!(call(Mean, vec![list(&[1, 2, 4])]), Ok((7, 3).into()));
+    assert_eq!(call(Median, vec![list(&[5, 1, 3])]), Ok(3.into()));
+    assert_eq!(call(PopVar, vec![list(&[1, 3])]), Ok(1.into()));
+    assert_eq!(call(Stdev, vec![list(&[1, 3])]), Ok(2f64.sqrt().into()));
+    assert_eq!(call(Min, vec![list(&[3, -1, 2])]), Ok((-1).into()));
+    assert_eq!(call(Max, vec![3.into(), 7.into(), 5.into()]), Ok(7.into()));
+    assert_eq!(
+        call(Quantile, vec![list(&[1, 2, 3]), (1, 4).into()]),
+        Ok((3, 2).into())
+    );
+    assert_eq!(
+        call(LinReg, vec![list(&[0, 1, 2]), list(&[1, 3, 5])]),
+        Ok(list(&[2, 1]))
+    );
+    assert_eq!(call(Mean, vec![list(&[])]), Err((Error::Domain, spa(0))));
+    assert_eq!(call(Var, vec![list(&[1])]), Err((Error::Domain, spa(0))));
+    assert_eq!(call(Mean, vec![1.into()]), Err((Error::Type, spa(0))));
+    assert_eq!(
+        call(Mean, vec![List(vec![list(&[1])])]),
+        Err((Error::Type, spa(0)))
+    );
+    assert_eq!(
+        call(Quantile, vec![list(&[1, 2]), 2.into()]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(
+        call(Cov, vec![list(&[1, 2]), list(&[1])]),
+        Err((Error::Length, spa(1)))
+    );
+    assert_eq!(
+        call(Corr, vec![list(&[1, 2]), list(&[1, 1])]),
+        Err((Error::Domain, spa(0)))
+    );
+}
+
+#[test]
+fn ranges() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    let list = |v: &[i64]| List(v.iter().map(|&x| x.into()).collect());
+    assert_eq!(
+        call(Range, vec![1.into(), 4.into()]),
+        Ok(list(&[1, 2, 3, 4]))
+    );
+    assert_eq!(call(Range, vec![3.into(), 1.into()]), Ok(list(&[])));
+    assert_eq!(
+        call(Range, vec![9.into(), 0.into(), (-4).into()]),
+        Ok(list(&[9, 5, 1]))
+    );
+    assert_eq!(
+        call(Range, vec![0.into(), 1.into(), (1, 3).into()]),
+        Ok(List(vec![0.into(), (1, 3).into(), (2, 3).into(), 1.into()]))
+    );
+    assert_eq!(
+        call(Range, vec![0.into(), 1.into(), 0.5.into()]),
+        Ok(List(vec![0.0.into(), 0.5.into(), 1.0.into()]))
+    );
+    assert_eq!(
+        call(Range, vec![0.into(), 1.into(), 0.into()]),
+        Err((Error::Domain, spa(2)))
+    );
+    assert_eq!(
+        call(Range, vec![0.into(), i64::MAX.into()]),
+        Err((Error::Domain, sp()))
+    );
+    assert_eq!(
+        call(Range, vec![List(vec![]), 1.into()]),
+        Err((Error::Type, spa(0)))
+    );
+    let square = Value::Func(
+        vec!["x".to_string()],
+        Box::new((
+            Expression::Exp(Box::new([
+                (Expression::Var("x".to_string()), sp()),
+                (2.into(), sp()),
+            ])),
+            sp(),
+        )),
+    );
+    assert_eq!(
+        call(Seq, vec![square.clone(), 1.into(), 3.into()]),
+        Ok(list(&[1, 4, 9]))
+    );
+    assert_eq!(
+        call(Seq, vec![square, 5.into(), 1.into(), (-2).into()]),
+        Ok(list(&[25, 9, 1]))
+    );
+    assert_eq!(
+        call(Seq, vec![2.into(), 1.into(), 3.into()]),
+        Err((Error::Type, spa(0)))
+    );
+}
+
+#[test]
+fn higher_order() {
+    let call = |f: Function, args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        f.apply_args(args, &rad(), &sp())
+    };
+    let list = |v: &[i64]| List(v.iter().map(|&x| x.into()).collect());
+    let var = |s: &str| (Expression::Var(s.to_string()), sp());
+    let func = |params: &[&str], body: Expression| {
+        let params = params.iter().map(|s| s.to_string()).collect();
+        Value::Func(params, Box::new((body, sp())))
+    };
+    let double = || func(&["x"], Expression::Mul(vec![var("x"), (2.into(), sp())]));
+    let add = || func(&["a", "b"], Expression::Add(vec![var("a"), var("b")]));
+    assert_eq!(
+        call(Map, vec![double(), list(&[1, 2, 3])]),
+        Ok(list(&[2, 4, 6]))
+    );
+    assert_eq!(
+        call(Map, vec![Builtin(Abs), list(&[-1, 2])]),
+        Ok(list(&[1, 2]))
+    );
+    assert_eq!(
+        call(Filter, vec![Builtin(IsPrime), list(&[4, 5, 6, 7])]),
+        Ok(list(&[5, 7]))
+    );
+    assert_eq!(call(Reduce, vec![add(), list(&[1, 2, 3])]), Ok(6.into()));
+    assert_eq!(
+        call(Reduce, vec![add(), list(&[]), 10.into()]),
+        Ok(10.into())
+    );
+    let composed = call(Compose, vec![Builtin(Abs), double()]).unwrap();
+    assert_eq!(call(Map, vec![composed, list(&[-1, 3])]), Ok(list(&[2, 6])));
+    let composed = call(Compose, vec![double(), add()]).unwrap();
+    assert_eq!(
+        composed,
+        func(
+            &["a", "b"],
+            Expression::Mul(vec![
+                (Expression::Add(vec![var("a"), var("b")]), sp()),
+                (2.into(), sp())
+            ])
+        )
+    );
+    assert_eq!(
+        call(Reduce, vec![add(), list(&[])]),
+        Err((Error::Domain, spa(1)))
+    );
+    assert_eq!(
+        call(Map, vec![1.into(), list(&[1])]),
+        Err((Error::Type, spa(0)))
+    );
+    assert_eq!(
+        call(Map, vec![double(), 1.into()]),
+        Err((Error::Type, spa(1)))
+    );
+    assert_eq!(
+        call(Filter, vec![double(), List(vec![List(vec![])])]),
+        Err((Error::Type, spa(0)))
+    );
+    assert_eq!(
+        call(Compose, vec![add(), double()]),
+        Err((Error::Type, spa(0)))
+    );
+}
+
+#[test]
+fn integrate() {
+    let call = |args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        Integrate.apply_args(args, &rad(), &sp())
+    };
+    let square = Value::Func(
+        vec!["x".to_string()],
+        Box::new((
+            Expression::Mul(vec![
+                (Expression::Var("x".to_string()), sp()),
+                (Expression::Var("x".to_string()), sp()),
+            ]),
+            sp(),
+        )),
+    );
+    let approx = |x: crate::Result<Value>, y: f64| {
+        assert!((x.unwrap().as_f64().unwrap() - y).abs() < 1e-12);
+    };
+    approx(call(vec![square.clone(), 0.into(), 3.into()]), 9.0);
+    approx(
+        call(vec![Builtin(Cos), 0.into(), (consts::PI / 2.0).into()]),
+        1.0,
+    );
+    approx(call(vec![Builtin(Atan), 1.into(), (-1).into()]), 0.0);
+    assert_eq!(
+        call(vec![square, List(vec![]), 1.into()]),
+        Err((Error::Type, spa(1)))
+    );
+    let reciprocal = Value::Func(
+        vec!["x".to_string()],
+        Box::new((
+            Expression::Frac(Box::new([
+                (1.into(), sp()),
+                (Expression::Var("x".to_string()), sp()),
+            ])),
+            sp(),
+        )),
+    );
+    assert_eq!(
+        call(vec![reciprocal, 0.into(), 1.into()]),
+        Err((Error::Convergence, sp()))
+    );
+}
+
+#[test]
+fn solve_function() {
+    let call = |args: Vec<Value>| {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| (x, spa(i as u32)))
+            .collect();
+        Solve.apply_args(args, &rad(), &sp())
+    };
+    // x^2 - 2
+    let f = Value::Func(
+        vec!["x".to_string()],
+        Box::new((
+            Expression::Sub(Box::new([
+                (
+                    Expression::Exp(Box::new([
+                        (Expression::Var("x".to_string()), sp()),
+                        (2.into(), sp()),
+                    ])),
+                    sp(),
+                ),
+                (2.into(), sp()),
+            ])),
+            sp(),
+        )),
+    );
+    let approx = |x: crate::Result<Value>, y: f64| {
+        assert!((x.unwrap().as_f64().unwrap() - y).abs() < 1e-12);
+    };
+    approx(call(vec![f.clone(), 0.into(), 2.into()]), consts::SQRT_2);
+    approx(call(vec![f.clone(), (-1).into()]), -consts::SQRT_2);
+    approx(call(vec![f.clone()]), consts::SQRT_2);
+    approx(call(vec![Builtin(Cos), 1.into()]), consts::FRAC_PI_2);
+    assert_eq!(
+        call(vec![f.clone(), 2.into(), 3.into()]),
+        Err((Error::Domain, sp()))
+    );
+    assert_eq!(
+        call(vec![f.clone(), List(vec![])]),
+        Err((Error::Type, spa(1)))
+    );
+    assert_eq!(
+        call(vec![f, 0.into(), 1.into(), 2.into()]),
+        Err((Error::Syntax, sp()))
+    );
+    assert_eq!(
+        call(vec![Builtin(Cosh), 0.into()]),
+        Err((Error::Convergence, sp()))
+    );
+}
+
+#[test]
+fn rationalize() {
+    assert_eq!(
+        Rationalize.apply(0.75.into(), &rad(), &sp()),
+        Ok((3, 4).into())
+    );
+    assert_eq!(
+        Rationalize.apply((2, 3).into(), &rad(), &sp()),
+        Ok((2, 3).into())
+    );
+    assert_eq!(
+        Rationalize.apply_args(
+            vec![(consts::PI.into(), sp()), (0.01.into(), sp())],
+            &rad(),
+            &sp()
+        ),
+        Ok((22, 7).into())
+    );
+    assert_eq!(
+        Rationalize.apply(f64::NAN.into(), &rad(), &sp()),
+        Err((Error::Domain, sp()))
+    );
+    assert_eq!(
+        Rationalize.apply_args(
+            vec![(1.5.into(), sp()), ((-1).into(), spa(5))],
+            &rad(),
+            &sp()
+        ),
+        Err((Error::Domain, spa(5)))
+    );
+}
+
+#[test]
+fn approx() {
+    assert_eq!(
+        Approx.apply((1.0 / 3.0).into(), &rad(), &sp()),
+        Ok((1, 3).into())
+    );
+    assert_eq!(
+        Approx.apply_args(
+            vec![(consts::PI.into(), sp()), (100.into(), sp())],
+            &rad(),
+            &sp()
+        ),
+        Ok((311, 99).into())
+    );
+    assert_eq!(
+        Approx.apply_args(
+            vec![((355, 113).into(), sp()), (10.into(), sp())],
+            &rad(),
+            &sp()
+        ),
+        Ok((22, 7).into())
+    );
+    assert_eq!(
+        Approx.apply_args(
+            vec![(1.5.into(), sp()), (2.5.into(), spa(5))],
+            &rad(),
+            &sp()
+        ),
+        Err((Error::Type, spa(5)))
+    );
+    assert_eq!(
+        Approx.apply_args(vec![(1.5.into(), sp()), (0.into(), spa(5))], &rad(), &sp()),
+        Err((Error::Domain, spa(5)))
+    );
+    assert_eq!(
+        Approx.apply_args(vec![], &rad(), &sp()),
+        Err((Error::Syntax, sp()))
+    );
+}
@@ -0,0 +1,155 @@
+//! Exact combinatorial functions on integers, and their extensions to real
+//! numbers.
+
+use std::f64::consts;
+
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::traits::{One, Zero};
+
+use crate::gamma;
+
+#[cfg(test)]
+mod tests;
+
+/// Get the falling factorial `n (n - 1) ... (n - k + 1)`, which is the
+/// number of ways to arrange `k` of `n` items.
+pub fn falling(n: i64, k: u64) -> BigInt {
+    let n = BigInt::from(n);
+    (0..k).fold(BigInt::one(), |acc, i| acc * (&n - i))
+}
+
+/// Get the factorial `n!`.
+pub fn factorial(n: u64) -> BigInt {
+    (2..=n).fold(BigInt::one(), |acc, i| acc * i)
+}
+
+/// Get the double factorial `n!! = n (n - 2) (n - 4) ...`.
+///
+/// Negative odd numbers are defined by `n!! = (n + 2)!! / (n + 2)`, so that
+/// `(-1)!! = 1` and `(-5)!! = 1/3`. Returns `None` for negative even numbers,
+/// which are poles.
+pub fn double_factorial(n: i64) -> Option<BigRational> {
+    if n >= -1 {
+        let n = n.max(0) as u64;
+        let prod = (1..=n)
+            .rev()
+            .step_by(2)
+            .fold(BigInt::one(), |acc, i| acc * i);
+        Some(BigRational::from_integer(prod))
+    } else if n % 2 != 0 {
+        // (-2m - 1)!! = (-1)^m / (2m - 1)!!
+        let m = (-n - 1) / 2;
+        let denom = double_factorial(2 * m - 1)?;
+        let sign = if m % 2 == 0 { 1 } else { -1 };
+        Some(BigRational::from_integer(sign.into()) / denom)
+    } else {
+        None
+    }
+}
+
+/// Get the binomial coefficient `n choose k` for any integer `n`.
+pub fn binomial(n: i64, k: i64) -> BigInt {
+    if k < 0 || (n >= 0 && k > n) {
+        return BigInt::zero();
+    }
+    let k = if n >= 0 { k.min(n - k) } else { k } as u64;
+    falling(n, k) / factorial(k)
+}
+
+/// Get the Fibonacci number `F(n)`, where `F(-n) = (-1)^(n + 1) F(n)`.
+pub fn fibonacci(n: i64) -> BigInt {
+    // Fast doubling: (F(k), F(k + 1)) -> (F(2k), F(2k + 1))
+    let m = n.unsigned_abs();
+    let (mut a, mut b) = (BigInt::zero(), BigInt::one());
+    for bit in (0..64 - m.leading_zeros()).rev() {
+        let c = &a * (&b * 2 - &a);
+        let d = &a * &a + &b * &b;
+        if m >> bit & 1 == 1 {
+            a = d.clone();
+            b = c + d;
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    if n < 0 && m.is_multiple_of(2) {
+        -a
+    } else {
+        a
+    }
+}
+
+/// Get the Catalan number `C(n) = (2n choose n) / (n + 1)`.
+pub fn catalan(n: u64) -> BigInt {
+    let n = n as i64;
+    binomial(2 * n, n) / (n + 1)
+}
+
+/// Get the unsigned Stirling number of the first kind, i.e., the number of
+/// permutations of `n` items with `k` cycles.
+pub fn stirling1(n: u64, k: u64) -> BigInt {
+    // row[j] holds the number for the current n and k = j
+    let mut row = vec![BigInt::one()];
+    for i in 0..n {
+        let mut next = vec![BigInt::zero(); row.len() + 1];
+        for (j, x) in row.iter().enumerate() {
+            next[j] += x * i;
+            next[j + 1] += x;
+        }
+        row = next;
+    }
+    row.into_iter().nth(k as usize).unwrap_or_else(BigInt::zero)
+}
+
+/// Get the Stirling number of the second kind, i.e., the number of ways to
+/// partition `n` items into `k` non-empty subsets.
+pub fn stirling2(n: u64, k: u64) -> BigInt {
+    if k > n {
+        return BigInt::zero();
+    }
+    // row[j] holds the number for the current n and k = j
+    let mut row = vec![BigInt::one()];
+    for _ in 0..n {
+        let mut next = vec![BigInt::zero(); row.len() + 1];
+        for (j, x) in row.iter().enumerate() {
+            next[j] += x * j;
+            next[j + 1] += x;
+        }
+        row = next;
+    }
+    row.swap_remove(k as usize)
+}
+
+/// Get the factorial of a real number, `x! = gamma(x + 1)`.
+pub fn factorial_f64(x: f64) -> f64 {
+    gamma(x + 1.0)
+}
+
+/// Get the double factorial of a real number, using the extension that
+/// agrees with the integer double factorial at odd integers.
+pub fn double_factorial_f64(x: f64) -> f64 {
+    let c = (consts::PI * x).cos();
+    2_f64.powf((1.0 + 2.0 * x - c) / 4.0) * consts::PI.powf((c - 1.0) / 4.0) * gamma(x / 2.0 + 1.0)
+}
+
+/// Get the binomial coefficient of real numbers.
+pub fn binomial_f64(x: f64, y: f64) -> f64 {
+    gamma(x + 1.0) / (gamma(y + 1.0) * gamma(x - y + 1.0))
+}
+
+/// Get the falling factorial of real numbers.
+pub fn falling_f64(x: f64, y: f64) -> f64 {
+    gamma(x + 1.0) / gamma(x - y + 1.0)
+}
+
+/// Get the Fibonacci number of a real number, using Binet's formula.
+pub fn fibonacci_f64(x: f64) -> f64 {
+    let phi = (1.0 + 5_f64.sqrt()) / 2.0;
+    (phi.powf(x) - (consts::PI * x).cos() * phi.powf(-x)) / 5_f64.sqrt()
+}
+
+/// Get the Catalan number of a real number.
+pub fn catalan_f64(x: f64) -> f64 {
+    gamma(2.0 * x + 1.0) / (gamma(x + 2.0) * gamma(x + 1.0))
+}
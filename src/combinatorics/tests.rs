@@ -0,0 +1,79 @@
+use num::bigint::BigInt;
+use num::rational::BigRational;
+
+use crate::{
+    binomial, binomial_f64, catalan, catalan_f64, double_factorial, double_factorial_f64,
+    factorial, factorial_f64, falling, fibonacci, fibonacci_f64, stirling1, stirling2,
+};
+
+fn big(s: &str) -> BigInt {
+    s.parse().unwrap()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-12 * b.abs().max(1.0)
+}
+
+#[test]
+fn factorials() {
+    assert_eq!(factorial(0), 1.into());
+    assert_eq!(factorial(25), big("15511210043330985984000000"));
+    assert_eq!(falling(10, 3), 720.into());
+    assert_eq!(falling(3, 5), 0.into());
+    assert_eq!(falling(-2, 3), (-24).into());
+    assert!(close(factorial_f64(0.5), 0.886226925452758));
+}
+
+#[test]
+fn double_factorials() {
+    let int = |n: i64| Some(BigRational::from_integer(n.into()));
+    assert_eq!(double_factorial(0), int(1));
+    assert_eq!(double_factorial(7), int(105));
+    assert_eq!(double_factorial(8), int(384));
+    assert_eq!(double_factorial(-1), int(1));
+    assert_eq!(double_factorial(-3), int(-1));
+    assert_eq!(
+        double_factorial(-5),
+        Some(BigRational::new(1.into(), 3.into()))
+    );
+    assert_eq!(double_factorial(-2), None);
+    assert!(close(double_factorial_f64(7.0), 105.0));
+    assert!(close(double_factorial_f64(8.0), 384.0));
+}
+
+#[test]
+fn binomials() {
+    assert_eq!(binomial(5, 2), 10.into());
+    assert_eq!(binomial(100, 50), big("100891344545564193334812497256"));
+    assert_eq!(binomial(5, 7), 0.into());
+    assert_eq!(binomial(5, -1), 0.into());
+    assert_eq!(binomial(-3, 2), 6.into());
+    assert!(close(binomial_f64(5.0, 2.0), 10.0));
+    assert!(close(binomial_f64(0.5, 1.0), 0.5));
+}
+
+#[test]
+fn sequences() {
+    let fibs: Vec<BigInt> = (-4..=10).map(fibonacci).collect();
+    let expected = [-3, 2, -1, 1, 0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55];
+    assert_eq!(fibs, expected.iter().map(|&n| n.into()).collect::<Vec<_>>());
+    assert_eq!(fibonacci(100), big("354224848179261915075"));
+    assert!(close(fibonacci_f64(10.0), 55.0));
+    let cats: Vec<BigInt> = (0..7).map(catalan).collect();
+    let expected = [1, 1, 2, 5, 14, 42, 132];
+    assert_eq!(cats, expected.iter().map(|&n| n.into()).collect::<Vec<_>>());
+    assert!(close(catalan_f64(6.0), 132.0));
+}
+
+#[test]
+fn stirling() {
+    assert_eq!(stirling1(5, 2), 50.into());
+    assert_eq!(stirling1(4, 4), 1.into());
+    assert_eq!(stirling1(4, 0), 0.into());
+    assert_eq!(stirling1(0, 0), 1.into());
+    assert_eq!(stirling1(3, 5), 0.into());
+    assert_eq!(stirling2(5, 2), 15.into());
+    assert_eq!(stirling2(10, 3), 9330.into());
+    assert_eq!(stirling2(0, 0), 1.into());
+    assert_eq!(stirling2(3, 5), 0.into());
+}
@@ -1,13 +1,18 @@
 //! Execution context, options, and variable storage.
 
 use std::collections::HashMap;
-use std::fmt;
-use std::fmt::{Display, Formatter};
+use std::fmt::Display;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::Value;
+use crate::{
+    DisplayMode, Expression, Format, Notation, Precision, Rounding, Span, ToLatex, ToMathMl,
+    ToUnicode, Value,
+};
+
+#[cfg(test)]
+mod tests;
 
 /// Execution context, options, and variables.
 #[derive(Clone, Debug, PartialEq)]
@@ -15,6 +20,28 @@ use crate::Value;
 pub struct Context {
     pub angle: AngleMeasure,
     pub notation_range: (f64, f64),
+    /// How to write floats outside of the notation range.
+    pub notation: Notation,
+    /// Whether to annotate floats with a simple fraction or multiple of pi
+    /// that they are practically equal to.
+    pub annotate: bool,
+    /// How many digits of floats to display.
+    pub precision: Precision,
+    /// How to round floats that are displayed with fewer digits.
+    pub rounding: Rounding,
+    /// How the `round` function breaks ties, either half-even or
+    /// half-away-from-zero.
+    pub round_ties: Rounding,
+    /// Whether to separate groups of three digits in the integer part of
+    /// numbers.
+    pub grouping: bool,
+    /// The decimal point and digit group separator characters.
+    pub separators: (char, char),
+    /// Whether the lexer accepts the decimal point from `separators` instead
+    /// of `.` in numeric literals.
+    pub locale_input: bool,
+    /// How the REPL shows results.
+    pub mode: DisplayMode,
     pub vars: HashMap<String, Value>,
 }
 
@@ -23,6 +50,125 @@ impl Context {
     pub fn display<'a>(&'a self, num: &'a Value) -> impl Display + 'a {
         Format { ctx: self, num }
     }
+
+    /// Show the result of an input in the display mode, as an equation
+    /// such as `1/2 + 1/3 = 5/6`.
+    ///
+    /// Only the result is shown in text mode, and only the input if it is an
+    /// assignment or the result is the same.
+    pub fn display_result(&self, input: &(Expression, Span), val: &Value) -> String {
+        let render = |expr: &Expression| match self.mode {
+            DisplayMode::Text => expr.to_string(),
+            DisplayMode::Unicode => expr.to_unicode(self),
+            DisplayMode::MathMl => expr.to_mathml(self),
+            DisplayMode::Latex => expr.to_latex(self),
+        };
+        if self.mode == DisplayMode::Text {
+            return self.display(val).to_string();
+        }
+        let result = (Expression::Val(val.clone()), input.1.clone());
+        if let Expression::Let(..) = input.0 {
+            return render(&input.0);
+        } else if render(&input.0) == render(&result.0) {
+            return render(&result.0);
+        }
+        render(&Expression::Equation(Box::new([input.clone(), result])))
+    }
+
+    /// Get a context with the same options and the given variables, in
+    /// which a function is called.
+    pub(crate) fn with_vars(&self, vars: HashMap<String, Value>) -> Context {
+        Context {
+            angle: self.angle,
+            notation_range: self.notation_range,
+            notation: self.notation,
+            annotate: self.annotate,
+            precision: self.precision,
+            rounding: self.rounding,
+            round_ties: self.round_ties,
+            grouping: self.grouping,
+            separators: self.separators,
+            locale_input: self.locale_input,
+            mode: self.mode,
+            vars,
+        }
+    }
+
+    /// Set a display or calculation option by name, such as `annotate`.
+    ///
+    /// Returns `false` if the option does not exist or the value is invalid.
+    pub fn set_option(&mut self, name: &str, value: &str) -> bool {
+        match name {
+            "annotate" => match parse_bool(value) {
+                Some(b) => self.annotate = b,
+                None => return false,
+            },
+            "digits" => match value {
+                "auto" => self.precision = Precision::Shortest,
+                _ => match value.parse() {
+                    Ok(n) if n > 0 => self.precision = Precision::Significant(n),
+                    _ => return false,
+                },
+            },
+            "decimals" => match value.parse() {
+                Ok(n) => self.precision = Precision::Decimals(n),
+                _ => return false,
+            },
+            "notation" => match value.parse() {
+                Ok(n) => self.notation = n,
+                _ => return false,
+            },
+            "rounding" => match value.parse() {
+                Ok(r) => self.rounding = r,
+                _ => return false,
+            },
+            "round_ties" => match value.parse() {
+                Ok(r @ (Rounding::HalfEven | Rounding::HalfAwayFromZero)) => self.round_ties = r,
+                _ => return false,
+            },
+            "grouping" => match parse_bool(value) {
+                Some(b) => self.grouping = b,
+                None => return false,
+            },
+            "separators" => match parse_separators(value) {
+                Some(seps) => self.separators = seps,
+                None => return false,
+            },
+            "locale_input" => match parse_bool(value) {
+                Some(b) => self.locale_input = b,
+                None => return false,
+            },
+            "mode" => match value.parse() {
+                Ok(m) => self.mode = m,
+                _ => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Parse a boolean option value.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" | "yes" => Some(true),
+        "off" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a pair of distinct decimal point and digit group separator
+/// characters, such as `,.`.
+fn parse_separators(value: &str) -> Option<(char, char)> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(decimal), Some(group), None)
+            if decimal != group && !decimal.is_alphanumeric() && !group.is_alphanumeric() =>
+        {
+            Some((decimal, group))
+        }
+        _ => None,
+    }
 }
 
 impl Default for Context {
@@ -30,16 +176,26 @@ impl Default for Context {
         Context {
             angle: Default::default(),
             notation_range: (1.0e-3, 1.0e+7),
+            notation: Default::default(),
+            annotate: false,
+            precision: Default::default(),
+            rounding: Default::default(),
+            round_ties: Default::default(),
+            grouping: false,
+            separators: ('.', ','),
+            locale_input: false,
+            mode: Default::default(),
             vars: Default::default(),
         }
     }
 }
 
 /// Angle measurement unit: degrees or radians.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AngleMeasure {
     Degrees,
+    #[default]
     Radians,
 }
 
@@ -61,51 +217,3 @@ impl AngleMeasure {
         }
     }
 }
-
-impl Default for AngleMeasure {
-    fn default() -> Self {
-        AngleMeasure::Radians
-    }
-}
-
-// Formatting numbers
-
-struct Format<'a> {
-    ctx: &'a Context,
-    num: &'a Value,
-}
-
-impl Display for Format<'_> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.num {
-            Value::Ratio(num) => {
-                if num.is_integer() {
-                    write!(f, "{}", num.numer())
-                } else {
-                    write!(f, "{} / {}", num.numer(), num.denom())
-                }
-            }
-            Value::Float(num) => {
-                let mag = num.abs();
-                if self.ctx.notation_range.0 < mag && mag < self.ctx.notation_range.1 || mag == 0.0
-                {
-                    // Show number normally (no scientific notation) if within the range
-                    // or equal to zero
-                    write!(f, "{}", num)
-                } else if mag < 1.0 {
-                    write!(f, "{:e}", num)
-                } else {
-                    // Force '+' on exponent
-                    let s = format!("{:e}", num);
-                    if let Some(e) = s.find('e') {
-                        write!(f, "{}e+{}", &s[..e], &s[(e + 1)..])
-                    } else {
-                        // No 'e' found -- probably +/- infinity
-                        write!(f, "{}", s)
-                    }
-                }
-            }
-            Value::Func(_, _) => write!(f, "<function>"),
-        }
-    }
-}
@@ -0,0 +1,287 @@
+use std::f64::consts;
+
+use crate::{
+    eval, lex, parse, parse_unit, Context, DisplayMode, Notation, Precision, Rounding, Value,
+};
+
+fn show(ctx: &Context, val: Value) -> String {
+    ctx.display(&val).to_string()
+}
+
+fn annotated() -> Context {
+    Context {
+        annotate: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn ratio() {
+    assert_eq!(show(&Context::default(), 42.into()), "42");
+    assert_eq!(show(&Context::default(), (-3, 4).into()), "-3 / 4");
+}
+
+#[test]
+fn float() {
+    assert_eq!(show(&Context::default(), 1.5.into()), "1.5");
+    assert_eq!(show(&Context::default(), 1.5e10.into()), "1.5e+10");
+    assert_eq!(show(&Context::default(), 1.5e-10.into()), "1.5e-10");
+}
+
+#[test]
+fn annotate_fraction() {
+    assert_eq!(
+        show(&annotated(), (1.0 / 3.0).into()),
+        "0.3333333333333333 (≈ 1 / 3)"
+    );
+    assert_eq!(
+        show(&annotated(), (0.1 + 0.2).into()),
+        "0.30000000000000004 (≈ 3 / 10)"
+    );
+    assert_eq!(show(&annotated(), 2.0.into()), "2");
+    assert_eq!(show(&annotated(), 1.234567891.into()), "1.234567891");
+}
+
+#[test]
+fn annotate_pi() {
+    assert_eq!(
+        show(&annotated(), consts::PI.into()),
+        "3.141592653589793 (≈ pi)"
+    );
+    assert_eq!(
+        show(&annotated(), (-consts::FRAC_PI_2).into()),
+        "-1.5707963267948966 (≈ -pi / 2)"
+    );
+    assert_eq!(
+        show(&annotated(), (3.0 * consts::FRAC_PI_4).into()),
+        "2.356194490192345 (≈ 3 pi / 4)"
+    );
+}
+
+#[test]
+fn set_option() {
+    let mut ctx = Context::default();
+    assert!(ctx.set_option("annotate", "on"));
+    assert!(ctx.annotate);
+    assert!(ctx.set_option("annotate", "off"));
+    assert!(!ctx.annotate);
+    assert!(!ctx.set_option("annotate", "maybe"));
+    assert!(!ctx.set_option("nonexistent", "on"));
+}
+
+fn with_precision(precision: Precision, rounding: Rounding) -> Context {
+    Context {
+        precision,
+        rounding,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn significant_digits() {
+    let ctx = with_precision(Precision::Significant(10), Rounding::HalfEven);
+    assert_eq!(show(&ctx, (0.1 + 0.2).into()), "0.3");
+    assert_eq!(show(&ctx, (2.0 / 3.0).into()), "0.6666666667");
+    assert_eq!(show(&ctx, 1234.5.into()), "1234.5");
+    assert_eq!(show(&ctx, (-1.0 / 3.0e12).into()), "-3.333333333e-13");
+    assert_eq!(show(&ctx, (2.0e20 / 3.0).into()), "6.666666667e+19");
+    let ctx = with_precision(Precision::Significant(2), Rounding::HalfEven);
+    assert_eq!(show(&ctx, 999.0.into()), "1000");
+    assert_eq!(show(&ctx, 0.125.into()), "0.12");
+    assert_eq!(show(&ctx, 0.0.into()), "0");
+}
+
+#[test]
+fn fixed_decimals() {
+    let ctx = with_precision(Precision::Decimals(2), Rounding::HalfEven);
+    assert_eq!(show(&ctx, 3.14259.into()), "3.14");
+    assert_eq!(show(&ctx, 2.0.into()), "2.00");
+    assert_eq!(show(&ctx, 0.125.into()), "0.12");
+    assert_eq!(show(&ctx, 0.004.into()), "0.00");
+    assert_eq!(show(&ctx, (-0.004).into()), "0.00");
+    assert_eq!(show(&ctx, 0.006.into()), "0.01");
+    assert_eq!(show(&ctx, 99.999.into()), "100.00");
+    assert_eq!(show(&ctx, 1.23456e10.into()), "1.23e+10");
+    assert_eq!(show(&ctx, 5.0e-5.into()), "5.00e-5");
+    let ctx = with_precision(Precision::Decimals(0), Rounding::HalfEven);
+    assert_eq!(show(&ctx, 2.5.into()), "2");
+    assert_eq!(show(&ctx, 0.5.into()), "0");
+    assert_eq!(show(&ctx, (-0.5).into()), "0");
+}
+
+#[test]
+fn rounding_modes() {
+    let ctx = |r| with_precision(Precision::Decimals(2), r);
+    assert_eq!(show(&ctx(Rounding::HalfEven), 0.125.into()), "0.12");
+    assert_eq!(show(&ctx(Rounding::HalfAwayFromZero), 0.125.into()), "0.13");
+    assert_eq!(show(&ctx(Rounding::HalfAwayFromZero), 2.675.into()), "2.68");
+    assert_eq!(show(&ctx(Rounding::TowardZero), (-1.999).into()), "-1.99");
+    assert_eq!(show(&ctx(Rounding::Floor), (-1.991).into()), "-2.00");
+    assert_eq!(show(&ctx(Rounding::Ceiling), 1.991.into()), "2.00");
+}
+
+#[test]
+fn precision_options() {
+    let mut ctx = Context::default();
+    assert!(ctx.set_option("digits", "12"));
+    assert_eq!(ctx.precision, Precision::Significant(12));
+    assert!(ctx.set_option("decimals", "3"));
+    assert_eq!(ctx.precision, Precision::Decimals(3));
+    assert!(ctx.set_option("digits", "auto"));
+    assert_eq!(ctx.precision, Precision::Shortest);
+    assert!(!ctx.set_option("digits", "0"));
+    assert!(ctx.set_option("rounding", "half-away"));
+    assert_eq!(ctx.rounding, Rounding::HalfAwayFromZero);
+    assert!(!ctx.set_option("rounding", "sideways"));
+    assert!(ctx.set_option("round_ties", "half-away"));
+    assert_eq!(ctx.round_ties, Rounding::HalfAwayFromZero);
+    assert!(!ctx.set_option("round_ties", "floor"));
+}
+
+fn with_notation(notation: Notation) -> Context {
+    Context {
+        notation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn engineering_notation() {
+    let ctx = with_notation(Notation::Engineering);
+    assert_eq!(show(&ctx, 4.7e-6.into()), "4.7e-6");
+    assert_eq!(show(&ctx, 4.7e-5.into()), "47e-6");
+    assert_eq!(show(&ctx, (-4.7e-4).into()), "-470e-6");
+    assert_eq!(show(&ctx, 1.5e10.into()), "15e+9");
+    assert_eq!(show(&ctx, 123.0.into()), "123");
+    let ctx = Context {
+        precision: Precision::Decimals(2),
+        ..ctx
+    };
+    assert_eq!(show(&ctx, 4.7e-5.into()), "47.00e-6");
+    assert_eq!(show(&ctx, 999.999e-6.into()), "1.00e-3");
+}
+
+#[test]
+fn si_notation() {
+    let ctx = with_notation(Notation::Si);
+    assert_eq!(show(&ctx, 4.7e-6.into()), "4.7 µ");
+    assert_eq!(show(&ctx, 4700.0.into()), "4.7 k");
+    assert_eq!(show(&ctx, (-0.047).into()), "-47 m");
+    assert_eq!(show(&ctx, 5.5.into()), "5.5");
+    assert_eq!(show(&ctx, 0.0.into()), "0");
+    assert_eq!(show(&ctx, 2.0e33.into()), "2e+33");
+    // A prefix before a unit would read as part of the unit
+    assert_eq!(show(&ctx, parse_unit("mm").unwrap().into_value()), "1 mm");
+    let mut q = parse_unit("m").unwrap();
+    q.value = 0.5;
+    assert_eq!(show(&ctx, q.into_value()), "0.5 m");
+    let ctx = Context {
+        precision: Precision::Significant(3),
+        ..ctx
+    };
+    assert_eq!(show(&ctx, (1.0 / 3.0e9).into()), "333 p");
+}
+
+#[test]
+fn notation_options() {
+    let mut ctx = Context::default();
+    assert!(ctx.set_option("notation", "eng"));
+    assert_eq!(ctx.notation, Notation::Engineering);
+    assert!(ctx.set_option("notation", "si"));
+    assert_eq!(ctx.notation, Notation::Si);
+    assert!(!ctx.set_option("notation", "roman"));
+}
+
+fn grouped(separators: (char, char)) -> Context {
+    Context {
+        grouping: true,
+        separators,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn digit_grouping() {
+    let ctx = grouped(('.', ','));
+    assert_eq!(show(&ctx, 1234567890.into()), "1,234,567,890");
+    assert_eq!(show(&ctx, (-123456).into()), "-123,456");
+    assert_eq!(show(&ctx, 999.into()), "999");
+    assert_eq!(show(&ctx, (1000001, 1000).into()), "1,000,001 / 1,000");
+    assert_eq!(show(&ctx, 1234567.125.into()), "1,234,567.125");
+    assert_eq!(show(&ctx, 0.000125.into()), "1.25e-4");
+    assert_eq!(show(&ctx, 1.5e12.into()), "1.5e+12");
+}
+
+#[test]
+fn locale_separators() {
+    let ctx = grouped((',', '.'));
+    assert_eq!(show(&ctx, 1234567.5.into()), "1.234.567,5");
+    assert_eq!(show(&ctx, 0.25.into()), "0,25");
+    assert_eq!(show(&ctx, 2.5e-9.into()), "2,5e-9");
+    let ctx = Context {
+        grouping: false,
+        ..ctx
+    };
+    assert_eq!(show(&ctx, 1234567.5.into()), "1234567,5");
+}
+
+#[test]
+fn separator_options() {
+    let mut ctx = Context::default();
+    assert!(ctx.set_option("grouping", "on"));
+    assert!(ctx.grouping);
+    assert!(ctx.set_option("separators", ",."));
+    assert_eq!(ctx.separators, (',', '.'));
+    assert!(ctx.set_option("separators", ".'"));
+    assert_eq!(ctx.separators, ('.', '\''));
+    assert!(!ctx.set_option("separators", ".."));
+    assert!(!ctx.set_option("separators", ".,;"));
+    assert!(!ctx.set_option("separators", "a,"));
+    assert!(ctx.set_option("locale_input", "on"));
+    assert!(ctx.locale_input);
+}
+
+#[test]
+fn list() {
+    let list = Value::List(vec![1.into(), (-1, 2).into(), Value::List(vec![])]);
+    assert_eq!(show(&Context::default(), list.clone()), "[1, -1 / 2, []]");
+    let ctx = Context {
+        separators: (',', '.'),
+        ..Default::default()
+    };
+    let list = Value::List(vec![1.5.into(), 2.into()]);
+    assert_eq!(show(&ctx, list), "[1,5; 2]");
+}
+
+#[test]
+fn matrix() {
+    let m = crate::Matrix::from_rows(vec![
+        vec![1.into(), (-1, 2).into()],
+        vec![10.into(), 2.5.into()],
+    ]);
+    let m = Value::Matrix(m.unwrap());
+    assert_eq!(show(&Context::default(), m), "[ 1  -1 / 2]\n[10     2.5]");
+}
+
+#[test]
+fn display_modes() {
+    let mut ctx = Context::default();
+    assert!(ctx.set_option("mode", "unicode"));
+    assert_eq!(ctx.mode, DisplayMode::Unicode);
+    assert!(!ctx.set_option("mode", "html"));
+    let result = |ctx: &mut Context, s: &str| {
+        let input = parse(lex(s, None)).unwrap();
+        let val = eval(&input, ctx).unwrap();
+        ctx.display_result(&input, &val)
+    };
+    assert_eq!(
+        result(&mut ctx, "1 / 2 + 1 / 3"),
+        " 1     1     5\n─── + ─── = ───\n 2     3     6"
+    );
+    assert_eq!(result(&mut ctx, "x = 2^3"), "x = 2³");
+    assert_eq!(result(&mut ctx, "7"), "7");
+    assert!(ctx.set_option("mode", "latex"));
+    assert_eq!(result(&mut ctx, "x + 1"), "x + 1 = 9");
+    assert!(ctx.set_option("mode", "text"));
+    assert_eq!(result(&mut ctx, "x / 16"), "1 / 2");
+}
@@ -0,0 +1,398 @@
+//! Symbolic differentiation.
+
+#[cfg(test)]
+mod tests;
+
+use num::rational::Ratio;
+use num::traits::{CheckedAdd, CheckedMul, One, Signed, Zero};
+
+use crate::{Constant, Context, Error, Expression, Function, Result, Span, Value};
+
+type ExprSpan = (Expression, Span);
+
+/// Get the value of an expression if it is a rational constant.
+fn as_ratio(expr: &Expression) -> Option<Ratio<i64>> {
+    match expr {
+        Expression::Val(Value::Ratio(r)) => Some(*r),
+        Expression::Neg(e) => as_ratio(&e.0).map(|r| -r),
+        _ => None,
+    }
+}
+
+/// Make a rational constant expression.
+fn ratio(r: impl Into<Ratio<i64>>, span: &Span) -> ExprSpan {
+    (Expression::Val(Value::Ratio(r.into())), span.clone())
+}
+
+/// Collect the terms of a sum, flattening nested sums and adding up the
+/// rational constants.
+fn add_terms(terms: Vec<ExprSpan>, constant: &mut Ratio<i64>, rest: &mut Vec<ExprSpan>) {
+    for term in terms {
+        match term {
+            (Expression::Add(v), _) => add_terms(v, constant, rest),
+            (e, s) => match as_ratio(&e).and_then(|r| constant.checked_add(&r)) {
+                Some(c) => *constant = c,
+                None => rest.push((e, s)),
+            },
+        }
+    }
+}
+
+/// Add terms, dropping zeros and combining rational constants.
+fn sum(terms: Vec<ExprSpan>, span: &Span) -> ExprSpan {
+    let mut constant = Ratio::zero();
+    let mut rest = vec![];
+    add_terms(terms, &mut constant, &mut rest);
+    if !constant.is_zero() {
+        rest.push(ratio(constant, span));
+    }
+    match rest.len() {
+        0 => ratio(0, span),
+        1 => rest.pop().unwrap(),
+        _ => (Expression::Add(rest), span.clone()),
+    }
+}
+
+/// Subtract `b` from `a`, dropping zeros.
+fn difference(a: ExprSpan, b: ExprSpan, span: &Span) -> ExprSpan {
+    match (as_ratio(&a.0), as_ratio(&b.0)) {
+        (_, Some(r)) if r.is_zero() => a,
+        (Some(r), _) if r.is_zero() => neg(b, span),
+        (Some(_), Some(_)) => sum(vec![a, neg(b, span)], span),
+        _ => (Expression::Sub(Box::new([a, b])), span.clone()),
+    }
+}
+
+/// Collect the factors of a product, flattening nested products and
+/// multiplying together the rational constants and signs.
+fn mul_factors(factors: Vec<ExprSpan>, coef: &mut Ratio<i64>, rest: &mut Vec<ExprSpan>) {
+    for factor in factors {
+        match factor {
+            (Expression::Mul(v), _) => mul_factors(v, coef, rest),
+            (Expression::Neg(e), _) => {
+                *coef = -*coef;
+                mul_factors(vec![*e], coef, rest);
+            }
+            (e, s) => match as_ratio(&e).and_then(|r| coef.checked_mul(&r)) {
+                Some(c) => *coef = c,
+                None => rest.push((e, s)),
+            },
+        }
+    }
+}
+
+/// Multiply factors, dropping ones, combining rational constants into a
+/// leading coefficient, and giving zero if any factor is zero.
+fn product(factors: Vec<ExprSpan>, span: &Span) -> ExprSpan {
+    let mut coef = Ratio::one();
+    let mut rest = vec![];
+    mul_factors(factors, &mut coef, &mut rest);
+    if coef.is_zero() || rest.is_empty() {
+        return ratio(coef, span);
+    }
+    if !coef.abs().is_one() {
+        rest.insert(0, ratio(coef.abs(), span));
+    }
+    let expr = match rest.len() {
+        1 => rest.pop().unwrap(),
+        _ => (Expression::Mul(rest), span.clone()),
+    };
+    if coef.is_negative() {
+        (Expression::Neg(Box::new(expr)), span.clone())
+    } else {
+        expr
+    }
+}
+
+/// Negate an expression, swapping the sides of a subtraction.
+fn neg(expr: ExprSpan, span: &Span) -> ExprSpan {
+    match expr {
+        (Expression::Sub(args), _) => {
+            let [a, b] = *args;
+            (Expression::Sub(Box::new([b, a])), span.clone())
+        }
+        expr => product(vec![ratio(-1, span), expr], span),
+    }
+}
+
+/// Divide `a` by `b`, multiplying by the reciprocal instead if `b` is a
+/// rational constant.
+fn quotient(a: ExprSpan, b: ExprSpan, span: &Span) -> ExprSpan {
+    match as_ratio(&b.0) {
+        Some(r) if !r.is_zero() => product(vec![ratio(r.recip(), span), a], span),
+        _ if as_ratio(&a.0).is_some_and(|r| r.is_zero()) => a,
+        _ => match a {
+            // Move the sign out of the numerator
+            (Expression::Neg(a), _) => neg(quotient(*a, b, span), span),
+            a => (Expression::Frac(Box::new([a, b])), span.clone()),
+        },
+    }
+}
+
+/// Raise `base` to the power of `exp`, dropping exponents of zero and one.
+fn power(base: ExprSpan, exp: ExprSpan, span: &Span) -> ExprSpan {
+    match as_ratio(&exp.0) {
+        Some(r) if r.is_zero() => ratio(1, span),
+        Some(r) if r.is_one() => base,
+        _ => (Expression::Exp(Box::new([base, exp])), span.clone()),
+    }
+}
+
+/// Call a built-in function with one or more arguments.
+fn call(f: Function, mut args: Vec<ExprSpan>, span: &Span) -> ExprSpan {
+    let arg = if args.len() == 1 {
+        args.pop().unwrap()
+    } else {
+        (Expression::Comma(args), span.clone())
+    };
+    (Expression::Func(f, Box::new(arg)), span.clone())
+}
+
+/// Get the derivative of the built-in function `f` of one argument, at `u`.
+///
+/// Returns `None` if the derivative can't be written in terms of the
+/// built-in functions.
+fn derivative_at(f: Function, u: &ExprSpan, span: &Span) -> Option<ExprSpan> {
+    let one = || ratio(1, span);
+    let square = || power(u.clone(), ratio(2, span), span);
+    let sqrt = |e| power(e, ratio((1, 2), span), span);
+    let derivative = match f {
+        Function::Abs => call(Function::Sign, vec![u.clone()], span),
+        Function::Sin => call(Function::Cos, vec![u.clone()], span),
+        Function::Cos => neg(call(Function::Sin, vec![u.clone()], span), span),
+        Function::Tan => {
+            let cos = call(Function::Cos, vec![u.clone()], span);
+            quotient(one(), power(cos, ratio(2, span), span), span)
+        }
+        Function::Asin => quotient(one(), sqrt(difference(one(), square(), span)), span),
+        Function::Acos => neg(
+            quotient(one(), sqrt(difference(one(), square(), span)), span),
+            span,
+        ),
+        Function::Atan => quotient(one(), sum(vec![square(), one()], span), span),
+        Function::Sinh => call(Function::Cosh, vec![u.clone()], span),
+        Function::Cosh => call(Function::Sinh, vec![u.clone()], span),
+        Function::Tanh => {
+            let cosh = call(Function::Cosh, vec![u.clone()], span);
+            quotient(one(), power(cosh, ratio(2, span), span), span)
+        }
+        Function::Asinh => quotient(one(), sqrt(sum(vec![square(), one()], span)), span),
+        Function::Acosh => quotient(one(), sqrt(difference(square(), one(), span)), span),
+        Function::Atanh => quotient(one(), difference(one(), square(), span), span),
+        Function::Ln => quotient(one(), u.clone(), span),
+        // The derivative of a step is zero wherever it exists
+        Function::Floor | Function::Ceil | Function::Round | Function::Trunc | Function::Sign => {
+            ratio(0, span)
+        }
+        Function::Frac => one(),
+        Function::Erf | Function::Erfc => {
+            // 2 e^(-u^2) / sqrt(pi)
+            let e = (Expression::Const(Constant::E), span.clone());
+            let exp = power(e, neg(square(), span), span);
+            let pi = (Expression::Const(Constant::Pi), span.clone());
+            let erf = quotient(product(vec![ratio(2, span), exp], span), sqrt(pi), span);
+            if f == Function::Erf {
+                erf
+            } else {
+                neg(erf, span)
+            }
+        }
+        _ => return None,
+    };
+    Some(derivative)
+}
+
+/// Differentiate a call to the built-in function `f` with the argument
+/// `arg`, by the chain rule.
+fn diff_func(
+    f: Function,
+    arg: &ExprSpan,
+    var: &str,
+    ctx: &Context,
+    span: &Span,
+) -> Result<ExprSpan> {
+    let not_differentiable = || (Error::NotDifferentiable, span.clone());
+    let (args, u) = match arg {
+        (Expression::Comma(args), _) => match f {
+            // The number of digits is a step
+            Function::Round if args.len() == 2 && !args[1].0.has_var(var) => {
+                return Ok(ratio(0, span));
+            }
+            Function::BesselJ | Function::BesselY if args.len() == 2 && !args[0].0.has_var(var) => {
+                (Some(&args[0]), &args[1])
+            }
+            _ => return Err(not_differentiable()),
+        },
+        u => (None, u),
+    };
+    let outer = match args {
+        // (J(n - 1, u) - J(n + 1, u)) / 2
+        Some(n) => {
+            let below = call(
+                f,
+                vec![sum(vec![n.clone(), ratio(-1, span)], span), u.clone()],
+                span,
+            );
+            let above = call(
+                f,
+                vec![sum(vec![n.clone(), ratio(1, span)], span), u.clone()],
+                span,
+            );
+            product(
+                vec![ratio((1, 2), span), difference(below, above, span)],
+                span,
+            )
+        }
+        None => derivative_at(f, u, span).ok_or_else(not_differentiable)?,
+    };
+    Ok(product(vec![outer, differentiate(u, var, ctx)?], span))
+}
+
+/// Differentiate a call to the user-defined function `name`, by
+/// substituting the arguments into its body.
+fn diff_call(
+    name: &str,
+    args: &[ExprSpan],
+    var: &str,
+    ctx: &Context,
+    span: &Span,
+) -> Result<ExprSpan> {
+    let (params, body) = match ctx.vars.get(name) {
+        Some(Value::Func(params, body)) if params.len() == args.len() => (params, body),
+        Some(Value::Builtin(f)) => return differentiate(&call(*f, args.to_vec(), span), var, ctx),
+        Some(Value::Func(..)) => return Err((Error::Syntax, span.clone())),
+        _ => return Err((Error::Type, span.clone())),
+    };
+    // Rename the parameters first, so that an argument can't be captured by
+    // a later parameter. `#` can't appear in a variable name.
+    let mut body = body.as_ref().clone();
+    for (i, param) in params.iter().enumerate() {
+        body = Expression::substitute(
+            &body,
+            param,
+            &(Expression::Var(format!("#{}", i)), span.clone()),
+        );
+    }
+    for (i, arg) in args.iter().enumerate() {
+        body = Expression::substitute(&body, &format!("#{}", i), arg);
+    }
+    differentiate(&(body.0, span.clone()), var, ctx)
+}
+
+/// Differentiate an expression with respect to the variable `var`, giving
+/// a simplified expression.
+///
+/// Other variables are treated as constants, and calls to functions defined
+/// in `ctx` are differentiated through their bodies. Returns an error if
+/// part of the expression, such as a call to `gamma`, can't be
+/// differentiated in terms of the built-in functions.
+pub fn differentiate(expr: &ExprSpan, var: &str, ctx: &Context) -> Result<ExprSpan> {
+    use Expression::*;
+    let (e, span) = expr;
+    if !e.has_var(var) {
+        return Ok(ratio(0, span));
+    }
+    let d = |e: &ExprSpan| differentiate(e, var, ctx);
+    match e {
+        Var(_) => Ok(ratio(1, span)),
+        Neg(u) => Ok(neg(d(u)?, span)),
+        Add(v) => Ok(sum(v.iter().map(d).collect::<Result<_>>()?, span)),
+        Sub(args) => Ok(difference(d(&args[0])?, d(&args[1])?, span)),
+        Mul(v) => {
+            // The product rule
+            let mut terms = vec![];
+            for (i, factor) in v.iter().enumerate() {
+                if factor.0.has_var(var) {
+                    let mut factors = v.clone();
+                    factors[i] = d(factor)?;
+                    terms.push(product(factors, span));
+                }
+            }
+            Ok(sum(terms, span))
+        }
+        Frac(args) => {
+            let [u, w] = args.as_ref();
+            if !w.0.has_var(var) {
+                return Ok(quotient(d(u)?, w.clone(), span));
+            }
+            // The quotient rule
+            let numer = difference(
+                product(vec![d(u)?, w.clone()], span),
+                product(vec![u.clone(), d(w)?], span),
+                span,
+            );
+            Ok(quotient(
+                numer,
+                power(w.clone(), ratio(2, span), span),
+                span,
+            ))
+        }
+        Exp(args) => {
+            let [u, w] = args.as_ref();
+            if !w.0.has_var(var) {
+                // The power rule
+                let exp = difference(w.clone(), ratio(1, span), span);
+                return Ok(product(
+                    vec![w.clone(), power(u.clone(), exp, span), d(u)?],
+                    span,
+                ));
+            }
+            let ln = call(Function::Ln, vec![u.clone()], span);
+            let inner = if !u.0.has_var(var) {
+                match &u.0 {
+                    Const(Constant::E) => d(w)?,
+                    _ => product(vec![ln, d(w)?], span),
+                }
+            } else {
+                // d/dx u^w = u^w (w' ln(u) + w u' / u)
+                sum(
+                    vec![
+                        product(vec![d(w)?, ln], span),
+                        quotient(product(vec![w.clone(), d(u)?], span), u.clone(), span),
+                    ],
+                    span,
+                )
+            };
+            Ok(product(vec![expr.clone(), inner], span))
+        }
+        Root(args) => {
+            let [u, n] = args.as_ref();
+            if n.0.has_var(var) {
+                let exp = quotient(ratio(1, span), n.clone(), span);
+                return d(&power(u.clone(), exp, span));
+            }
+            // d/dx root(u, n) = u' / (n root(u, n)^(n - 1))
+            let exp = difference(n.clone(), ratio(1, span), span);
+            let denom = product(vec![n.clone(), power(expr.clone(), exp, span)], span);
+            Ok(quotient(d(u)?, denom, span))
+        }
+        Func(f, arg) => diff_func(*f, arg, var, ctx, span),
+        Call(name, args) => diff_call(name, args, var, ctx, span),
+        List(v) => Ok((List(v.iter().map(d).collect::<Result<_>>()?), span.clone())),
+        Index(args) if !args[1].0.has_var(var) => Ok((
+            Index(Box::new([d(&args[0])?, args[1].clone()])),
+            span.clone(),
+        )),
+        Sum(k, args) if !args[0].0.has_var(var) && !args[1].0.has_var(var) => {
+            let [start, end, body] = args.as_ref();
+            let args = Box::new([start.clone(), end.clone(), d(body)?]);
+            Ok((Sum(k.clone(), args), span.clone()))
+        }
+        Integral(t, args) => {
+            // The Leibniz integral rule
+            let [start, end, body] = args.as_ref();
+            let at = |bound: &ExprSpan| Expression::substitute(body, t, bound);
+            let mut terms = vec![
+                product(vec![at(end), d(end)?], span),
+                neg(product(vec![at(start), d(start)?], span), span),
+            ];
+            if t != var && body.0.has_var(var) {
+                let args = Box::new([start.clone(), end.clone(), d(body)?]);
+                terms.push((Integral(t.clone(), args), span.clone()));
+            }
+            Ok(sum(terms, span))
+        }
+        Index(_) | Sum(..) | Product(..) => Err((Error::NotDifferentiable, span.clone())),
+        _ => Err((Error::Type, span.clone())),
+    }
+}
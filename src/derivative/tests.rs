@@ -0,0 +1,160 @@
+use std::f64::consts;
+
+use crate::Expression::*;
+use crate::{differentiate, eval, lex, parse, Context, Error, Expression, Function};
+
+fn d(s: &str) -> crate::Result<Expression> {
+    let expr = parse(lex(s, None))?;
+    differentiate(&expr, "x", &Context::default()).map(|(e, _)| e)
+}
+
+/// Check the derivative of `s` against `expected` at each of the points.
+fn check(s: &str, expected: impl Fn(f64) -> f64, points: &[f64]) {
+    let expr = parse(lex(s, None)).unwrap();
+    let derivative = differentiate(&expr, "x", &Context::default()).unwrap();
+    for &x in points {
+        let mut ctx = Context::default();
+        ctx.vars.insert("x".to_string(), x.into());
+        let actual = eval(&derivative, &mut ctx).unwrap().as_f64().unwrap();
+        let expected = expected(x);
+        assert!(
+            (actual - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+            "d/dx {} at {} = {}, not {}",
+            s,
+            x,
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn arithmetic() {
+    let points = [-2.5, 0.5, 1.0, 3.0];
+    check("x", |_| 1.0, &points);
+    check("3 * x - 2", |_| 3.0, &points);
+    check("x^2 + x + 1", |x| 2.0 * x + 1.0, &points);
+    check("-x^3", |x| -3.0 * x * x, &points);
+    check(
+        "x * (x + 1) * (x + 2)",
+        |x| 3.0 * x * x + 6.0 * x + 2.0,
+        &points,
+    );
+    check("1 / x", |x| -1.0 / (x * x), &points);
+    check(
+        "x / (x^2 + 1)",
+        |x| (1.0 - x * x) / (x * x + 1.0).powi(2),
+        &points,
+    );
+    check("(x^2 + 1) / 4", |x| x / 2.0, &points);
+}
+
+#[test]
+fn powers() {
+    let points = [0.5, 1.0, 2.0];
+    check("x^(1 / 2)", |x| 0.5 / x.sqrt(), &points);
+    check("e^(2 * x)", |x| 2.0 * (2.0 * x).exp(), &points);
+    check("2^x", |x| 2f64.powf(x) * consts::LN_2, &points);
+    check("x^x", |x| x.powf(x) * (x.ln() + 1.0), &points);
+    check("ln(x^2)", |x| 2.0 / x, &points);
+}
+
+#[test]
+fn functions() {
+    let points = [-0.5, 0.25, 0.75];
+    check("sin(x)", f64::cos, &points);
+    check("cos(3 * x)", |x| -3.0 * (3.0 * x).sin(), &points);
+    check("tan(x)", |x| 1.0 / x.cos().powi(2), &points);
+    check("asin(x)", |x| 1.0 / (1.0 - x * x).sqrt(), &points);
+    check("acos(x)", |x| -1.0 / (1.0 - x * x).sqrt(), &points);
+    check("atan(x^2)", |x| 2.0 * x / (1.0 + x.powi(4)), &points);
+    check("sinh(x)", f64::cosh, &points);
+    check("cosh(x)", f64::sinh, &points);
+    check("tanh(x)", |x| 1.0 / x.cosh().powi(2), &points);
+    check("asinh(x)", |x| 1.0 / (x * x + 1.0).sqrt(), &points);
+    check("atanh(x)", |x| 1.0 / (1.0 - x * x), &points);
+    check(
+        "acosh(x + 2)",
+        |x| 1.0 / ((x + 2.0).powi(2) - 1.0).sqrt(),
+        &points,
+    );
+    check("abs(x)", f64::signum, &points);
+    check("floor(x) + frac(x)", |_| 1.0, &points);
+    check(
+        "erf(x)",
+        |x| 2.0 * (-x * x).exp() / consts::PI.sqrt(),
+        &points,
+    );
+    check("(sin(x))^2 + (cos(x))^2", |_| 0.0, &points);
+}
+
+#[test]
+fn bessel() {
+    let expr = parse(lex("besselj(0, x)", None)).unwrap();
+    let derivative = differentiate(&expr, "x", &Context::default()).unwrap();
+    let minus_j1 = parse(lex("-besselj(1, x)", None)).unwrap();
+    for &x in &[0.5, 2.0, 7.0] {
+        let mut ctx = Context::default();
+        ctx.vars.insert("x".to_string(), x.into());
+        let actual = eval(&derivative, &mut ctx).unwrap().as_f64().unwrap();
+        let expected = eval(&minus_j1, &mut ctx).unwrap().as_f64().unwrap();
+        assert!((actual - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn simplified() {
+    assert_eq!(d("5"), Ok(0.into()));
+    assert_eq!(d("y^2"), Ok(0.into()));
+    assert_eq!(d("x + 5"), Ok(1.into()));
+    assert_eq!(d("3 * x"), Ok(3.into()));
+    assert_eq!(d("x / 2"), Ok((1, 2).into()));
+    assert!(matches!(
+        d("sin(x)"),
+        Ok(Func(Function::Cos, arg)) if arg.0 == Var("x".to_string())
+    ));
+    // 2 x
+    match d("x^2") {
+        Ok(Mul(v)) => {
+            assert_eq!(v[0].0, 2.into());
+            assert_eq!(v[1].0, Var("x".to_string()));
+        }
+        result => panic!("{:?}", result),
+    }
+    // -sin(x)
+    match d("cos(x)") {
+        Ok(Neg(e)) => assert!(matches!(e.0, Func(Function::Sin, _))),
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn other_variables() {
+    let expr = parse(lex("a * x^2 + b", None)).unwrap();
+    let derivative = differentiate(&expr, "x", &Context::default()).unwrap();
+    let mut ctx = Context::default();
+    ctx.vars.insert("a".to_string(), 3.into());
+    ctx.vars.insert("b".to_string(), 7.into());
+    ctx.vars.insert("x".to_string(), 2.into());
+    assert_eq!(eval(&derivative, &mut ctx), Ok(12.into()));
+}
+
+#[test]
+fn not_differentiable() {
+    assert_eq!(
+        d("gamma(x)").map_err(|e| (e.0, e.1.start)),
+        Err((Error::NotDifferentiable, 1))
+    );
+    assert_eq!(d("y = x").map_err(|e| e.0), Err(Error::Type));
+    assert_eq!(
+        d("besselj(x, 1)").map_err(|e| e.0),
+        Err(Error::NotDifferentiable)
+    );
+    // The argument doesn't depend on the variable
+    assert_eq!(d("gamma(y)"), Ok(0.into()));
+    assert_eq!(
+        d("[1, 2][x]").map_err(|e| e.0),
+        Err(Error::NotDifferentiable)
+    );
+    assert_eq!(d("f(x)").map_err(|e| e.0), Err(Error::Type));
+}
@@ -15,6 +15,18 @@ pub enum Error {
     Undefined(String),
     /// Invalid argument type.
     Type,
+    /// Argument outside of the function's domain.
+    Domain,
+    /// Quantities with incompatible units.
+    Dimension,
+    /// Lists with different lengths.
+    Length,
+    /// Matrices with incompatible shapes.
+    Shape,
+    /// A numerical method that failed to find a result.
+    Convergence,
+    /// A function that has no symbolic derivative.
+    NotDifferentiable,
 }
 
 impl Display for Error {
@@ -23,6 +35,12 @@ impl Display for Error {
             Error::Syntax => write!(f, "Syntax error"),
             Error::Undefined(v) => write!(f, "Undefined variable '{}'", v),
             Error::Type => write!(f, "Invalid argument type"),
+            Error::Domain => write!(f, "Argument out of domain"),
+            Error::Dimension => write!(f, "Incompatible units"),
+            Error::Length => write!(f, "Lists have different lengths"),
+            Error::Shape => write!(f, "Incompatible matrix shapes"),
+            Error::Convergence => write!(f, "Failed to converge"),
+            Error::NotDifferentiable => write!(f, "Cannot differentiate"),
         }
     }
 }
@@ -32,11 +32,160 @@ pub enum Expression {
     Let(String, Box<(Expression, Span)>),
     /// A call to a user-defined function.
     Call(String, Vec<(Expression, Span)>),
+    /// A function of the named parameters, such as `x -> x^2`.
+    Lambda(Vec<String>, Box<(Expression, Span)>),
+
+    /// A conversion of a quantity to the unit of another expression.
+    Convert(Box<[(Expression, Span); 2]>),
+
+    /// A list of expressions, such as `[1, 2, 3]`.
+    List(Vec<(Expression, Span)>),
+    /// An element of a list, given the list and its index.
+    Index(Box<[(Expression, Span); 2]>),
+    /// A list of the numbers counting up by one from a start to an end
+    /// inclusive, such as `1..10`.
+    Range(Box<[(Expression, Span); 2]>),
+    /// A sum over an integer index variable, given the start and end of the
+    /// index and the summand, such as `sum(k, 1, 10, k^2)`.
+    Sum(String, Box<[(Expression, Span); 3]>),
+    /// A product over an integer index variable, given the start and end of
+    /// the index and the factor.
+    Product(String, Box<[(Expression, Span); 3]>),
+    /// A definite integral over a variable, given the lower and upper bounds
+    /// and the integrand, such as `integrate(x, 0, 1, x^2)`.
+    Integral(String, Box<[(Expression, Span); 3]>),
+    /// An equation between two expressions, which may only be solved.
+    Equation(Box<[(Expression, Span); 2]>),
+    /// A solution of an equation for a variable, given the equation and up
+    /// to two initial guesses, such as `solve(x^2 = 2, x, 1)`.
+    Solve(String, Box<(Expression, Span)>, Vec<(Expression, Span)>),
+    /// The derivative of an expression with respect to a variable, as a
+    /// function of that variable, such as `diff(x^2, x)`.
+    Derivative(String, Box<(Expression, Span)>),
+    /// An expression to be simplified, as a function of its free variables,
+    /// such as `simplify(x + x)`.
+    Simplify(Box<(Expression, Span)>),
 
     /// Multiple expressions delimited by commas.
     Comma(Vec<(Expression, Span)>),
 }
 
+impl Expression {
+    /// Replace each free occurrence of the variable `name` in an expression
+    /// with `value`.
+    pub fn substitute(
+        (expr, span): &(Expression, Span),
+        name: &str,
+        value: &(Expression, Span),
+    ) -> (Expression, Span) {
+        use Expression::*;
+        let sub = |e: &(Expression, Span)| Expression::substitute(e, name, value);
+        let sub_all = |v: &[(Expression, Span)]| v.iter().map(sub).collect();
+        let sub_pair = |[a, b]: &[(Expression, Span); 2]| Box::new([sub(a), sub(b)]);
+        let expr = match expr {
+            Var(var) if var == name => return value.clone(),
+            Val(_) | Const(_) | Var(_) => expr.clone(),
+            Neg(e) => Neg(Box::new(sub(e))),
+            Add(v) => Add(sub_all(v)),
+            Sub(args) => Sub(sub_pair(args)),
+            Mul(v) => Mul(sub_all(v)),
+            Frac(args) => Frac(sub_pair(args)),
+            Exp(args) => Exp(sub_pair(args)),
+            Root(args) => Root(sub_pair(args)),
+            Func(f, e) => Func(*f, Box::new(sub(e))),
+            Let(var, e) => Let(var.clone(), Box::new(sub(e))),
+            Call(f, args) => Call(f.clone(), sub_all(args)),
+            // The parameters of a function shadow the variable
+            Lambda(params, _) if params.iter().any(|p| p == name) => expr.clone(),
+            Lambda(params, body) => Lambda(params.clone(), Box::new(sub(body))),
+            Convert(args) => Convert(sub_pair(args)),
+            List(v) => List(sub_all(v)),
+            Index(args) => Index(sub_pair(args)),
+            Range(args) => Range(sub_pair(args)),
+            Equation(args) => Equation(sub_pair(args)),
+            Solve(var, eq, guesses) => {
+                // The variable that is solved for shadows the variable
+                let eq = if var == name {
+                    eq.as_ref().clone()
+                } else {
+                    sub(eq)
+                };
+                Solve(var.clone(), Box::new(eq), sub_all(guesses))
+            }
+            Derivative(var, _) if var == name => expr.clone(),
+            Derivative(var, e) => Derivative(var.clone(), Box::new(sub(e))),
+            Simplify(e) => Simplify(Box::new(sub(e))),
+            Sum(var, args) | Product(var, args) | Integral(var, args) => {
+                let [start, end, body] = args.as_ref();
+                // The index variable shadows the variable in the body
+                let body = if var == name { body.clone() } else { sub(body) };
+                let args = Box::new([sub(start), sub(end), body]);
+                match expr {
+                    Sum(..) => Sum(var.clone(), args),
+                    Product(..) => Product(var.clone(), args),
+                    _ => Integral(var.clone(), args),
+                }
+            }
+            Comma(v) => Comma(sub_all(v)),
+        };
+        (expr, span.clone())
+    }
+
+    /// Get the immediate subexpressions of the expression.
+    pub fn children(&self) -> Vec<&(Expression, Span)> {
+        use Expression::*;
+        match self {
+            Val(_) | Const(_) | Var(_) => vec![],
+            Neg(e) | Func(_, e) | Let(_, e) | Lambda(_, e) | Derivative(_, e) | Simplify(e) => {
+                vec![e]
+            }
+            Add(v) | Mul(v) | Call(_, v) | List(v) | Comma(v) => v.iter().collect(),
+            Sub(args) | Frac(args) | Exp(args) | Root(args) | Convert(args) | Index(args)
+            | Range(args) | Equation(args) => args.iter().collect(),
+            Sum(_, args) | Product(_, args) | Integral(_, args) => args.iter().collect(),
+            Solve(_, eq, guesses) => std::iter::once(eq.as_ref()).chain(guesses).collect(),
+        }
+    }
+
+    /// Get the variables that occur free in the expression, in the order
+    /// that they first appear.
+    pub fn free_vars(&self) -> Vec<String> {
+        fn all_vars(expr: &Expression, vars: &mut Vec<String>) {
+            match expr {
+                Expression::Var(var) if !vars.contains(var) => vars.push(var.clone()),
+                _ => expr
+                    .children()
+                    .into_iter()
+                    .for_each(|(e, _)| all_vars(e, vars)),
+            }
+        }
+        let mut vars = vec![];
+        all_vars(self, &mut vars);
+        vars.retain(|var| self.has_var(var));
+        vars
+    }
+
+    /// Test whether the variable `name` occurs free in the expression.
+    pub fn has_var(&self, name: &str) -> bool {
+        use Expression::*;
+        let any = |v: &[(Expression, Span)]| v.iter().any(|(e, _)| e.has_var(name));
+        match self {
+            Var(var) => var == name,
+            Val(_) | Const(_) => false,
+            Neg(e) | Func(_, e) | Let(_, e) | Simplify(e) => e.0.has_var(name),
+            Add(v) | Mul(v) | Call(_, v) | List(v) | Comma(v) => any(v),
+            Sub(args) | Frac(args) | Exp(args) | Root(args) | Convert(args) | Index(args)
+            | Range(args) | Equation(args) => any(&args[..]),
+            Lambda(params, body) => !params.iter().any(|p| p == name) && body.0.has_var(name),
+            Solve(var, eq, guesses) => var != name && eq.0.has_var(name) || any(guesses),
+            Derivative(var, e) => var != name && e.0.has_var(name),
+            Sum(var, args) | Product(var, args) | Integral(var, args) => {
+                any(&args[..2]) || var != name && args[2].0.has_var(name)
+            }
+        }
+    }
+}
+
 impl<T: Into<Value>> From<T> for Expression {
     fn from(val: T) -> Expression {
         Expression::Val(val.into())
@@ -0,0 +1,716 @@
+//! Formatting values for display.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use num::rational::{BigRational, Ratio};
+use num::traits::{CheckedAdd, CheckedSub, One, Signed, Zero};
+use num::Integer;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::printer::write_lambda;
+use crate::{pi_multiple, si_prefix_symbol, simple_fraction, Context, Matrix, Symbolic, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// How many digits of a float to display.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Precision {
+    /// As many digits as needed to uniquely identify the float.
+    #[default]
+    Shortest,
+    /// A maximum number of significant digits. Trailing zeros are omitted.
+    Significant(usize),
+    /// A fixed number of digits after the decimal point (of the mantissa, in
+    /// scientific notation).
+    Decimals(usize),
+}
+
+/// How to write floats that are outside of the context's notation range.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Notation {
+    /// Scientific notation, such as `4.7e-6`.
+    #[default]
+    Scientific,
+    /// Engineering notation, where the exponent is a multiple of three, such
+    /// as `47e-6`.
+    Engineering,
+    /// SI prefixes, such as `4.7 µ`. Prefixes are used regardless of the
+    /// notation range, falling back to engineering notation for numbers
+    /// beyond the largest and smallest prefixes.
+    Si,
+}
+
+impl FromStr for Notation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Notation, ()> {
+        match s {
+            "sci" => Ok(Notation::Scientific),
+            "eng" => Ok(Notation::Engineering),
+            "si" => Ok(Notation::Si),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How to round a number to fewer digits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rounding {
+    /// Round to the nearest, with ties going to the even neighbour.
+    #[default]
+    HalfEven,
+    /// Round to the nearest, with ties going away from zero.
+    HalfAwayFromZero,
+    /// Round toward zero, i.e., truncate.
+    TowardZero,
+    /// Round toward negative infinity.
+    Floor,
+    /// Round toward positive infinity.
+    Ceiling,
+}
+
+impl Rounding {
+    /// Round a rational number to an integer.
+    ///
+    /// Returns `None` if the calculation overflows.
+    pub fn round_ratio(self, r: Ratio<i64>) -> Option<Ratio<i64>> {
+        let floor = r.floor();
+        let up = match self {
+            Rounding::Floor => return Some(floor),
+            Rounding::Ceiling => return Some(r.ceil()),
+            Rounding::TowardZero => return Some(r.trunc()),
+            Rounding::HalfEven | Rounding::HalfAwayFromZero => {
+                match r.checked_sub(&floor)?.cmp(&Ratio::new(1, 2)) {
+                    Ordering::Less => false,
+                    Ordering::Greater => true,
+                    Ordering::Equal if self == Rounding::HalfEven => floor.numer() % 2 != 0,
+                    Ordering::Equal => r > Ratio::zero(),
+                }
+            }
+        };
+        if up {
+            floor.checked_add(&Ratio::one())
+        } else {
+            Some(floor)
+        }
+    }
+
+    /// Round a big rational number to an integer.
+    pub fn round_big(self, r: BigRational) -> BigRational {
+        let floor = r.floor();
+        let up = match self {
+            Rounding::Floor => return floor,
+            Rounding::Ceiling => return r.ceil(),
+            Rounding::TowardZero => return r.trunc(),
+            Rounding::HalfEven | Rounding::HalfAwayFromZero => {
+                let half = BigRational::new(1.into(), 2.into());
+                match (&r - &floor).cmp(&half) {
+                    Ordering::Less => false,
+                    Ordering::Greater => true,
+                    Ordering::Equal if self == Rounding::HalfEven => floor.numer().is_odd(),
+                    Ordering::Equal => r.is_positive(),
+                }
+            }
+        };
+        if up {
+            floor + BigRational::one()
+        } else {
+            floor
+        }
+    }
+
+    /// Round a float to an integer.
+    pub fn round_f64(self, x: f64) -> f64 {
+        match self {
+            Rounding::HalfEven => x.round_ties_even(),
+            Rounding::HalfAwayFromZero => x.round(),
+            Rounding::TowardZero => x.trunc(),
+            Rounding::Floor => x.floor(),
+            Rounding::Ceiling => x.ceil(),
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Rounding, ()> {
+        match s {
+            "half-even" => Ok(Rounding::HalfEven),
+            "half-away" => Ok(Rounding::HalfAwayFromZero),
+            "toward-zero" => Ok(Rounding::TowardZero),
+            "floor" => Ok(Rounding::Floor),
+            "ceiling" => Ok(Rounding::Ceiling),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the REPL shows results.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DisplayMode {
+    /// The result alone, as plain text.
+    #[default]
+    Text,
+    /// The input and result laid out over multiple lines as in a textbook,
+    /// with stacked fractions, superscripts and radicals.
+    Unicode,
+    /// The input and result as a MathML element.
+    MathMl,
+    /// The input and result as LaTeX math.
+    Latex,
+}
+
+impl FromStr for DisplayMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<DisplayMode, ()> {
+        match s {
+            "text" => Ok(DisplayMode::Text),
+            "unicode" => Ok(DisplayMode::Unicode),
+            "mathml" => Ok(DisplayMode::MathMl),
+            "latex" => Ok(DisplayMode::Latex),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let s = match self {
+            Rounding::HalfEven => "half-even",
+            Rounding::HalfAwayFromZero => "half-away",
+            Rounding::TowardZero => "toward-zero",
+            Rounding::Floor => "floor",
+            Rounding::Ceiling => "ceiling",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The characters used to write a number.
+#[derive(Copy, Clone, Debug)]
+struct Separators {
+    decimal: char,
+    /// The digit group separator, or `None` if digits are not grouped.
+    group: Option<char>,
+}
+
+impl Separators {
+    fn new(ctx: &Context) -> Separators {
+        Separators {
+            decimal: ctx.separators.0,
+            group: if ctx.grouping {
+                Some(ctx.separators.1)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Write an integer, grouping its digits in threes if enabled.
+fn write_int(f: &mut Formatter, seps: Separators, num: i64) -> fmt::Result {
+    write_digits(f, seps, num < 0, &num.unsigned_abs().to_string())
+}
+
+/// Write an integer given its sign and decimal digits, separating groups of
+/// three digits if needed.
+fn write_digits(f: &mut Formatter, seps: Separators, neg: bool, digits: &str) -> fmt::Result {
+    if neg {
+        write!(f, "-")?;
+    }
+    for (i, d) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            if let Some(group) = seps.group {
+                write!(f, "{}", group)?;
+            }
+        }
+        write!(f, "{}", d)?;
+    }
+    Ok(())
+}
+
+/// The decimal digits of a finite float in scientific notation, i.e.,
+/// `d1.d2d3... * 10^exp`.
+#[derive(Clone, Debug, PartialEq)]
+struct Decimal {
+    neg: bool,
+    digits: Vec<u8>,
+    exp: i32,
+}
+
+impl Decimal {
+    /// Get the shortest decimal digits that uniquely identify `num`.
+    fn new(num: f64) -> Decimal {
+        let s = format!("{:e}", num.abs());
+        let e = s.find('e').expect("float has no exponent");
+        let digits = s[..e]
+            .bytes()
+            .filter(u8::is_ascii_digit)
+            .map(|b| b - b'0')
+            .collect();
+        Decimal {
+            neg: num.is_sign_negative(),
+            digits,
+            exp: s[(e + 1)..].parse().expect("invalid exponent"),
+        }
+    }
+
+    /// Round to `keep` significant digits. `keep` may be zero or negative, in
+    /// which case the number is rounded to a place above its leading digit.
+    ///
+    /// Rounding is performed on the shortest decimal representation, so a
+    /// float such as `2.675` rounds as if it were exactly that number.
+    fn round(&mut self, keep: i32, mode: Rounding) {
+        let mut keep = keep;
+        if keep < 0 {
+            // Shift in leading zeros so that at least zero digits are kept
+            let zeros = (-keep) as usize;
+            self.digits.splice(0..0, std::iter::repeat_n(0, zeros));
+            self.exp += zeros as i32;
+            keep = 0;
+        }
+        let keep = keep as usize;
+        if keep >= self.digits.len() {
+            return;
+        }
+        let rest = self.digits.split_off(keep);
+        if rest.iter().all(|&d| d == 0) {
+            return;
+        }
+        // Compare the discarded digits with one half
+        let half = match rest[0].cmp(&5) {
+            Ordering::Equal if rest[1..].iter().any(|&d| d != 0) => Ordering::Greater,
+            o => o,
+        };
+        let up = match mode {
+            Rounding::HalfEven => match half {
+                Ordering::Equal => self.digits.last().is_some_and(|d| d % 2 == 1),
+                o => o == Ordering::Greater,
+            },
+            Rounding::HalfAwayFromZero => half != Ordering::Less,
+            Rounding::TowardZero => false,
+            Rounding::Floor => self.neg,
+            Rounding::Ceiling => !self.neg,
+        };
+        if up {
+            self.increment();
+        }
+        if self.digits.is_empty() {
+            self.digits.push(0);
+        }
+        // A negative number that rounds to zero is written as zero
+        if self.is_zero() {
+            self.neg = false;
+            self.exp = 0;
+        }
+    }
+
+    /// Add one unit in the last kept place.
+    fn increment(&mut self) {
+        for d in self.digits.iter_mut().rev() {
+            if *d == 9 {
+                *d = 0;
+            } else {
+                *d += 1;
+                return;
+            }
+        }
+        // Carried past the leading digit (e.g., 9.99 to 10.0)
+        let len = self.digits.len().max(1);
+        self.digits.insert(0, 1);
+        self.digits.truncate(len);
+        self.exp += 1;
+    }
+
+    /// Whether the digits are all zero.
+    fn is_zero(&self) -> bool {
+        self.digits.iter().all(|&d| d == 0)
+    }
+
+    /// Remove trailing zeros, keeping at least one digit.
+    fn trim(&mut self) {
+        while self.digits.len() > 1 && self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+
+    /// Pad with trailing zeros or drop trailing digits to get exactly `len`
+    /// digits. Dropped digits must already have been rounded away.
+    fn pad(&mut self, len: usize) {
+        self.digits.resize(len, 0);
+    }
+
+    fn write_sign(&self, f: &mut Formatter) -> fmt::Result {
+        if self.neg {
+            write!(f, "-")?;
+        }
+        Ok(())
+    }
+
+    /// Write the number without an exponent.
+    fn write_plain(&self, f: &mut Formatter, seps: Separators) -> fmt::Result {
+        self.write_sign(f)?;
+        if self.exp < 0 {
+            write!(f, "0{}", seps.decimal)?;
+            for _ in 0..(-self.exp - 1) {
+                write!(f, "0")?;
+            }
+            for d in &self.digits {
+                write!(f, "{}", d)?;
+            }
+        } else {
+            let int_len = self.exp as usize + 1;
+            for i in 0..int_len {
+                if i > 0 && (int_len - i).is_multiple_of(3) {
+                    if let Some(group) = seps.group {
+                        write!(f, "{}", group)?;
+                    }
+                }
+                write!(f, "{}", self.digits.get(i).unwrap_or(&0))?;
+            }
+            if self.digits.len() > int_len {
+                write!(f, "{}", seps.decimal)?;
+                for d in &self.digits[int_len..] {
+                    write!(f, "{}", d)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the number with an exponent that is a multiple of three, either
+    /// in `e` notation or, if `si` is set and possible, as an SI prefix.
+    fn write_engineering(
+        &self,
+        f: &mut Formatter,
+        seps: Separators,
+        plus: bool,
+        si: bool,
+    ) -> fmt::Result {
+        let shift = self.exp.rem_euclid(3);
+        let exp = self.exp - shift;
+        let mantissa = Decimal {
+            neg: self.neg,
+            digits: self.digits.clone(),
+            exp: shift,
+        };
+        mantissa.write_plain(f, seps)?;
+        if si {
+            if exp == 0 {
+                return Ok(());
+            } else if let Some(prefix) = si_prefix_symbol(exp) {
+                return write!(f, " {}", prefix);
+            }
+        }
+        if plus && exp >= 0 {
+            write!(f, "e+{}", exp)
+        } else {
+            write!(f, "e{}", exp)
+        }
+    }
+
+    /// Write the number in scientific notation. The exponent is written with
+    /// an explicit sign if `plus` is set.
+    fn write_scientific(&self, f: &mut Formatter, seps: Separators, plus: bool) -> fmt::Result {
+        self.write_sign(f)?;
+        write!(f, "{}", self.digits[0])?;
+        if self.digits.len() > 1 {
+            write!(f, "{}", seps.decimal)?;
+            for d in &self.digits[1..] {
+                write!(f, "{}", d)?;
+            }
+        }
+        if plus && self.exp >= 0 {
+            write!(f, "e+{}", self.exp)
+        } else {
+            write!(f, "e{}", self.exp)
+        }
+    }
+}
+
+/// A value formatted according to a context's display options.
+pub(crate) struct Format<'a> {
+    pub ctx: &'a Context,
+    pub num: &'a Value,
+}
+
+impl Display for Format<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.num {
+            Value::Ratio(num) => {
+                let seps = Separators::new(self.ctx);
+                write_int(f, seps, *num.numer())?;
+                if !num.is_integer() {
+                    write!(f, " / ")?;
+                    write_int(f, seps, *num.denom())?;
+                }
+                Ok(())
+            }
+            Value::BigRatio(num) => {
+                let seps = Separators::new(self.ctx);
+                let numer = num.numer().abs().to_string();
+                write_digits(f, seps, num.is_negative(), &numer)?;
+                if !num.is_integer() {
+                    write!(f, " / ")?;
+                    write_digits(f, seps, false, &num.denom().to_string())?;
+                }
+                Ok(())
+            }
+            Value::Float(num) => {
+                write_float(f, self.ctx, *num)?;
+                if self.ctx.annotate {
+                    write_annotation(f, *num)?;
+                }
+                Ok(())
+            }
+            Value::Symbolic(sym) => {
+                write_symbolic(f, self.ctx, *sym)?;
+                write!(f, " (\u{2248} ")?;
+                write_float(f, self.ctx, sym.to_f64())?;
+                write!(f, ")")
+            }
+            Value::Quantity(q) => {
+                let (num, unit) = q.display_parts();
+                write_float(f, &quantity_options(self.ctx), num)?;
+                write!(f, " {}", unit)
+            }
+            Value::Factors(factors) => write_factors(f, self.ctx, factors),
+            Value::List(items) => {
+                // Avoid confusion with a decimal comma
+                let sep = if self.ctx.separators.0 == ',' {
+                    "; "
+                } else {
+                    ", "
+                };
+                write!(f, "[")?;
+                for (i, num) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", sep)?;
+                    }
+                    write!(f, "{}", Format { ctx: self.ctx, num })?;
+                }
+                write!(f, "]")
+            }
+            Value::Matrix(m) => write_matrix(f, self.ctx, m),
+            Value::Func(params, body) => write_lambda(f, params, &body.0),
+            Value::Builtin(func) => write!(f, "{}", func),
+        }
+    }
+}
+
+/// Get the options to write the number of a quantity with, which has no SI
+/// prefix, since it would read as part of the unit.
+pub(crate) fn quantity_options(ctx: &Context) -> Context {
+    let notation = match ctx.notation {
+        Notation::Si => Notation::Engineering,
+        notation => notation,
+    };
+    Context {
+        notation,
+        ..ctx.with_vars(Default::default())
+    }
+}
+
+/// A float formatted according to a context's notation, precision, and
+/// rounding, without an annotation.
+pub(crate) struct FloatFormat<'a> {
+    pub ctx: &'a Context,
+    pub num: f64,
+}
+
+impl Display for FloatFormat<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_float(f, self.ctx, self.num)
+    }
+}
+
+/// Write a matrix as a table, with one row per line and the entries of each
+/// column aligned to the right.
+fn write_matrix(f: &mut Formatter, ctx: &Context, m: &Matrix) -> fmt::Result {
+    let cells: Vec<String> = (0..m.rows())
+        .flat_map(|i| m.row(i))
+        .map(|num| Format { ctx, num }.to_string())
+        .collect();
+    let widths: Vec<usize> = (0..m.cols())
+        .map(|j| {
+            cells[j..]
+                .iter()
+                .step_by(m.cols())
+                .map(|s| s.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for (i, row) in cells.chunks(m.cols()).enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "[")?;
+        for (j, cell) in row.iter().enumerate() {
+            if j > 0 {
+                write!(f, "  ")?;
+            }
+            write!(f, "{:>1$}", cell, widths[j])?;
+        }
+        write!(f, "]")?;
+    }
+    Ok(())
+}
+
+/// The layout of a written float.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Scientific,
+    Engineering,
+    Si,
+}
+
+impl Style {
+    /// Get the number of digits before the decimal point for a number with
+    /// the given exponent.
+    fn int_digits(self, exp: i32) -> i32 {
+        match self {
+            Style::Plain => exp + 1,
+            Style::Scientific => 1,
+            Style::Engineering | Style::Si => exp.rem_euclid(3) + 1,
+        }
+    }
+}
+
+/// Write a float using the context's notation, precision, and rounding.
+fn write_float(f: &mut Formatter, ctx: &Context, num: f64) -> fmt::Result {
+    if !num.is_finite() {
+        return write!(f, "{}", num);
+    }
+    let mag = num.abs();
+    // Show number normally (no scientific notation) if within the range or
+    // equal to zero
+    let plain = ctx.notation_range.0 < mag && mag < ctx.notation_range.1 || mag == 0.0;
+    let style = match ctx.notation {
+        Notation::Si if mag != 0.0 => Style::Si,
+        _ if plain => Style::Plain,
+        Notation::Engineering => Style::Engineering,
+        _ => Style::Scientific,
+    };
+    let seps = Separators::new(ctx);
+    let mut dec = Decimal::new(num);
+    match ctx.precision {
+        Precision::Shortest => (),
+        Precision::Significant(n) => {
+            dec.round(n as i32, ctx.rounding);
+            dec.trim();
+        }
+        Precision::Decimals(n) => {
+            dec.round(style.int_digits(dec.exp) + n as i32, ctx.rounding);
+            // Rounding may have carried into a new digit or to zero, which
+            // changes the number of integer digits
+            dec.pad((style.int_digits(dec.exp) + n as i32).max(1) as usize);
+        }
+    }
+    // Force '+' on exponent for large numbers
+    match style {
+        Style::Plain => dec.write_plain(f, seps),
+        Style::Scientific => dec.write_scientific(f, seps, mag >= 1.0),
+        Style::Engineering => dec.write_engineering(f, seps, mag >= 1.0, false),
+        Style::Si => dec.write_engineering(f, seps, mag >= 1.0, true),
+    }
+}
+
+/// Write a simple fraction or multiple of pi that `num` is practically equal
+/// to, if there is one.
+fn write_annotation(f: &mut Formatter, num: f64) -> fmt::Result {
+    if let Some(r) = simple_fraction(num) {
+        // Integers that are exactly equal need no annotation
+        if !r.is_integer() || *r.numer() as f64 != num {
+            write_fraction(f, *r.numer(), *r.denom(), "")?;
+        }
+    } else if let Some(r) = pi_multiple(num) {
+        write_fraction(f, *r.numer(), *r.denom(), "pi")?;
+    }
+    Ok(())
+}
+
+/// Write a prime factorisation, such as `2^3 * 3 * 5`.
+fn write_factors(f: &mut Formatter, ctx: &Context, factors: &[(i64, u32)]) -> fmt::Result {
+    if factors.is_empty() {
+        return write!(f, "1");
+    }
+    let seps = Separators::new(ctx);
+    for (i, &(p, e)) in factors.iter().enumerate() {
+        if i > 0 {
+            write!(f, " * ")?;
+        }
+        write_int(f, seps, p)?;
+        if e > 1 {
+            write!(f, "^{}", e)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write an exact symbolic value, such as `2 pi / 3` or `1 / e^2`.
+fn write_symbolic(f: &mut Formatter, ctx: &Context, sym: Symbolic) -> fmt::Result {
+    let seps = Separators::new(ctx);
+    let power = sym.power.abs();
+    let term = if power.is_one() {
+        sym.base.to_string()
+    } else if power.is_integer() {
+        format!("{}^{}", sym.base, power)
+    } else {
+        format!("{}^({})", sym.base, power)
+    };
+    let numer = *sym.coef.numer();
+    let denom = *sym.coef.denom();
+    if numer < 0 {
+        write!(f, "-")?;
+    }
+    if sym.power > Ratio::zero() {
+        if numer.abs() != 1 {
+            write_int(f, seps, numer.abs())?;
+            write!(f, " ")?;
+        }
+        write!(f, "{}", term)?;
+        if denom != 1 {
+            write!(f, " / ")?;
+            write_int(f, seps, denom)?;
+        }
+    } else {
+        write_int(f, seps, numer.abs())?;
+        write!(f, " / ")?;
+        if denom != 1 {
+            write!(f, "(")?;
+            write_int(f, seps, denom)?;
+            write!(f, " {})", term)?;
+        } else {
+            write!(f, "{}", term)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write an annotation of the form `(≈ numer unit / denom)`.
+fn write_fraction(f: &mut Formatter, numer: i64, denom: i64, unit: &str) -> fmt::Result {
+    write!(f, " (\u{2248} ")?;
+    match (numer, unit) {
+        (n, "") => write!(f, "{}", n)?,
+        (1, u) => write!(f, "{}", u)?,
+        (-1, u) => write!(f, "-{}", u)?,
+        (n, u) => write!(f, "{} {}", n, u)?,
+    }
+    if denom != 1 {
+        write!(f, " / {}", denom)?;
+    }
+    write!(f, ")")
+}
@@ -0,0 +1,79 @@
+use super::Decimal;
+use crate::Rounding;
+use crate::Rounding::*;
+
+fn dec(num: f64) -> Decimal {
+    Decimal::new(num)
+}
+
+fn round(num: f64, keep: i32, mode: Rounding) -> (Vec<u8>, i32) {
+    let mut d = dec(num);
+    d.round(keep, mode);
+    (d.digits, d.exp)
+}
+
+#[test]
+fn digits() {
+    assert_eq!(dec(123.45).digits, vec![1, 2, 3, 4, 5]);
+    assert_eq!(dec(123.45).exp, 2);
+    assert_eq!(dec(-0.00123).digits, vec![1, 2, 3]);
+    assert_eq!(dec(-0.00123).exp, -3);
+    assert!(dec(-0.00123).neg);
+    assert_eq!(dec(0.0).digits, vec![0]);
+}
+
+#[test]
+fn round_half() {
+    assert_eq!(round(2.5, 1, HalfEven), (vec![2], 0));
+    assert_eq!(round(3.5, 1, HalfEven), (vec![4], 0));
+    assert_eq!(round(2.5, 1, HalfAwayFromZero), (vec![3], 0));
+    assert_eq!(round(-2.5, 1, HalfAwayFromZero), (vec![3], 0));
+    assert_eq!(round(2.675, 3, HalfAwayFromZero), (vec![2, 6, 8], 0));
+    assert_eq!(round(2.6751, 3, HalfEven), (vec![2, 6, 8], 0));
+    assert_eq!(round(2.674, 3, HalfAwayFromZero), (vec![2, 6, 7], 0));
+}
+
+#[test]
+fn round_directed() {
+    assert_eq!(round(2.9, 1, TowardZero), (vec![2], 0));
+    assert_eq!(round(-2.9, 1, TowardZero), (vec![2], 0));
+    assert_eq!(round(2.1, 1, Ceiling), (vec![3], 0));
+    assert_eq!(round(-2.1, 1, Ceiling), (vec![2], 0));
+    assert_eq!(round(2.1, 1, Floor), (vec![2], 0));
+    assert_eq!(round(-2.1, 1, Floor), (vec![3], 0));
+}
+
+#[test]
+fn round_carry() {
+    assert_eq!(round(9.99, 2, HalfEven), (vec![1, 0], 1));
+    assert_eq!(round(0.006, 0, HalfEven), (vec![1], -2));
+    assert_eq!(round(0.0004, -1, HalfEven), (vec![0], 0));
+    let mut d = dec(-0.0004);
+    d.round(0, HalfEven);
+    assert!(!d.neg);
+}
+
+#[test]
+fn round_ratio() {
+    use num::rational::Ratio;
+    let r = |n, d| Ratio::new(n, d);
+    assert_eq!(HalfEven.round_ratio(r(5, 2)), Some(r(2, 1)));
+    assert_eq!(HalfEven.round_ratio(r(-5, 2)), Some(r(-2, 1)));
+    assert_eq!(HalfEven.round_ratio(r(7, 2)), Some(r(4, 1)));
+    assert_eq!(HalfAwayFromZero.round_ratio(r(5, 2)), Some(r(3, 1)));
+    assert_eq!(HalfAwayFromZero.round_ratio(r(-5, 2)), Some(r(-3, 1)));
+    assert_eq!(HalfAwayFromZero.round_ratio(r(-7, 3)), Some(r(-2, 1)));
+    assert_eq!(TowardZero.round_ratio(r(-7, 3)), Some(r(-2, 1)));
+    assert_eq!(Floor.round_ratio(r(-7, 3)), Some(r(-3, 1)));
+    assert_eq!(Ceiling.round_ratio(r(-7, 3)), Some(r(-2, 1)));
+    assert_eq!(Ceiling.round_ratio(r(7, 3)), Some(r(3, 1)));
+}
+
+#[test]
+fn round_f64() {
+    assert_eq!(HalfEven.round_f64(2.5), 2.0);
+    assert_eq!(HalfAwayFromZero.round_f64(2.5), 3.0);
+    assert_eq!(TowardZero.round_f64(-2.7), -2.0);
+    assert_eq!(Floor.round_f64(-2.2), -3.0);
+    assert_eq!(Ceiling.round_f64(2.2), 3.0);
+}
@@ -2,11 +2,14 @@ use std::convert::TryFrom;
 
 use if_chain::if_chain;
 use num::pow::checked_pow;
-use num::rational::Ratio;
-use num::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+use num::rational::{BigRational, Ratio};
+use num::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Zero};
 
 use crate::Value::*;
-use crate::{Context, Error, Expression, Result, Span, Value};
+use crate::{
+    differentiate, find_root, integrate, optimize, simplify, symbolic_binary, unit, Context, Error,
+    Expression, Function, Result, Span, Value,
+};
 
 #[cfg(test)]
 mod tests;
@@ -14,65 +17,492 @@ mod tests;
 type ExprSpan = (Expression, Span);
 
 fn neg(expr: &ExprSpan, c: &mut Context, span: &Span) -> Result<Value> {
-    match eval(expr, c)? {
+    negate(eval(expr, c)?.expand(), span)
+}
+
+/// Negate a value, element-wise if it is a list.
+fn negate(val: Value, span: &Span) -> Result<Value> {
+    match val {
         Float(f) => Ok((-f).into()),
         Ratio(r) => Ok((-r).into()),
+        BigRatio(r) => Ok((-r).into()),
+        Symbolic(s) => Ok((-s).into()),
+        Quantity(q) => Ok(Quantity(unit::Quantity {
+            value: -q.value,
+            ..q
+        })),
+        List(v) => map_list(v, |x| negate(x, span)),
+        Matrix(m) => m.try_map(|x| negate(x.clone(), span)).map(Matrix),
         _ => Err((Error::Type, span.clone())),
     }
 }
 
 /// Converts the value to a float.
 fn to_f64(val: &Value, span: &Span) -> Result<f64> {
+    val.as_f64().ok_or_else(|| (Error::Type, span.clone()))
+}
+
+/// Apply a function to each element of a list.
+fn map_list(v: Vec<Value>, f: impl FnMut(Value) -> Result<Value>) -> Result<Value> {
+    v.into_iter()
+        .map(Value::expand)
+        .map(f)
+        .collect::<Result<_>>()
+        .map(List)
+}
+
+/// A binary arithmetic operator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    fn float(self, a: f64, b: f64) -> f64 {
+        match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+        }
+    }
+
+    fn ratio(self, a: Ratio<i64>, b: Ratio<i64>) -> Option<Ratio<i64>> {
+        match self {
+            Op::Add => a.checked_add(&b),
+            Op::Sub => a.checked_sub(&b),
+            Op::Mul => a.checked_mul(&b),
+            Op::Div if b.is_zero() => None,
+            Op::Div => a.checked_div(&b),
+        }
+    }
+
+    fn big(self, a: BigRational, b: BigRational) -> Option<BigRational> {
+        match self {
+            Op::Add => Some(a + b),
+            Op::Sub => Some(a - b),
+            Op::Mul => Some(a * b),
+            Op::Div if b.is_zero() => None,
+            Op::Div => Some(a / b),
+        }
+    }
+
+    fn quantity(self, a: &unit::Quantity, b: &unit::Quantity) -> Option<unit::Quantity> {
+        match self {
+            Op::Add => a.add(b),
+            Op::Sub => a.sub(b),
+            Op::Mul => a.mul(b),
+            Op::Div => a.div(b),
+        }
+    }
+
+    fn symbolic(self, a: &Value, b: &Value) -> Option<Value> {
+        match self {
+            Op::Add => symbolic_binary(a, b, |a, b| a.checked_add(b)),
+            Op::Sub => symbolic_binary(a, b, |a, b| a.checked_sub(b)),
+            Op::Mul => symbolic_binary(a, b, |a, b| a.checked_mul(b)),
+            Op::Div => symbolic_binary(a, b, |a, b| a.checked_div(b)),
+        }
+    }
+}
+
+/// Converts the value to a quantity, treating numbers as dimensionless.
+fn to_quantity(val: &Value, span: &Span) -> Result<unit::Quantity> {
     match val {
-        Value::Ratio(f) => Ok(*f.numer() as f64 / *f.denom() as f64),
-        Value::Float(f) => Ok(*f),
-        _ => Err((Error::Type, span.clone())),
+        Quantity(q) => Ok(q.clone()),
+        _ => Ok(unit::Quantity::scalar(to_f64(val, span)?)),
     }
 }
 
-fn apply<F, G>(
-    mut f: F,
-    mut g: G,
-    exprs: &[ExprSpan],
-    c: &mut Context,
-    span: &Span,
-) -> Result<Value>
-where
-    F: FnMut(f64, f64) -> f64,
-    G: FnMut(Ratio<i64>, Ratio<i64>) -> Option<Ratio<i64>>,
-{
+/// Apply a binary operator to two values.
+///
+/// Lists are combined element-wise, and a list combined with a single value
+/// applies the operator to each element. Matrices are multiplied by each
+/// other and by lists as in linear algebra, but are otherwise treated like
+/// lists.
+pub(crate) fn binary(op: Op, lhs: &Value, rhs: &Value, span: &Span) -> Result<Value> {
+    match (lhs, rhs) {
+        (Matrix(a), Matrix(b)) => match op {
+            Op::Mul => matmul(a, b, span).map(Matrix),
+            Op::Add | Op::Sub if a.rows() == b.rows() && a.cols() == b.cols() => {
+                crate::Matrix::try_build(a.rows(), a.cols(), |i, j| {
+                    binary(op, a.get(i, j), b.get(i, j), span)
+                })
+                .map(Matrix)
+            }
+            Op::Add | Op::Sub => Err((Error::Shape, span.clone())),
+            Op::Div => Err((Error::Type, span.clone())),
+        },
+        (Matrix(a), List(v)) if op == Op::Mul => {
+            let col = crate::Matrix::column(v.clone()).ok_or((Error::Shape, span.clone()))?;
+            Ok(List(matmul(a, &col, span)?.into_entries()))
+        }
+        (List(v), Matrix(b)) if op == Op::Mul => {
+            let row =
+                crate::Matrix::from_rows(vec![v.clone()]).ok_or((Error::Shape, span.clone()))?;
+            Ok(List(matmul(&row, b, span)?.into_entries()))
+        }
+        (Matrix(_), List(_)) | (List(_), Matrix(_)) => Err((Error::Type, span.clone())),
+        (Matrix(a), _) => a.try_map(|x| binary(op, x, rhs, span)).map(Matrix),
+        (_, Matrix(b)) => b.try_map(|x| binary(op, lhs, x, span)).map(Matrix),
+        (List(left), List(right)) => {
+            if left.len() != right.len() {
+                return Err((Error::Length, span.clone()));
+            }
+            left.iter()
+                .zip(right)
+                .map(|(a, b)| binary(op, &a.clone().expand(), &b.clone().expand(), span))
+                .collect::<Result<_>>()
+                .map(List)
+        }
+        (List(left), _) => map_list(left.clone(), |a| binary(op, &a, rhs, span)),
+        (_, List(right)) => map_list(right.clone(), |b| binary(op, lhs, &b, span)),
+        (Ratio(left), Ratio(right)) => match op.ratio(*left, *right) {
+            Some(x) => Ok(x.into()),
+            None => Ok(op.float(to_f64(lhs, span)?, to_f64(rhs, span)?).into()),
+        },
+        (Quantity(_), _) | (_, Quantity(_)) => {
+            let lhs = to_quantity(lhs, span)?;
+            let rhs = to_quantity(rhs, span)?;
+            match op.quantity(&lhs, &rhs) {
+                Some(q) => Ok(q.into_value()),
+                None => Err((Error::Dimension, span.clone())),
+            }
+        }
+        (Symbolic(_), _) | (_, Symbolic(_)) => match op.symbolic(lhs, rhs) {
+            Some(x) => Ok(x),
+            None => Ok(op.float(to_f64(lhs, span)?, to_f64(rhs, span)?).into()),
+        },
+        (BigRatio(_), _) | (_, BigRatio(_)) => {
+            let exact = match (lhs.to_big(), rhs.to_big()) {
+                (Some(a), Some(b)) => op.big(a, b),
+                _ => None,
+            };
+            match exact {
+                Some(x) => Ok(x.into()),
+                None => Ok(op.float(to_f64(lhs, span)?, to_f64(rhs, span)?).into()),
+            }
+        }
+        (lhs, rhs) => Ok(op.float(to_f64(lhs, span)?, to_f64(rhs, span)?).into()),
+    }
+}
+
+/// Multiply two matrices.
+fn matmul(a: &crate::Matrix, b: &crate::Matrix, span: &Span) -> Result<crate::Matrix> {
+    if a.cols() != b.rows() {
+        return Err((Error::Shape, span.clone()));
+    }
+    crate::Matrix::try_build(a.rows(), b.cols(), |i, j| {
+        let mut acc = binary(Op::Mul, a.get(i, 0), b.get(0, j), span)?;
+        for k in 1..a.cols() {
+            let term = binary(Op::Mul, a.get(i, k), b.get(k, j), span)?;
+            acc = binary(Op::Add, &acc, &term, span)?;
+        }
+        Ok(acc)
+    })
+}
+
+/// Raise a square matrix to an integer power, using its inverse for
+/// negative powers.
+fn matrix_pow(m: &crate::Matrix, n: i64, span: &Span) -> Result<crate::Matrix> {
+    if !m.is_square() {
+        return Err((Error::Shape, span.clone()));
+    }
+    let mut base = if n < 0 {
+        match m.inverse() {
+            Some(Some(inv)) => inv,
+            Some(None) => return Err((Error::Domain, span.clone())),
+            None => return Err((Error::Type, span.clone())),
+        }
+    } else {
+        m.clone()
+    };
+    let mut result = crate::Matrix::identity(m.rows());
+    let mut n = n.unsigned_abs();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = matmul(&result, &base, span)?;
+        }
+        n >>= 1;
+        if n > 0 {
+            base = matmul(&base, &base, span)?;
+        }
+    }
+    Ok(result)
+}
+
+/// Convert a list of rows of numbers into a matrix if the rows all have the
+/// same length, or keep it as a list otherwise.
+fn list_or_matrix(items: Vec<Value>) -> Value {
+    let rows: Option<Vec<Vec<Value>>> = items
+        .iter()
+        .map(|x| match x {
+            List(row) if !row.iter().any(|x| matches!(x, List(_) | Matrix(_))) => {
+                Some(row.iter().cloned().map(Value::expand).collect())
+            }
+            _ => None,
+        })
+        .collect();
+    match rows.and_then(crate::Matrix::from_rows) {
+        Some(m) => Matrix(m),
+        None => List(items),
+    }
+}
+
+fn apply(op: Op, exprs: &[ExprSpan], c: &mut Context, span: &Span) -> Result<Value> {
     debug_assert!(!exprs.is_empty());
-    let mut acc = eval(&exprs[0], c)?;
+    let mut acc = eval(&exprs[0], c)?.expand();
     for expr in &exprs[1..] {
-        let rhs = eval(expr, c)?;
-        acc = match (&acc, &rhs) {
-            (Ratio(left), Ratio(right)) => match g(*left, *right) {
-                Some(x) => x.into(),
-                None => f(to_f64(&acc, span)?, to_f64(&rhs, span)?).into(),
-            },
-            (lhs, rhs) => f(to_f64(&lhs, span)?, to_f64(&rhs, span)?).into(),
-        };
+        let rhs = eval(expr, c)?.expand();
+        acc = binary(op, &acc, &rhs, span)?;
     }
     Ok(acc)
 }
 
 fn eval_exp(lhs: &ExprSpan, rhs: &ExprSpan, c: &mut Context, span: &Span) -> Result<Value> {
-    let lhs = eval(&lhs, c)?;
-    let rhs = eval(&rhs, c)?;
+    let lhs = eval(lhs, c)?.expand();
+    let rhs = eval(rhs, c)?.expand();
     do_exp(lhs, rhs, span)
 }
 
 fn eval_root(lhs: &ExprSpan, rhs: &ExprSpan, c: &mut Context, span: &Span) -> Result<Value> {
-    let lhs = eval(&lhs, c)?;
-    let rhs = match eval(&rhs, c)? {
+    let lhs = eval(lhs, c)?.expand();
+    let rhs = match eval(rhs, c)?.expand() {
         Float(f) => f.recip().into(),
         Ratio(r) => r.recip().into(),
+        Symbolic(s) => s.to_f64().recip().into(),
         _ => return Err((Error::Type, span.clone())),
     };
     do_exp(lhs, rhs, span)
 }
 
-fn do_exp(lhs: Value, rhs: Value, span: &Span) -> Result<Value> {
+/// Convert the quantity `lhs` to the unit of the quantity `rhs`.
+fn eval_convert(lhs: &ExprSpan, rhs: &ExprSpan, c: &mut Context, span: &Span) -> Result<Value> {
+    let lhs = eval(lhs, c)?.expand();
+    let rhs = match eval(rhs, c)? {
+        Quantity(q) => q,
+        _ => return Err((Error::Type, span.clone())),
+    };
+    match to_quantity(&lhs, span)?.convert(&rhs) {
+        Some(q) => Ok(q.into_value()),
+        None => Err((Error::Dimension, span.clone())),
+    }
+}
+
+/// Get the element of the list `lhs` at the index `rhs`, counting from zero.
+/// Indexing a matrix gives one of its rows as a list.
+fn eval_index(lhs: &ExprSpan, rhs: &ExprSpan, c: &mut Context) -> Result<Value> {
+    let val = eval(lhs, c)?;
+    let len = match &val {
+        List(v) => v.len(),
+        Matrix(m) => m.rows(),
+        _ => return Err((Error::Type, lhs.1.clone())),
+    };
+    let index = match eval(rhs, c)?.expand() {
+        Ratio(r) if r.is_integer() => *r.numer(),
+        _ => return Err((Error::Type, rhs.1.clone())),
+    };
+    let i = match usize::try_from(index) {
+        Ok(i) if i < len => i,
+        _ => return Err((Error::Domain, rhs.1.clone())),
+    };
+    match val {
+        List(mut v) => Ok(v.swap_remove(i)),
+        Matrix(m) => Ok(List(m.row(i).to_vec())),
+        _ => unreachable!(),
+    }
+}
+
+/// Run `f` with the variable `var` available for it to bind in the context,
+/// restoring any value that `var` had afterward.
+fn with_var<T>(c: &mut Context, var: &str, f: impl FnOnce(&mut Context) -> T) -> T {
+    let shadowed = c.vars.remove(var);
+    let result = f(c);
+    c.vars.remove(var);
+    if let Some(val) = shadowed {
+        c.vars.insert(var.to_string(), val);
+    }
+    result
+}
+
+/// Evaluate a sum or product of `body` over the integers `var` from `start`
+/// to `end` inclusive, with `var` bound in the context only while `body` is
+/// evaluated.
+fn eval_series(
+    op: Op,
+    var: &str,
+    [start, end, body]: &[ExprSpan; 3],
+    c: &mut Context,
+    span: &Span,
+) -> Result<Value> {
+    let bound = |expr: &ExprSpan, c: &mut Context| match eval(expr, c)?.expand() {
+        Ratio(r) if r.is_integer() => Ok(*r.numer()),
+        BigRatio(r) if r.is_integer() => Err((Error::Domain, expr.1.clone())),
+        _ => Err((Error::Type, expr.1.clone())),
+    };
+    let (first, last) = (bound(start, c)?, bound(end, c)?);
+    if last.saturating_sub(first) >= MAX_SERIES_TERMS {
+        return Err((Error::Domain, span.clone()));
+    }
+    let result = with_var(c, var, |c| {
+        let mut acc: Option<Value> = None;
+        for k in first..=last {
+            c.vars.insert(var.to_string(), k.into());
+            let x = eval(body, c)?.expand();
+            acc = Some(match acc {
+                None => x,
+                Some(acc) => match (acc.to_big(), x.to_big()) {
+                    // Stay exact, even if the terms do not fit in `Ratio`
+                    (Some(a), Some(b)) => op.big(a, b).unwrap().into(),
+                    _ => binary(op, &acc, &x, &body.1)?,
+                },
+            });
+        }
+        Ok(acc)
+    })?;
+    let empty = if op == Op::Add { 0 } else { 1 };
+    Ok(result.unwrap_or_else(|| empty.into()))
+}
+
+/// Evaluate the integral of `body` over `var` from `start` to `end`, with
+/// `var` bound in the context only while `body` is evaluated.
+fn eval_integral(
+    var: &str,
+    [start, end, body]: &[ExprSpan; 3],
+    c: &mut Context,
+    span: &Span,
+) -> Result<Value> {
+    let a = to_f64(&eval(start, c)?, &start.1)?;
+    let b = to_f64(&eval(end, c)?, &end.1)?;
+    let integral = with_var(c, var, |c| {
+        let f = |x: f64| {
+            c.vars.insert(var.to_string(), x.into());
+            to_f64(&eval(body, c)?, &body.1)
+        };
+        integrate(f, a, b)
+    })?;
+    match integral {
+        Some(integral) => Ok(integral.value.into()),
+        None => Err((Error::Convergence, span.clone())),
+    }
+}
+
+/// Evaluate the solution of `eq` for `var`, starting from the `guesses`,
+/// with `var` bound in the context only while `eq` is evaluated.
+fn eval_solve(
+    var: &str,
+    eq: &ExprSpan,
+    guesses: &[ExprSpan],
+    c: &mut Context,
+    span: &Span,
+) -> Result<Value> {
+    let guesses = guesses
+        .iter()
+        .map(|g| to_f64(&eval(g, c)?, &g.1))
+        .collect::<Result<Vec<_>>>()?;
+    let (lhs, rhs) = match &eq.0 {
+        Expression::Equation(sides) => (&sides[0], &sides[1]),
+        _ => return Err((Error::Syntax, eq.1.clone())),
+    };
+    let root = with_var(c, var, |c| {
+        let f = |x: f64| {
+            c.vars.insert(var.to_string(), x.into());
+            Ok(to_f64(&eval(lhs, c)?, &lhs.1)? - to_f64(&eval(rhs, c)?, &rhs.1)?)
+        };
+        find_root(f, &guesses)
+    })?;
+    match root {
+        Some(root) => Ok(root.into()),
+        // The bracket doesn't contain a sign change
+        None if guesses.len() == 2 => Err((Error::Domain, span.clone())),
+        None => Err((Error::Convergence, span.clone())),
+    }
+}
+
+/// Simplify an expression, giving a function of its free variables, or its
+/// value if it has none. A function is simplified through its body.
+fn eval_simplify(expr: &ExprSpan, c: &mut Context) -> Result<Value> {
+    let func = match &expr.0 {
+        Expression::Lambda(params, body) => Some((params.clone(), body)),
+        Expression::Var(name) => match c.vars.get(name) {
+            Some(Func(params, body)) => Some((params.clone(), body)),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some((params, body)) = func {
+        return Ok(Func(params, Box::new(simplify(body))));
+    }
+    let vars = expr.0.free_vars();
+    let simplified = simplify(expr);
+    if vars.is_empty() {
+        eval(&simplified, c)
+    } else {
+        Ok(Func(vars, Box::new(simplified)))
+    }
+}
+
+/// The largest number of terms in a sum or product.
+const MAX_SERIES_TERMS: i64 = 1_000_000;
+
+/// The largest number of bits in the result of raising a big rational to a
+/// power exactly, beyond which a float is used instead.
+const MAX_BIG_BITS: i64 = 1 << 20;
+
+/// Raise `lhs` to the power of `rhs`, element-wise for lists.
+pub(crate) fn do_exp(lhs: Value, rhs: Value, span: &Span) -> Result<Value> {
+    match (lhs, rhs) {
+        (Matrix(m), Ratio(r)) if r.is_integer() => matrix_pow(&m, *r.numer(), span).map(Matrix),
+        (Matrix(_), _) | (_, Matrix(_)) => Err((Error::Type, span.clone())),
+        (List(left), List(right)) => {
+            if left.len() != right.len() {
+                return Err((Error::Length, span.clone()));
+            }
+            left.into_iter()
+                .zip(right)
+                .map(|(a, b)| do_exp(a.expand(), b.expand(), span))
+                .collect::<Result<_>>()
+                .map(List)
+        }
+        (List(left), rhs) => map_list(left, |a| do_exp(a, rhs.clone(), span)),
+        (lhs, List(right)) => map_list(right, |b| do_exp(lhs.clone(), b, span)),
+        (lhs, rhs) => do_exp_scalar(lhs, rhs, span),
+    }
+}
+
+fn do_exp_scalar(lhs: Value, rhs: Value, span: &Span) -> Result<Value> {
+    if let Quantity(q) = &lhs {
+        return match rhs {
+            Ratio(r) => match q.pow(r) {
+                Some(q) => Ok(q.into_value()),
+                None => Err((Error::Dimension, span.clone())),
+            },
+            _ => Err((Error::Dimension, span.clone())),
+        };
+    }
+    if_chain! {
+        if let BigRatio(base) = &lhs;
+        if let Ratio(exp) = rhs;
+        if exp.is_integer();
+        let bits = base.numer().bits().max(base.denom().bits()) as i64;
+        if bits.saturating_mul(exp.numer().saturating_abs()) <= MAX_BIG_BITS;
+        then {
+            let n = *exp.numer();
+            let base = if n < 0 { base.recip() } else { base.clone() };
+            return Ok(num::pow::pow(base, n.unsigned_abs() as usize).into());
+        }
+    }
+    if_chain! {
+        if let Symbolic(s) = lhs;
+        if let Ratio(r) = rhs;
+        if let Some(x) = s.pow(r);
+        then {
+            return Ok(x.into_value());
+        }
+    }
     if_chain! {
         if let Ratio(lhs) = lhs;
         if let Ratio(rhs) = rhs;
@@ -86,20 +516,107 @@ fn do_exp(lhs: Value, rhs: Value, span: &Span) -> Result<Value> {
     Ok(to_f64(&lhs, span)?.powf(to_f64(&rhs, span)?).into())
 }
 
+/// Test whether an expression assigns to the variable `name`.
+fn assigns(expr: &Expression, name: &str) -> bool {
+    match expr {
+        Expression::Let(var, _) if var == name => true,
+        _ => expr.children().into_iter().any(|(e, _)| assigns(e, name)),
+    }
+}
+
+/// Collect the names of the functions that an expression calls.
+fn calls(expr: &Expression, names: &mut Vec<String>) {
+    if let Expression::Call(name, _) = expr {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    for (e, _) in expr.children() {
+        calls(e, names);
+    }
+}
+
+/// Replace the free variables of a function body that are defined in the
+/// context with their values, and define the functions that it calls at
+/// its start, since the function is called in a new context.
+fn capture(body: &ExprSpan, params: &[String], c: &Context) -> ExprSpan {
+    let mut body = body.clone();
+    let free = |var: &String, body: &ExprSpan| !params.contains(var) && !assigns(&body.0, var);
+    for var in body.0.free_vars() {
+        if !free(&var, &body) {
+            continue;
+        }
+        if let Some(val) = c.vars.get(&var) {
+            let val = (Expression::Val(val.clone()), body.1.clone());
+            body = Expression::substitute(&body, &var, &val);
+        }
+    }
+    let mut names = vec![];
+    calls(&body.0, &mut names);
+    let mut defs: Vec<_> = names
+        .into_iter()
+        .filter(|name| free(name, &body))
+        .filter_map(|name| {
+            let val = (Expression::Val(c.vars.get(&name)?.clone()), body.1.clone());
+            Some((Expression::Let(name, Box::new(val)), body.1.clone()))
+        })
+        .collect();
+    if defs.is_empty() {
+        return body;
+    }
+    let span = body.1.clone();
+    defs.push(body);
+    (Expression::Comma(defs), span)
+}
+
+/// Call a user-defined or built-in function value with arguments paired
+/// with their spans.
+pub(crate) fn call_value(
+    f: &Value,
+    args: Vec<(Value, Span)>,
+    c: &Context,
+    span: &Span,
+) -> Result<Value> {
+    match f {
+        Func(params, body) => {
+            if args.len() != params.len() {
+                return Err((Error::Syntax, span.clone()));
+            }
+            let vars = params
+                .iter()
+                .zip(args)
+                .map(|(name, (val, _))| (name.to_string(), val))
+                .collect();
+            eval(body, &mut c.with_vars(vars))
+        }
+        Builtin(f) => f.apply_args(args, c, span),
+        _ => Err((Error::Type, span.clone())),
+    }
+}
+
 /// Evaluate the expression in the given context.
 pub fn eval((expr, span): &(Expression, Span), c: &mut Context) -> Result<Value> {
     use crate::Expression::*;
     match expr {
         Val(v) => Ok(v.clone()),
         Neg(expr) => neg(expr, c, span),
-        Add(exprs) => apply(|a, b| a + b, |a, b| a.checked_add(&b), exprs, c, span),
-        Sub(args) => apply(|a, b| a - b, |a, b| a.checked_sub(&b), &args[..], c, span),
-        Mul(exprs) => apply(|a, b| a * b, |a, b| a.checked_mul(&b), exprs, c, span),
-        Frac(args) => apply(|a, b| a / b, |a, b| a.checked_div(&b), &args[..], c, span),
+        Add(exprs) => apply(Op::Add, exprs, c, span),
+        Sub(args) => apply(Op::Sub, &args[..], c, span),
+        Mul(exprs) => apply(Op::Mul, exprs, c, span),
+        Frac(args) => apply(Op::Div, &args[..], c, span),
         Exp(args) => eval_exp(&args[0], &args[1], c, span),
         Root(args) => eval_root(&args[0], &args[1], c, span),
         Const(con) => Ok(con.value()),
-        Func(f, expr) => f.apply(eval(expr, c)?, c, span),
+        Func(f, expr) => match expr.as_ref() {
+            (Comma(exprs), _) => {
+                let args = exprs
+                    .iter()
+                    .map(|a| Ok((eval(a, c)?, a.1.clone())))
+                    .collect::<Result<Vec<_>>>()?;
+                f.apply_args(args, c, span)
+            }
+            _ => f.apply(eval(expr, c)?, c, span),
+        },
         Var(var) => {
             if let Some(val) = c.vars.get(var.as_str()) {
                 Ok(val.clone())
@@ -115,21 +632,39 @@ pub fn eval((expr, span): &(Expression, Span), c: &mut Context) -> Result<Value>
         Call(name, args) => {
             let args = args
                 .iter()
-                .map(|a| eval(a, c))
+                .map(|a| Ok((eval(a, c)?, a.1.clone())))
                 .collect::<Result<Vec<_>>>()?;
-            let (params, expr) = match c.vars.get(name) {
-                Some(Value::Func(params, expr)) => (params, expr),
-                Some(_) => return Err((Error::Type, span.clone())),
-                None => return Err((Error::Undefined(name.to_string()), span.clone())),
-            };
-            if args.len() != params.len() {
-                return Err((Error::Syntax, span.clone()));
+            match c.vars.get(name) {
+                Some(f) => call_value(f, args, c, span),
+                None => Err((Error::Undefined(name.to_string()), span.clone())),
             }
-            let mut inner_ctx = Context::default();
-            for (name, val) in params.iter().zip(args.into_iter()) {
-                inner_ctx.vars.insert(name.to_string(), val);
-            }
-            eval(expr, &mut inner_ctx)
+        }
+        Lambda(params, body) => {
+            let body = optimize(&capture(body, params, c));
+            Ok(Value::Func(params.clone(), Box::new(body)))
+        }
+        Convert(args) => eval_convert(&args[0], &args[1], c, span),
+        List(exprs) => exprs
+            .iter()
+            .map(|a| eval(a, c))
+            .collect::<Result<_>>()
+            .map(list_or_matrix),
+        Index(args) => eval_index(&args[0], &args[1], c),
+        Sum(var, args) => eval_series(Op::Add, var, args, c, span),
+        Product(var, args) => eval_series(Op::Mul, var, args, c, span),
+        Integral(var, args) => eval_integral(var, args, c, span),
+        Solve(var, eq, guesses) => eval_solve(var, eq, guesses, c, span),
+        Equation(_) => Err((Error::Syntax, span.clone())),
+        Derivative(var, expr) => {
+            let params = vec![var.clone()];
+            let derivative = simplify(&capture(&differentiate(expr, var, c)?, &params, c));
+            Ok(Value::Func(params, Box::new(derivative)))
+        }
+        Simplify(expr) => eval_simplify(expr, c),
+        Range(args) => {
+            let start = (eval(&args[0], c)?, args[0].1.clone());
+            let end = (eval(&args[1], c)?, args[1].1.clone());
+            Function::Range.apply_args(vec![start, end], c, span)
         }
         Comma(exprs) => {
             debug_assert!(!exprs.is_empty());
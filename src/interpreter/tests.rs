@@ -1,8 +1,7 @@
-use std::f64::consts;
-use std::i64;
+use num::BigInt;
 
 use crate::Expression::*;
-use crate::{eval, Constant, Context, Error, Span, Value};
+use crate::{eval, Constant, Context, Error, Span, Symbolic, Value};
 
 fn sp() -> Span {
     Span {
@@ -127,13 +126,19 @@ fn root() {
 #[test]
 fn const_pi() {
     let x = (Const(Constant::Pi), sp());
-    assert_eq!(eval(&x, &mut ctx()), Ok(consts::PI.into()));
+    assert_eq!(
+        eval(&x, &mut ctx()),
+        Ok(Symbolic::constant(Constant::Pi).into())
+    );
 }
 
 #[test]
 fn const_e() {
     let x = (Const(Constant::E), sp());
-    assert_eq!(eval(&x, &mut ctx()), Ok(consts::E.into()));
+    assert_eq!(
+        eval(&x, &mut ctx()),
+        Ok(Symbolic::constant(Constant::E).into())
+    );
 }
 
 #[test]
@@ -235,3 +240,137 @@ fn call() {
     let x = (Call("increment".to_string(), vec![(4.into(), sp())]), sp());
     assert_eq!(eval(&x, &mut c), Ok(5.into()));
 }
+
+#[test]
+fn lambda() {
+    let body = (
+        Mul(vec![(Var("x".to_string()), sp()), (2.into(), sp())]),
+        sp(),
+    );
+    let x = (
+        Comma(vec![
+            (
+                Let(
+                    "double".to_string(),
+                    Box::new((Lambda(vec!["x".to_string()], Box::new(body.clone())), sp())),
+                ),
+                sp(),
+            ),
+            (Call("double".to_string(), vec![(4.into(), sp())]), sp()),
+        ]),
+        sp(),
+    );
+    let mut c = ctx();
+    assert_eq!(eval(&x, &mut c), Ok(8.into()));
+    assert_eq!(
+        c.vars.get("double"),
+        Some(&Value::Func(vec!["x".to_string()], Box::new(body)))
+    );
+}
+
+#[test]
+fn series() {
+    let var = |s: &str| (Var(s.to_string()), sp());
+    let reciprocal = (Frac(Box::new([(1.into(), sp()), var("k")])), sp());
+    let x = (
+        Sum(
+            "k".to_string(),
+            Box::new([(1.into(), sp()), var("n"), reciprocal]),
+        ),
+        sp(),
+    );
+    let mut c = ctx();
+    c.vars.insert("n".to_string(), 4.into());
+    c.vars.insert("k".to_string(), 10.into());
+    assert_eq!(eval(&x, &mut c), Ok((25, 12).into()));
+    // The index variable is restored afterward
+    assert_eq!(c.vars.get("k"), Some(&10.into()));
+    let x = (
+        Product(
+            "k".to_string(),
+            Box::new([(1.into(), sp()), (25.into(), sp()), var("k")]),
+        ),
+        sp(),
+    );
+    assert_eq!(
+        eval(&x, &mut ctx()),
+        Ok(Value::from(
+            "15511210043330985984000000".parse::<BigInt>().unwrap()
+        ))
+    );
+    let x = (
+        Product(
+            "k".to_string(),
+            Box::new([(1.into(), sp()), (0.into(), sp()), var("j")]),
+        ),
+        sp(),
+    );
+    assert_eq!(eval(&x, &mut ctx()), Ok(1.into()));
+    let x = (
+        Sum(
+            "k".to_string(),
+            Box::new([(1.into(), sp()), ((1, 2).into(), sp()), var("k")]),
+        ),
+        sp(),
+    );
+    assert_eq!(eval(&x, &mut ctx()), Err((Error::Type, sp())));
+    let mut c = ctx();
+    let x = (
+        Sum(
+            "k".to_string(),
+            Box::new([(1.into(), sp()), (2.into(), sp()), var("j")]),
+        ),
+        sp(),
+    );
+    assert_eq!(
+        eval(&x, &mut c),
+        Err((Error::Undefined("j".to_string()), sp()))
+    );
+    assert_eq!(c.vars.get("k"), None);
+}
+
+#[test]
+fn list_broadcast() {
+    let list = |v: Vec<i64>| {
+        (
+            List(v.into_iter().map(|x| (x.into(), sp())).collect()),
+            sp(),
+        )
+    };
+    let values = |v: Vec<i64>| Value::List(v.into_iter().map(Value::from).collect());
+    let x = (Mul(vec![list(vec![1, 2]), (3.into(), sp())]), sp());
+    assert_eq!(eval(&x, &mut ctx()), Ok(values(vec![3, 6])));
+    let x = (Sub(Box::new([(10.into(), sp()), list(vec![1, 2])])), sp());
+    assert_eq!(eval(&x, &mut ctx()), Ok(values(vec![9, 8])));
+    let x = (Add(vec![list(vec![1, 2]), list(vec![3, 4])]), sp());
+    assert_eq!(eval(&x, &mut ctx()), Ok(values(vec![4, 6])));
+    let x = (Exp(Box::new([list(vec![2, 3]), list(vec![3, 2])])), sp());
+    assert_eq!(eval(&x, &mut ctx()), Ok(values(vec![8, 9])));
+    let x = (Neg(Box::new(list(vec![1, -2]))), sp());
+    assert_eq!(eval(&x, &mut ctx()), Ok(values(vec![-1, 2])));
+    let span = Span { start: 7, ..sp() };
+    let x = (Add(vec![list(vec![1, 2]), list(vec![3])]), span.clone());
+    assert_eq!(eval(&x, &mut ctx()), Err((Error::Length, span)));
+}
+
+#[test]
+fn index() {
+    let list = (List(vec![(5.into(), sp()), (6.into(), sp())]), sp());
+    let at = |i: Value| {
+        let span = Span { start: 3, ..sp() };
+        (Index(Box::new([list.clone(), (i.into(), span)])), sp())
+    };
+    assert_eq!(eval(&at(1.into()), &mut ctx()), Ok(6.into()));
+    let span = Span { start: 3, ..sp() };
+    assert_eq!(
+        eval(&at(2.into()), &mut ctx()),
+        Err((Error::Domain, span.clone()))
+    );
+    assert_eq!(
+        eval(&at((-1).into()), &mut ctx()),
+        Err((Error::Domain, span.clone()))
+    );
+    assert_eq!(eval(&at(0.5.into()), &mut ctx()), Err((Error::Type, span)));
+    let x = (Index(Box::new([(1.into(), sp()), (0.into(), sp())])), sp());
+    assert_eq!(eval(&x, &mut ctx()), Err((Error::Type, sp())));
+}
@@ -0,0 +1,513 @@
+//! Render expressions and values as LaTeX.
+
+use num::rational::Ratio;
+use num::traits::{One, Signed, Zero};
+use num::BigRational;
+
+use crate::format::{quantity_options, FloatFormat};
+use crate::parser::unit_name;
+use crate::printer::{precedence, COMMA, CONVERT, INDEX, LAMBDA, LET, POWER, PREFIX, PRODUCT, SUM};
+use crate::{Constant, Context, Expression, Function, Span, Symbolic, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// Rendering as LaTeX math, such as `\frac{1}{2}` for `1 / 2`.
+pub trait ToLatex {
+    /// Render as LaTeX, writing floats with the context's display options.
+    fn to_latex(&self, ctx: &Context) -> String;
+}
+
+impl ToLatex for Expression {
+    fn to_latex(&self, ctx: &Context) -> String {
+        expr_latex(self, ctx)
+    }
+}
+
+impl ToLatex for Value {
+    fn to_latex(&self, ctx: &Context) -> String {
+        value_latex(self, ctx)
+    }
+}
+
+/// Greek letters that a variable may be named after, such as `alpha`.
+const GREEK: [(&str, char); 32] = [
+    ("alpha", '\u{3b1}'),
+    ("beta", '\u{3b2}'),
+    ("gamma", '\u{3b3}'),
+    ("delta", '\u{3b4}'),
+    ("epsilon", '\u{3b5}'),
+    ("zeta", '\u{3b6}'),
+    ("eta", '\u{3b7}'),
+    ("theta", '\u{3b8}'),
+    ("iota", '\u{3b9}'),
+    ("kappa", '\u{3ba}'),
+    ("lambda", '\u{3bb}'),
+    ("mu", '\u{3bc}'),
+    ("nu", '\u{3bd}'),
+    ("xi", '\u{3be}'),
+    ("rho", '\u{3c1}'),
+    ("sigma", '\u{3c3}'),
+    ("upsilon", '\u{3c5}'),
+    ("chi", '\u{3c7}'),
+    ("psi", '\u{3c8}'),
+    ("omega", '\u{3c9}'),
+    ("Gamma", '\u{393}'),
+    ("Delta", '\u{394}'),
+    ("Theta", '\u{398}'),
+    ("Lambda", '\u{39b}'),
+    ("Xi", '\u{39e}'),
+    ("Pi", '\u{3a0}'),
+    ("Sigma", '\u{3a3}'),
+    ("Upsilon", '\u{3a5}'),
+    ("Phi", '\u{3a6}'),
+    ("Psi", '\u{3a8}'),
+    ("Omega", '\u{3a9}'),
+    ("varphi", '\u{3c6}'),
+];
+
+/// Get the Greek letter that a variable is named after, if it is.
+pub(crate) fn greek_letter(name: &str) -> Option<char> {
+    GREEK.iter().find(|(s, _)| *s == name).map(|&(_, ch)| ch)
+}
+
+/// Get the level of the grammar that an expression is rendered at, which
+/// differs from the source where LaTeX groups with braces or delimiters
+/// instead of parentheses.
+pub(crate) fn level(expr: &Expression) -> u8 {
+    use Expression::*;
+    match expr {
+        Val(Value::Ratio(r)) if !r.is_integer() => PREFIX,
+        Val(Value::BigRatio(r)) if !r.is_integer() => PREFIX,
+        Val(Value::Symbolic(_)) | Frac(_) | Root(_) | Integral(..) => PREFIX,
+        Func(Function::Abs | Function::Floor | Function::Ceil | Function::Norm, _) => INDEX,
+        Func(Function::Choose, arg) if matches!(&arg.0, Comma(v) if v.len() == 2) => INDEX,
+        Func(Function::Fact, _) => POWER,
+        Sum(..) | Product(..) => SUM,
+        _ => precedence(expr),
+    }
+}
+
+/// Render an expression, in parentheses if it is below the `level` of the
+/// grammar.
+fn group(expr: &Expression, ctx: &Context, min: u8) -> String {
+    if level(expr) < min {
+        format!("\\left({}\\right)", expr_latex(expr, ctx))
+    } else {
+        expr_latex(expr, ctx)
+    }
+}
+
+/// Render expressions separated by commas.
+fn items(v: &[(Expression, Span)], ctx: &Context) -> String {
+    let v: Vec<_> = v.iter().map(|(e, _)| group(e, ctx, LET)).collect();
+    v.join(", ")
+}
+
+/// Render the name of a built-in function, such as `\sin` or
+/// `\operatorname{gamma}`.
+fn func_name(func: Function) -> String {
+    use Function::*;
+    match func {
+        Sin | Cos | Tan | Sinh | Cosh | Tanh | Ln | Gcd | Det | Min | Max => format!("\\{}", func),
+        Asin | Acos | Atan => format!("\\arc{}", &func.to_string()[1..]),
+        _ => format!("\\operatorname{{{}}}", func),
+    }
+}
+
+/// Render a variable, with a Greek letter for its name or a subscript after
+/// an underscore, as in `x_{1}`.
+fn var_latex(name: &str) -> String {
+    match name.split_once('_') {
+        Some((base, sub)) if !base.is_empty() && !sub.is_empty() => {
+            format!("{}_{{{}}}", var_latex(base), var_latex(sub))
+        }
+        _ if greek_letter(name).is_some() => format!("\\{}", name),
+        _ if name.chars().count() == 1 => name.to_string(),
+        _ => format!("\\mathrm{{{}}}", name),
+    }
+}
+
+fn const_latex(con: Constant) -> &'static str {
+    use Constant::*;
+    match con {
+        Pi => "\\pi",
+        E | ElementaryCharge => "e",
+        Tau => "\\tau",
+        Phi => "\\varphi",
+        EulerGamma => "\\gamma",
+        Sqrt2 => "\\sqrt{2}",
+        Inf => "\\infty",
+        Nan => "\\mathrm{NaN}",
+        SpeedOfLight => "c",
+        Planck => "h",
+        ReducedPlanck => "\\hbar",
+        Boltzmann => "k_\\mathrm{B}",
+        Avogadro => "N_\\mathrm{A}",
+        Gravitation => "G",
+        ElectronMass => "m_\\mathrm{e}",
+        ProtonMass => "m_\\mathrm{p}",
+        VacuumPermittivity => "\\varepsilon_0",
+        VacuumPermeability => "\\mu_0",
+        GasConstant => "R",
+        StandardGravity => "g_0",
+    }
+}
+
+/// Render a function of the named parameters, such as `x \mapsto x^{2}`.
+fn lambda_latex(params: &[String], body: &Expression, ctx: &Context) -> String {
+    let params: Vec<_> = params.iter().map(|p| var_latex(p)).collect();
+    let params = if params.len() == 1 {
+        params[0].clone()
+    } else {
+        format!("\\left({}\\right)", params.join(", "))
+    };
+    format!("{} \\mapsto {}", params, group(body, ctx, LAMBDA))
+}
+
+/// Render a call to a built-in function.
+fn func_latex(func: Function, arg: &Expression, ctx: &Context) -> String {
+    let inner = || expr_latex(arg, ctx);
+    match func {
+        Function::Abs => format!("\\left|{}\\right|", inner()),
+        Function::Floor => format!("\\left\\lfloor {}\\right\\rfloor", inner()),
+        Function::Ceil => format!("\\left\\lceil {}\\right\\rceil", inner()),
+        Function::Norm => format!("\\left\\|{}\\right\\|", inner()),
+        Function::Fact => format!("{}!", group(arg, ctx, INDEX)),
+        Function::Choose => match arg {
+            Expression::Comma(v) if v.len() == 2 => format!(
+                "\\binom{{{}}}{{{}}}",
+                expr_latex(&v[0].0, ctx),
+                expr_latex(&v[1].0, ctx)
+            ),
+            _ => format!("{}\\left({}\\right)", func_name(func), inner()),
+        },
+        _ => format!("{}\\left({}\\right)", func_name(func), inner()),
+    }
+}
+
+/// Render the base of a power, grouping anything longer than a symbol.
+fn base_latex(base: &Expression, ctx: &Context) -> String {
+    use Expression::*;
+    match base {
+        _ if level(base) < INDEX => format!("\\left({}\\right)", expr_latex(base, ctx)),
+        Val(_) | Index(_) | Call(..) | List(_) => {
+            let s = expr_latex(base, ctx);
+            if s.chars().count() > 1 {
+                format!("{{{}}}", s)
+            } else {
+                s
+            }
+        }
+        _ => expr_latex(base, ctx),
+    }
+}
+
+fn expr_latex(expr: &Expression, ctx: &Context) -> String {
+    use Expression::*;
+    match expr {
+        Val(val) => value_latex(val, ctx),
+        // A unit, such as the `km/h` in `x to km/h`
+        Exp(_) | Mul(_) | Frac(_) if unit_name(expr).is_some() => {
+            unit_latex(&unit_name(expr).unwrap())
+        }
+        Neg(e) => format!("-{}", group(&e.0, ctx, PREFIX)),
+        Add(v) => {
+            let mut s = group(&v[0].0, ctx, SUM);
+            for (term, _) in &v[1..] {
+                // Write `a + -b` as `a - b`
+                match term {
+                    Neg(e) => s += &format!(" - {}", group(&e.0, ctx, PRODUCT)),
+                    _ => s += &format!(" + {}", group(term, ctx, PRODUCT)),
+                }
+            }
+            s
+        }
+        Sub(args) => format!(
+            "{} - {}",
+            group(&args[0].0, ctx, SUM),
+            group(&args[1].0, ctx, PRODUCT)
+        ),
+        Mul(v) => {
+            let mut s = group(&v[0].0, ctx, PRODUCT);
+            for (factor, _) in &v[1..] {
+                let factor = match unit_name(factor) {
+                    Some(unit) => unit_latex(&unit),
+                    None => group(factor, ctx, PREFIX),
+                };
+                // Juxtapose factors unless they would run together as a
+                // number or a mixed fraction
+                let dot = factor.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.')
+                    || factor.starts_with("\\frac");
+                s += if dot { " \\cdot " } else { " " };
+                s += &factor;
+            }
+            s
+        }
+        Frac(args) => format!(
+            "\\frac{{{}}}{{{}}}",
+            expr_latex(&args[0].0, ctx),
+            expr_latex(&args[1].0, ctx)
+        ),
+        Exp(args) => format!(
+            "{}^{{{}}}",
+            base_latex(&args[0].0, ctx),
+            expr_latex(&args[1].0, ctx)
+        ),
+        Root(args) => match &args[1].0 {
+            Val(Value::Ratio(n)) if *n == Ratio::from_integer(2) => {
+                format!("\\sqrt{{{}}}", expr_latex(&args[0].0, ctx))
+            }
+            n => format!(
+                "\\sqrt[{}]{{{}}}",
+                expr_latex(n, ctx),
+                expr_latex(&args[0].0, ctx)
+            ),
+        },
+        Const(con) => const_latex(*con).to_string(),
+        Func(func, e) => func_latex(*func, &e.0, ctx),
+        Var(var) => var_latex(var),
+        Let(var, e) => format!("{} = {}", var_latex(var), group(&e.0, ctx, LAMBDA)),
+        Call(name, args) => format!("{}\\left({}\\right)", var_latex(name), items(args, ctx)),
+        Lambda(params, body) => lambda_latex(params, &body.0, ctx),
+        Convert(args) => {
+            let target = match unit_name(&args[1].0) {
+                Some(unit) => unit_latex(&unit),
+                None => group(&args[1].0, ctx, SUM),
+            };
+            format!("{} \\to {}", group(&args[0].0, ctx, CONVERT), target)
+        }
+        List(v) => format!("\\left[{}\\right]", items(v, ctx)),
+        Index(args) => format!(
+            "{}\\left[{}\\right]",
+            group(&args[0].0, ctx, INDEX),
+            expr_latex(&args[1].0, ctx)
+        ),
+        Range(args) => format!(
+            "{} \\ldots {}",
+            group(&args[0].0, ctx, CONVERT),
+            group(&args[1].0, ctx, CONVERT)
+        ),
+        Sum(var, args) | Product(var, args) => {
+            let [start, end, body] = args.as_ref();
+            format!(
+                "\\{}_{{{} = {}}}^{{{}}} {}",
+                if let Sum(..) = expr { "sum" } else { "prod" },
+                var_latex(var),
+                expr_latex(&start.0, ctx),
+                expr_latex(&end.0, ctx),
+                group(&body.0, ctx, PRODUCT)
+            )
+        }
+        Integral(var, args) => {
+            let [lower, upper, body] = args.as_ref();
+            format!(
+                "\\int_{{{}}}^{{{}}} {} \\,\\mathrm{{d}}{}",
+                expr_latex(&lower.0, ctx),
+                expr_latex(&upper.0, ctx),
+                group(&body.0, ctx, SUM),
+                var_latex(var)
+            )
+        }
+        Equation(args) => format!(
+            "{} = {}",
+            group(&args[0].0, ctx, LAMBDA),
+            group(&args[1].0, ctx, LAMBDA)
+        ),
+        Solve(var, eq, guesses) => {
+            let mut s = format!(
+                "{}\\left({}, {}",
+                func_name(Function::Solve),
+                group(&eq.0, ctx, LET),
+                var_latex(var)
+            );
+            for (guess, _) in guesses {
+                s += &format!(", {}", group(guess, ctx, LET));
+            }
+            s + "\\right)"
+        }
+        Derivative(var, e) => format!(
+            "\\frac{{\\mathrm{{d}}}}{{\\mathrm{{d}}{}}} {}",
+            var_latex(var),
+            group(&e.0, ctx, PREFIX)
+        ),
+        Simplify(e) => format!(
+            "{}\\left({}\\right)",
+            func_name(Function::Simplify),
+            group(&e.0, ctx, COMMA)
+        ),
+        Comma(v) => items(v, ctx),
+    }
+}
+
+/// Escape the digit group separators of a formatted number.
+fn escape_number(s: &str) -> String {
+    let mut out = String::new();
+    for ch in s.chars() {
+        match ch {
+            ',' => out += "{,}",
+            '_' => out += "\\_",
+            '\'' => out += "\\text{'}",
+            ' ' => out += "\\ ",
+            '\u{2009}' => out += "\\,",
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Render a unit, such as `\mathrm{m/s^{2}}`.
+fn unit_latex(unit: &str) -> String {
+    let mut out = String::new();
+    let mut chars = unit.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => out += " \\cdot ",
+            '^' => {
+                let mut exp = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '-' && exp.is_empty() {
+                        exp.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                out += &format!("^{{{}}}", exp);
+            }
+            '\u{b5}' | '\u{3bc}' => out += "\\mu ",
+            '\u{3a9}' => out += "\\Omega ",
+            '\u{b0}' => out += "^{\\circ}",
+            _ => out.push(ch),
+        }
+    }
+    format!("\\mathrm{{{}}}", out)
+}
+
+/// Render an integer with the context's digit grouping.
+fn int_latex(ctx: &Context, val: Value) -> String {
+    escape_number(&ctx.display(&val).to_string())
+}
+
+/// Render a fraction of two formatted integers, with the sign in front.
+fn fraction(numer: String, denom: String) -> String {
+    match numer.strip_prefix('-') {
+        Some(numer) => format!("-\\frac{{{}}}{{{}}}", numer, denom),
+        None => format!("\\frac{{{}}}{{{}}}", numer, denom),
+    }
+}
+
+/// Render a float, with scientific notation as a power of ten.
+fn float_latex(ctx: &Context, num: f64) -> String {
+    if num.is_nan() {
+        return "\\mathrm{NaN}".to_string();
+    } else if num.is_infinite() {
+        return if num < 0.0 { "-\\infty" } else { "\\infty" }.to_string();
+    }
+    let s = FloatFormat { ctx, num }.to_string();
+    if let Some((mantissa, exp)) = s.split_once('e') {
+        let exp = exp.trim_start_matches('+');
+        format!("{} \\times 10^{{{}}}", escape_number(mantissa), exp)
+    } else {
+        match s.rsplit_once(' ') {
+            // An SI prefix, such as `4.7 k`
+            Some((mantissa, prefix)) if !prefix.contains(|c: char| c.is_ascii_digit()) => {
+                format!("{}\\,{}", escape_number(mantissa), unit_latex(prefix))
+            }
+            _ => escape_number(&s),
+        }
+    }
+}
+
+/// Render an exact symbolic value, such as `\frac{2 \pi}{3}`.
+fn symbolic_latex(ctx: &Context, sym: Symbolic) -> String {
+    let power = sym.power.abs();
+    let mut term = const_latex(sym.base).to_string();
+    if !power.is_one() {
+        let power = value_latex(&Value::Ratio(power), ctx);
+        term = format!("{}^{{{}}}", term, power);
+    }
+    let numer = sym.coef.numer().abs();
+    let denom = *sym.coef.denom();
+    let sign = if sym.coef.is_negative() { "-" } else { "" };
+    let int = |n: i64| int_latex(ctx, Value::Ratio(Ratio::from_integer(n)));
+    let (top, bottom) = if sym.power > Ratio::zero() {
+        let top = if numer == 1 {
+            term
+        } else {
+            format!("{} {}", int(numer), term)
+        };
+        (top, (denom != 1).then(|| int(denom)))
+    } else if denom == 1 {
+        (int(numer), Some(term))
+    } else {
+        (int(numer), Some(format!("{} {}", int(denom), term)))
+    };
+    match bottom {
+        Some(bottom) => format!("{}\\frac{{{}}}{{{}}}", sign, top, bottom),
+        None => format!("{}{}", sign, top),
+    }
+}
+
+fn value_latex(val: &Value, ctx: &Context) -> String {
+    match val {
+        Value::Ratio(r) => {
+            let numer = int_latex(ctx, Value::Ratio(Ratio::from_integer(*r.numer())));
+            if r.is_integer() {
+                numer
+            } else {
+                let denom = int_latex(ctx, Value::Ratio(Ratio::from_integer(*r.denom())));
+                fraction(numer, denom)
+            }
+        }
+        Value::BigRatio(r) => {
+            let int = |n| int_latex(ctx, Value::BigRatio(BigRational::from_integer(n)));
+            let numer = int(r.numer().clone());
+            if r.is_integer() {
+                numer
+            } else {
+                fraction(numer, int(r.denom().clone()))
+            }
+        }
+        Value::Float(num) => float_latex(ctx, *num),
+        Value::Symbolic(sym) => symbolic_latex(ctx, *sym),
+        Value::Quantity(q) => {
+            let (num, unit) = q.display_parts();
+            let ctx = &quantity_options(ctx);
+            format!("{}\\,{}", float_latex(ctx, num), unit_latex(&unit))
+        }
+        Value::Factors(factors) if factors.is_empty() => "1".to_string(),
+        Value::Factors(factors) => {
+            let factors: Vec<_> = factors
+                .iter()
+                .map(|&(p, e)| {
+                    let p = int_latex(ctx, Value::Ratio(Ratio::from_integer(p)));
+                    if e > 1 {
+                        format!("{}^{{{}}}", p, e)
+                    } else {
+                        p
+                    }
+                })
+                .collect();
+            factors.join(" \\cdot ")
+        }
+        Value::List(items) => {
+            let items: Vec<_> = items.iter().map(|v| value_latex(v, ctx)).collect();
+            format!("\\left[{}\\right]", items.join(", "))
+        }
+        Value::Matrix(m) => {
+            let rows: Vec<_> = (0..m.rows())
+                .map(|i| {
+                    let row: Vec<_> = m.row(i).iter().map(|v| value_latex(v, ctx)).collect();
+                    row.join(" & ")
+                })
+                .collect();
+            format!(
+                "\\begin{{bmatrix}} {} \\end{{bmatrix}}",
+                rows.join(" \\\\ ")
+            )
+        }
+        Value::Func(params, body) => lambda_latex(params, &body.0, ctx),
+        Value::Builtin(func) => func_name(*func),
+    }
+}
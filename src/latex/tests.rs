@@ -0,0 +1,141 @@
+use crate::{eval, lex, parse, Context, Precision, ToLatex, Value};
+
+fn latex(s: &str) -> String {
+    parse(lex(s, None)).unwrap().0.to_latex(&Context::default())
+}
+
+fn result(s: &str) -> String {
+    let mut ctx = Context::default();
+    let val = eval(&parse(lex(s, None)).unwrap(), &mut ctx).unwrap();
+    val.to_latex(&ctx)
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(latex("1 + 2 * 3"), "1 + 2 \\cdot 3");
+    assert_eq!(latex("(a + b) * c"), "\\left(a + b\\right) c");
+    assert_eq!(latex("2 * x * y"), "2 x y");
+    assert_eq!(latex("a - (b - c)"), "a - \\left(b - c\\right)");
+    assert_eq!(latex("a + -b"), "a - b");
+    assert_eq!(latex("-(x + 1)"), "-\\left(x + 1\\right)");
+    assert_eq!(latex("x * -2"), "x \\cdot -2");
+    assert_eq!(latex("2 * (1 / 3)"), "2 \\cdot \\frac{1}{3}");
+}
+
+#[test]
+fn fractions() {
+    assert_eq!(latex("1 / 2"), "\\frac{1}{2}");
+    assert_eq!(latex("(x + 1) / (x - 1)"), "\\frac{x + 1}{x - 1}");
+    assert_eq!(latex("-x / 2"), "\\frac{-x}{2}");
+    assert_eq!(latex("a / b / c"), "\\frac{\\frac{a}{b}}{c}");
+    assert_eq!(latex("(1 / 2)^2"), "\\left(\\frac{1}{2}\\right)^{2}");
+}
+
+#[test]
+fn powers() {
+    assert_eq!(latex("x^2"), "x^{2}");
+    assert_eq!(latex("x^(n + 1)"), "x^{n + 1}");
+    assert_eq!(latex("2^3^4"), "2^{3^{4}}");
+    assert_eq!(latex("10^6"), "{10}^{6}");
+    assert_eq!(latex("(x^2)^3"), "\\left(x^{2}\\right)^{3}");
+    assert_eq!(latex("(-x)^2"), "\\left(-x\\right)^{2}");
+    assert_eq!(latex("-x^2"), "-x^{2}");
+    assert_eq!(
+        latex("(sin(x))^2"),
+        "\\left(\\sin\\left(x\\right)\\right)^{2}"
+    );
+    assert_eq!(latex("e^(2 * x)"), "e^{2 x}");
+    assert_eq!(latex("v[0]^2"), "{v\\left[0\\right]}^{2}");
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        latex("sin(x) * cos(x)"),
+        "\\sin\\left(x\\right) \\cos\\left(x\\right)"
+    );
+    assert_eq!(latex("atan(1)"), "\\arctan\\left(1\\right)");
+    assert_eq!(latex("gamma(x)"), "\\operatorname{gamma}\\left(x\\right)");
+    assert_eq!(latex("abs(x - 1)"), "\\left|x - 1\\right|");
+    assert_eq!(
+        latex("floor(x / 2)"),
+        "\\left\\lfloor \\frac{x}{2}\\right\\rfloor"
+    );
+    assert_eq!(latex("fact(n + 1)"), "\\left(n + 1\\right)!");
+    assert_eq!(latex("choose(n, 2)"), "\\binom{n}{2}");
+    assert_eq!(latex("max(1, 2)"), "\\max\\left(1, 2\\right)");
+    assert_eq!(latex("f(x, y)"), "f\\left(x, y\\right)");
+    assert_eq!(
+        latex("map(sin, v)"),
+        "\\operatorname{map}\\left(\\sin, v\\right)"
+    );
+}
+
+#[test]
+fn names() {
+    assert_eq!(latex("2 * pi * r"), "2 \\pi r");
+    assert_eq!(latex("alpha + x_1"), "\\alpha + x_{1}");
+    assert_eq!(latex("speed * phys.c"), "\\mathrm{speed} c");
+    assert_eq!(latex("inf"), "\\infty");
+}
+
+#[test]
+fn forms() {
+    assert_eq!(latex("f = x -> x^2"), "f = x \\mapsto x^{2}");
+    assert_eq!(latex("(x, y) -> x * y"), "\\left(x, y\\right) \\mapsto x y");
+    assert_eq!(latex("sum(k, 1, n, k^2)"), "\\sum_{k = 1}^{n} k^{2}");
+    assert_eq!(
+        latex("prod(k, 1, n, k + 1)"),
+        "\\prod_{k = 1}^{n} \\left(k + 1\\right)"
+    );
+    assert_eq!(
+        latex("integrate(x, 0, 1, x^2 + 1)"),
+        "\\int_{0}^{1} x^{2} + 1 \\,\\mathrm{d}x"
+    );
+    assert_eq!(
+        latex("diff(x^2, x)"),
+        "\\frac{\\mathrm{d}}{\\mathrm{d}x} x^{2}"
+    );
+    assert_eq!(
+        latex("diff(2 * x, x)"),
+        "\\frac{\\mathrm{d}}{\\mathrm{d}x} \\left(2 x\\right)"
+    );
+    assert_eq!(
+        latex("solve(x^2 = 2, x, 1)"),
+        "\\operatorname{solve}\\left(x^{2} = 2, x, 1\\right)"
+    );
+    assert_eq!(latex("3 km to m"), "3 \\mathrm{km} \\to \\mathrm{m}");
+    assert_eq!(latex("v to km/h"), "v \\to \\mathrm{km/h}");
+    assert_eq!(latex("a to m/s^2"), "a \\to \\mathrm{m/s^{2}}");
+    assert_eq!(latex("x to y"), "x \\to y");
+    assert_eq!(latex("[1, 2][0]"), "\\left[1, 2\\right]\\left[0\\right]");
+}
+
+#[test]
+fn values() {
+    assert_eq!(result("1 / 2 + 1 / 3"), "\\frac{5}{6}");
+    assert_eq!(result("-3 / 4"), "-\\frac{3}{4}");
+    assert_eq!(result("fact(25)"), "15511210043330985984000000");
+    assert_eq!(result("1.5e-9"), "1.5 \\times 10^{-9}");
+    assert_eq!(result("2 * pi / 3"), "\\frac{2 \\pi}{3}");
+    assert_eq!(result("-1 / pi"), "-\\frac{1}{\\pi}");
+    assert_eq!(result("e^2"), "e^{2}");
+    assert_eq!(result("[1, 1 / 2]"), "\\left[1, \\frac{1}{2}\\right]");
+    assert_eq!(
+        result("[[1, 2], [3, 4]]"),
+        "\\begin{bmatrix} 1 & 2 \\\\ 3 & 4 \\end{bmatrix}"
+    );
+    assert_eq!(result("factor(360)"), "2^{3} \\cdot 3^{2} \\cdot 5");
+    assert_eq!(result("9.8 m/s^2"), "9.8\\,\\mathrm{m/s^{2}}");
+    assert_eq!(result("x -> x^2"), "x \\mapsto x^{2}");
+    assert_eq!(
+        Value::from(f64::INFINITY).to_latex(&Context::default()),
+        "\\infty"
+    );
+    let ctx = Context {
+        precision: Precision::Significant(3),
+        grouping: true,
+        ..Context::default()
+    };
+    assert_eq!(Value::from(1234567.0).to_latex(&ctx), "1{,}230{,}000");
+}
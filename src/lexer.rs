@@ -3,18 +3,29 @@ mod tests;
 
 use std::rc::Rc;
 
-use crate::{Error, Result, Span, Token, TokenStream};
+use crate::{parse_unit, si_prefix_exponent, Error, Result, Span, Token, TokenStream};
 
 /// Lex the `input` into a stream of tokens.
 ///
 /// The `file` parameter specifies the filename from which the input
 /// originated. This is to produce better diagnostic messages.
-pub fn lex(input: &str, file: Option<String>) -> impl TokenStream {
+pub fn lex(input: &str, file: Option<String>) -> impl TokenStream<'_> {
+    lex_decimal(input, file, '.')
+}
+
+/// Lex the `input` into a stream of tokens, using `decimal` as the decimal
+/// point in numeric literals.
+///
+/// If the decimal point is a comma, then semicolons take the place of commas
+/// as the `Token::Comma` separator, so that `f(1,5; 2)` is equivalent to
+/// `f(1.5, 2)` with a period.
+pub fn lex_decimal(input: &str, file: Option<String>, decimal: char) -> impl TokenStream<'_> {
     Lex {
         input,
         file: file.map(Rc::new),
         line: 1,
         col: 1,
+        decimal,
         peeked: None,
     }
 }
@@ -24,9 +35,23 @@ struct Lex<'a> {
     file: Option<Rc<String>>,
     line: u32,
     col: u32,
+    decimal: char,
     peeked: Option<(Token<'a>, Span)>,
 }
 
+/// Parse the float literal `s` multiplied by `10^scale`.
+///
+/// Returns `None` if the exponent is out of range.
+fn scale_float(s: &str, scale: i32) -> Option<f64> {
+    let (mantissa, exp) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], s[(i + 1)..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    format!("{}e{}", mantissa, exp.checked_add(scale)?)
+        .parse()
+        .ok()
+}
+
 /// Return whether this character is acceptable in an identifier.
 fn is_ident_char(ch: char) -> bool {
     ch.is_alphanumeric() || ch == '_'
@@ -79,37 +104,95 @@ impl<'a> Lex<'a> {
     }
 
     /// Read a numeric literal.
-    fn read_number(&mut self) -> (Token<'a>, Span) {
+    ///
+    /// The literal may be followed by an SI prefix, such as `4.7k` or `10u`,
+    /// which scales it by the corresponding power of ten.
+    fn read_number(&mut self) -> Result<(Token<'a>, Span)> {
         let mut end = 0;
         let mut is_float = false;
-        end = self.read_while(end, |c| c.is_digit(10));
-        if self.input[end..].starts_with('.') {
-            end += 1;
-            end = self.read_while(end, |c| c.is_digit(10));
+        end = self.read_while(end, |c| c.is_ascii_digit());
+        // A period followed by another is a range operator, as in `1..10`
+        let rest = &self.input[end..];
+        if rest.starts_with(self.decimal) && !rest.starts_with("..") {
+            end += self.decimal.len_utf8();
+            end = self.read_while(end, |c| c.is_ascii_digit());
             is_float = true;
         }
         if let Some('e') | Some('E') = self.input[end..].chars().next() {
-            end += 1;
+            let mut exp = end + 1;
             // Consume a + or -
-            if let Some('+') | Some('-') = self.input[end..].chars().next() {
-                end += 1;
+            if let Some('+') | Some('-') = self.input[exp..].chars().next() {
+                exp += 1;
+            }
+            // Consume exponent, if there is one, as `2E` is an SI prefix
+            let digits = self.read_while(exp, |c| c.is_ascii_digit());
+            if digits > exp {
+                end = digits;
+                is_float = true;
             }
-            // Consume exponent
-            end = self.read_while(end, |c| c.is_digit(10));
-            is_float = true;
         }
-        let s = &self.input[..end];
-        let tok = if is_float {
-            Token::Float(s.parse().expect("invalid float literal"))
+        let s = &self.input[..end].replace(self.decimal, ".");
+        let (scale, suffix_len) = self.read_si_prefix(end);
+        let span = self.advance_span(end + suffix_len);
+        let tok = if scale == 0 {
+            if is_float {
+                s.parse().ok().map(Token::Float)
+            } else {
+                s.parse().ok().map(Token::Integer)
+            }
         } else {
-            Token::Integer(s.parse().expect("invalid integer literal"))
+            let int = if is_float || scale < 0 {
+                None
+            } else {
+                s.parse::<u64>()
+                    .ok()
+                    .zip(10_u64.checked_pow(scale as u32))
+                    .and_then(|(n, p)| n.checked_mul(p))
+            };
+            match int {
+                Some(n) => Some(Token::Integer(n)),
+                None => scale_float(s, scale).map(Token::Float),
+            }
         };
-        (tok, self.advance_span(end))
+        match tok {
+            Some(tok) => Ok((tok, span)),
+            None => Err((Error::Syntax, span)),
+        }
+    }
+
+    /// Read an SI prefix symbol at `start` that is not part of a longer
+    /// identifier, returning its power of ten and length.
+    ///
+    /// A symbol that is also a unit, such as `m`, is read as the unit
+    /// instead, so `1m` is one metre. Returns `(0, 0)` if there is no such
+    /// prefix.
+    fn read_si_prefix(&self, start: usize) -> (i32, usize) {
+        if let Some(ch) = self.input[start..].chars().next() {
+            let end = start + ch.len_utf8();
+            let longer = self.input[end..].chars().next().is_some_and(is_ident_char);
+            let symbol = &self.input[start..end];
+            if !longer && parse_unit(symbol).is_none() {
+                if let Some(exp) = si_prefix_exponent(symbol) {
+                    return (exp, ch.len_utf8());
+                }
+            }
+        }
+        (0, 0)
     }
 
     /// Read an identifier.
+    ///
+    /// Identifiers may contain periods followed by a letter, as in `phys.c`.
     fn read_ident(&mut self) -> (Token<'a>, Span) {
-        let end = self.read_while(0, is_ident_char);
+        let mut end = self.read_while(0, is_ident_char);
+        while self.input[end..].starts_with('.')
+            && self.input[(end + 1)..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphabetic)
+        {
+            end = self.read_while(end + 1, is_ident_char);
+        }
         let s = &self.input[..end];
         (Token::Ident(s), self.advance_span(end))
     }
@@ -117,6 +200,11 @@ impl<'a> Lex<'a> {
     /// Read an operator.
     fn read_operator(&mut self, ch: char) -> Option<(Token<'a>, Span)> {
         use Token::*;
+        for (s, op) in [("..", DotDot), ("->", Arrow)] {
+            if self.input.starts_with(s) {
+                return Some((op, self.advance_span(2)));
+            }
+        }
         let op = match ch {
             '+' => Plus,
             '-' => Minus,
@@ -125,8 +213,11 @@ impl<'a> Lex<'a> {
             '^' => Exponent,
             '(' => LeftParen,
             ')' => RightParen,
+            '[' => LeftBracket,
+            ']' => RightBracket,
             '=' => Equals,
-            ',' => Comma,
+            ',' if self.decimal != ',' => Comma,
+            ';' if self.decimal == ',' => Comma,
             _ => return None,
         };
         Some((op, self.advance_span(1)))
@@ -155,8 +246,12 @@ impl<'a> Lex<'a> {
             return Ok(tok);
         }
         // Is this a numeric literal?
-        if ch.is_digit(10) || ch == '.' {
-            return Ok(self.read_number());
+        let digit_follows = self.input[ch.len_utf8()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit());
+        if ch.is_ascii_digit() || ch == self.decimal && digit_follows {
+            return self.read_number();
         }
         // Is this an identifier?
         if is_ident_char(ch) {
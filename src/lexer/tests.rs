@@ -1,8 +1,8 @@
 use crate::Token::*;
-use crate::{lex, Span, Token, TokenStream};
+use crate::{lex, lex_decimal, Error, Span, Token, TokenStream};
 
 /// Collect the tokens and spans from the input string.
-fn collect(s: &str) -> Vec<(Token, Span)> {
+fn collect(s: &str) -> Vec<(Token<'_>, Span)> {
     let mut v = vec![];
     let mut l = lex(s, None);
     loop {
@@ -22,7 +22,7 @@ fn collect(s: &str) -> Vec<(Token, Span)> {
 }
 
 /// Collect the tokens from the input string.
-fn v(s: &str) -> Vec<Token> {
+fn v(s: &str) -> Vec<Token<'_>> {
     collect(s).into_iter().map(|(t, _)| t).collect()
 }
 
@@ -127,3 +127,113 @@ fn peek() {
     assert_eq!(l.peek(), Ok(&(Eof, sp(10, 10))));
     assert_eq!(l.next(), Ok((Eof, sp(10, 10))));
 }
+
+#[test]
+fn number_with_si_prefix() {
+    assert_eq!(
+        v("4.7k 10u 3M 2.2µ 1e3n"),
+        vec![
+            4700.0.into(),
+            1e-5.into(),
+            3000000.into(),
+            2.2e-6.into(),
+            1e-6.into(),
+            Eof
+        ],
+    );
+    assert_eq!(v("4k"), vec![4000.into(), Eof]);
+    assert_eq!(v("5ms"), vec![5.into(), Ident("ms"), Eof]);
+    // A unit takes precedence over the prefix with the same symbol
+    assert_eq!(v("1m"), vec![1.into(), Ident("m"), Eof]);
+    assert_eq!(
+        v("2E 2E3"),
+        vec![2000000000000000000.into(), 2000.0.into(), Eof]
+    );
+    assert_eq!(v("2e"), vec![2.into(), Ident("e"), Eof]);
+    assert_eq!(s("4.7k+10µ"), vec![sp(1, 4), sp(5, 5), sp(6, 8), sp(9, 9)]);
+}
+
+#[test]
+fn number_out_of_range() {
+    for s in ["1e99999999999k", "1e2147483647k", "99999999999999999999"] {
+        assert_eq!(lex(s, None).next().map_err(|e| e.0), Err(Error::Syntax));
+    }
+}
+
+#[test]
+fn decimal_comma() {
+    let v = |s| {
+        let mut l = lex_decimal(s, None, ',');
+        let mut v = vec![];
+        loop {
+            match l.next().unwrap() {
+                (Eof, _) => break v,
+                (tok, _) => v.push(tok),
+            }
+        }
+    };
+    assert_eq!(v("3,5"), vec![3.5.into()]);
+    assert_eq!(
+        v("f(1,5; ,25)"),
+        vec![
+            Ident("f"),
+            LeftParen,
+            1.5.into(),
+            Comma,
+            0.25.into(),
+            RightParen
+        ]
+    );
+    assert_eq!(v("2,5k"), vec![2500.0.into()]);
+}
+
+#[test]
+fn lone_decimal_point() {
+    let mut l = lex("1 . 2", None);
+    assert_eq!(l.next(), Ok((1.into(), sp(1, 1))));
+    assert_eq!(l.next(), Err((Error::Syntax, sp(3, 3))));
+    let mut l = lex_decimal("1,", None, ',');
+    assert_eq!(l.next(), Ok((1.0.into(), sp(1, 2))));
+    let mut l = lex_decimal("x, y", None, ',');
+    assert_eq!(l.next(), Ok((Ident("x"), sp(1, 1))));
+    assert_eq!(l.next(), Err((Error::Syntax, sp(2, 2))));
+}
+
+#[test]
+fn dotted_idents() {
+    assert_eq!(
+        v("phys.c a.b.c"),
+        vec![Ident("phys.c"), Ident("a.b.c"), Eof]
+    );
+    assert_eq!(v("x.5"), vec![Ident("x"), 0.5.into(), Eof]);
+}
+
+#[test]
+fn brackets() {
+    assert_eq!(
+        v("[1, x][0]"),
+        vec![
+            LeftBracket,
+            1.into(),
+            Comma,
+            Ident("x"),
+            RightBracket,
+            LeftBracket,
+            0.into(),
+            RightBracket,
+            Eof
+        ]
+    );
+    assert_eq!(s("[]"), vec![sp(1, 1), sp(2, 2), sp(3, 3)]);
+}
+
+#[test]
+fn range_and_arrow() {
+    assert_eq!(v("1..10"), vec![1.into(), DotDot, 10.into(), Eof]);
+    assert_eq!(v("1.5..2"), vec![1.5.into(), DotDot, 2.into(), Eof]);
+    assert_eq!(s("a..b"), vec![sp(1, 1), sp(2, 3), sp(4, 4), sp(5, 5)]);
+    assert_eq!(
+        v("x->x - 1"),
+        vec![Ident("x"), Arrow, Ident("x"), Minus, 1.into(), Eof]
+    );
+}
@@ -1,23 +1,60 @@
+pub use crate::approx::*;
 pub use crate::builtin::*;
+pub use crate::combinatorics::*;
 pub use crate::context::*;
+pub use crate::derivative::*;
 pub use crate::error::*;
 pub use crate::expression::*;
+pub use crate::format::*;
 pub use crate::interpreter::*;
+pub use crate::latex::*;
 pub use crate::lexer::*;
+pub use crate::mathml::*;
+pub use crate::matrix::*;
+pub use crate::number::*;
+pub use crate::optimize::*;
 pub use crate::parser::*;
+pub use crate::prefix::*;
+pub use crate::quadrature::*;
+pub use crate::roots::*;
+pub use crate::simplify::*;
 pub use crate::span::*;
+pub use crate::special::*;
+pub use crate::stats::*;
+pub use crate::symbolic::*;
 pub use crate::token::*;
+pub use crate::unicode::*;
+pub use crate::unit::*;
 pub use crate::value::*;
 
+mod approx;
 mod builtin;
+mod combinatorics;
 mod context;
+mod derivative;
 mod error;
 mod expression;
+mod format;
 mod interpreter;
+mod latex;
 mod lexer;
+mod mathml;
+mod matrix;
+mod number;
+mod optimize;
 mod parser;
+mod prefix;
+mod printer;
+mod quadrature;
+mod roots;
+mod simplify;
 mod span;
+mod special;
+mod stats;
+mod symbolic;
 #[cfg(test)]
 mod tests;
 mod token;
+mod unicode;
+mod unit;
 mod value;
@@ -4,7 +4,10 @@ use std::io;
 use std::io::{BufRead, BufReader};
 use std::process::exit;
 
-use pcalc::{eval, lex, parse, Context, Error, Result, Span, Value};
+use pcalc::{
+    eval, lex_decimal, parse, Constant, Context, DisplayMode, Error, Expression, Result, Span,
+    ToLatex, Value,
+};
 
 fn show_err(err: Error, span: Span, arrow: bool) {
     if arrow {
@@ -19,12 +22,73 @@ fn show_err(err: Error, span: Span, arrow: bool) {
     eprintln!("{}: {}", span, err);
 }
 
+fn parse_expr(expr: &str, ctx: &Context, filename: Option<String>) -> Result<(Expression, Span)> {
+    let decimal = if ctx.locale_input {
+        ctx.separators.0
+    } else {
+        '.'
+    };
+    parse(lex_decimal(expr, filename, decimal))
+}
+
 fn run_expr(expr: &str, ctx: &mut Context, filename: Option<String>) -> Result<Value> {
-    let tokens = lex(&expr, filename);
-    let expr = parse(tokens)?;
+    let expr = parse_expr(expr, ctx, filename)?;
     eval(&expr, ctx)
 }
 
+/// Print an expression and its result as LaTeX, or only the expression if it
+/// is an assignment or has undefined variables.
+fn show_latex(input: &str, ctx: &mut Context) -> Result<()> {
+    let expr = parse_expr(input, ctx, None)?;
+    match (&expr.0, eval(&expr, ctx)) {
+        (Expression::Let(..), Ok(_)) => println!("{}", expr.0.to_latex(ctx)),
+        (_, Ok(val)) => println!("{} = {}", expr.0.to_latex(ctx), val.to_latex(ctx)),
+        (_, Err((Error::Undefined(_), _))) => println!("{}", expr.0.to_latex(ctx)),
+        (_, Err(err)) => return Err(err),
+    }
+    Ok(())
+}
+
+/// Run a REPL command, i.e., a line starting with a colon.
+fn run_command(cmd: &str, ctx: &mut Context) {
+    let words: Vec<&str> = cmd.split_whitespace().collect();
+    match words.as_slice() {
+        ["constants"] => {
+            let rows: Vec<_> = Constant::ALL
+                .iter()
+                .map(|con| {
+                    let val = ctx.display(&con.value()).to_string();
+                    (con.to_string(), val, con.description())
+                })
+                .collect();
+            let name_width = rows.iter().map(|r| r.0.chars().count()).max().unwrap_or(0);
+            let val_width = rows.iter().map(|r| r.1.chars().count()).max().unwrap_or(0);
+            for (name, val, description) in rows {
+                println!(
+                    "  {:<name_width$} = {:<val_width$} {}",
+                    name,
+                    val,
+                    description,
+                    name_width = name_width,
+                    val_width = val_width
+                );
+            }
+        }
+        ["latex", ..] => {
+            let input = cmd.trim_start().trim_start_matches("latex");
+            if let Err((e, s)) = show_latex(input, ctx) {
+                show_err(e, s, false);
+            }
+        }
+        ["set", name, value] => {
+            if !ctx.set_option(name, value) {
+                eprintln!("pcalc: invalid option {} = {}", name, value);
+            }
+        }
+        _ => eprintln!("pcalc: unknown command :{}", cmd),
+    }
+}
+
 fn main() {
     let mut ctx = Context::default();
 
@@ -50,10 +114,32 @@ fn main() {
     let mut line_num = 1_u32;
     for line in BufReader::new(stdin).lines() {
         let line = line.unwrap();
-        match run_expr(&line, &mut ctx, None) {
+        if let Some(cmd) = line.trim().strip_prefix(':') {
+            run_command(cmd, &mut ctx);
+            continue;
+        }
+        let input = match parse_expr(&line, &ctx, None) {
+            Ok(input) => input,
+            Err((e, s)) => {
+                show_err(e, s, true);
+                continue;
+            }
+        };
+        match eval(&input, &mut ctx) {
             Ok(v) => {
                 let ans_name = format!("ans{}", line_num);
-                println!("  ans{:<4} = {}", line_num, ctx.display(&v));
+                match ctx.mode {
+                    DisplayMode::Text => {
+                        // Indent further lines, such as the rows of a matrix
+                        let shown = ctx.display(&v).to_string().replace('\n', "\n            ");
+                        println!("  ans{:<4} = {}", line_num, shown);
+                    }
+                    DisplayMode::Unicode => {
+                        let shown = ctx.display_result(&input, &v).replace('\n', "\n  ");
+                        println!("  {}", shown);
+                    }
+                    _ => println!("{}", ctx.display_result(&input, &v)),
+                }
                 ctx.vars.insert(ans_name, v.clone());
                 if let Some(ans) = ctx.vars.get_mut("ans") {
                     *ans = v;
@@ -0,0 +1,532 @@
+//! Render expressions and values as presentation MathML.
+
+use num::rational::Ratio;
+use num::traits::{One, Signed, Zero};
+use num::BigRational;
+
+use crate::format::{quantity_options, FloatFormat};
+use crate::latex::{greek_letter, level};
+use crate::parser::unit_name;
+use crate::printer::{COMMA, CONVERT, INDEX, LAMBDA, LET, PREFIX, PRODUCT, SUM};
+use crate::{Constant, Context, Expression, Function, Span, Symbolic, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// Rendering as a MathML `<math>` element.
+pub trait ToMathMl {
+    /// Render as MathML, writing floats with the context's display options.
+    fn to_mathml(&self, ctx: &Context) -> String;
+}
+
+impl ToMathMl for Expression {
+    fn to_mathml(&self, ctx: &Context) -> String {
+        math(expr_mathml(self, ctx))
+    }
+}
+
+impl ToMathMl for Value {
+    fn to_mathml(&self, ctx: &Context) -> String {
+        math(value_mathml(self, ctx))
+    }
+}
+
+const MINUS: &str = "\u{2212}";
+const DOT: &str = "\u{22c5}";
+const INVISIBLE_TIMES: &str = "\u{2062}";
+const APPLY_FUNCTION: &str = "\u{2061}";
+
+fn math(content: String) -> String {
+    format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+        content
+    )
+}
+
+fn mi(s: &str) -> String {
+    format!("<mi>{}</mi>", s)
+}
+
+/// An identifier that is upright even if it is a single letter, such as the
+/// `d` in `dx`.
+fn mi_normal(s: &str) -> String {
+    format!("<mi mathvariant=\"normal\">{}</mi>", s)
+}
+
+fn mn(s: &str) -> String {
+    format!("<mn>{}</mn>", s)
+}
+
+fn mo(s: &str) -> String {
+    format!("<mo>{}</mo>", s)
+}
+
+fn mrow(s: String) -> String {
+    format!("<mrow>{}</mrow>", s)
+}
+
+fn fenced(inner: String, open: &str, close: &str) -> String {
+    mrow(format!("{}{}{}", mo(open), inner, mo(close)))
+}
+
+/// Render an expression, in parentheses if it is below the `level` of the
+/// grammar.
+fn group(expr: &Expression, ctx: &Context, min: u8) -> String {
+    if level(expr) < min {
+        fenced(expr_mathml(expr, ctx), "(", ")")
+    } else {
+        expr_mathml(expr, ctx)
+    }
+}
+
+/// Render expressions separated by commas, as a single element.
+fn items<'a>(v: impl IntoIterator<Item = &'a Expression>, ctx: &Context) -> String {
+    let v: Vec<_> = v.into_iter().map(|e| group(e, ctx, LET)).collect();
+    mrow(v.join(&mo(",")))
+}
+
+/// Render a name with any subscript after an underscore, as in `x_1`.
+fn name_mathml(name: &str) -> String {
+    match name.split_once('_') {
+        Some((base, sub)) if !base.is_empty() && !sub.is_empty() => {
+            format!("<msub>{}{}</msub>", name_mathml(base), name_mathml(sub))
+        }
+        _ if name.chars().all(|c| c.is_ascii_digit()) => mn(name),
+        _ => match greek_letter(name) {
+            Some(ch) => mi(&ch.to_string()),
+            None => mi(name),
+        },
+    }
+}
+
+fn const_mathml(con: Constant) -> String {
+    match con {
+        Constant::Sqrt2 => format!("<msqrt>{}</msqrt>", mn("2")),
+        _ => name_mathml(con.symbol()),
+    }
+}
+
+/// Test whether an expression is rendered starting with a number, so that
+/// it needs a visible multiplication sign after another factor.
+fn starts_with_number(expr: &Expression) -> bool {
+    use Expression::*;
+    match expr {
+        Val(Value::Func(..) | Value::Builtin(_) | Value::List(_) | Value::Matrix(_)) => false,
+        Val(_) | Neg(_) | Frac(_) => true,
+        Mul(v) => starts_with_number(&v[0].0),
+        Exp(args) | Index(args) => level(&args[0].0) >= INDEX && starts_with_number(&args[0].0),
+        _ => false,
+    }
+}
+
+/// Render a function of the named parameters, such as `x ↦ x^2`.
+fn lambda_mathml(params: &[String], body: &Expression, ctx: &Context) -> String {
+    let params: Vec<_> = params.iter().map(|p| name_mathml(p)).collect();
+    let params = if params.len() == 1 {
+        params[0].clone()
+    } else {
+        fenced(params.join(&mo(",")), "(", ")")
+    };
+    mrow(format!(
+        "{}{}{}",
+        params,
+        mo("\u{21a6}"),
+        group(body, ctx, LAMBDA)
+    ))
+}
+
+/// Render a call to a function given its rendered name and arguments.
+fn call_mathml(name: String, args: String) -> String {
+    mrow(format!(
+        "{}{}{}",
+        name,
+        mo(APPLY_FUNCTION),
+        fenced(args, "(", ")")
+    ))
+}
+
+/// Render a call to a built-in function.
+fn func_mathml(func: Function, arg: &Expression, ctx: &Context) -> String {
+    let inner = || expr_mathml(arg, ctx);
+    match func {
+        Function::Abs => fenced(inner(), "|", "|"),
+        Function::Floor => fenced(inner(), "\u{230a}", "\u{230b}"),
+        Function::Ceil => fenced(inner(), "\u{2308}", "\u{2309}"),
+        Function::Norm => fenced(inner(), "\u{2016}", "\u{2016}"),
+        Function::Fact => mrow(format!("{}{}", group(arg, ctx, INDEX), mo("!"))),
+        Function::Choose => match arg {
+            Expression::Comma(v) if v.len() == 2 => fenced(
+                format!(
+                    "<mfrac linethickness=\"0\">{}{}</mfrac>",
+                    expr_mathml(&v[0].0, ctx),
+                    expr_mathml(&v[1].0, ctx)
+                ),
+                "(",
+                ")",
+            ),
+            _ => call_mathml(mi(&func.to_string()), inner()),
+        },
+        _ => call_mathml(mi(&func.to_string()), inner()),
+    }
+}
+
+/// Render a sum or product over an index variable.
+fn series_mathml(op: &str, var: &str, args: &[(Expression, Span); 3], ctx: &Context) -> String {
+    let [start, end, body] = args;
+    let under = mrow(format!(
+        "{}{}{}",
+        name_mathml(var),
+        mo("="),
+        expr_mathml(&start.0, ctx)
+    ));
+    mrow(format!(
+        "<munderover>{}{}{}</munderover>{}",
+        mo(op),
+        under,
+        expr_mathml(&end.0, ctx),
+        group(&body.0, ctx, PRODUCT)
+    ))
+}
+
+fn expr_mathml(expr: &Expression, ctx: &Context) -> String {
+    use Expression::*;
+    match expr {
+        Val(val) => value_mathml(val, ctx),
+        // A unit, such as the `km/h` in `x to km/h`
+        Exp(_) | Mul(_) | Frac(_) if unit_name(expr).is_some() => {
+            unit_mathml(&unit_name(expr).unwrap())
+        }
+        Neg(e) => mrow(format!("{}{}", mo(MINUS), group(&e.0, ctx, PREFIX))),
+        Add(v) => {
+            let mut s = group(&v[0].0, ctx, SUM);
+            for (term, _) in &v[1..] {
+                // Write `a + -b` as `a - b`
+                match term {
+                    Neg(e) => s += &format!("{}{}", mo(MINUS), group(&e.0, ctx, PRODUCT)),
+                    _ => s += &format!("{}{}", mo("+"), group(term, ctx, PRODUCT)),
+                }
+            }
+            mrow(s)
+        }
+        Sub(args) => mrow(format!(
+            "{}{}{}",
+            group(&args[0].0, ctx, SUM),
+            mo(MINUS),
+            group(&args[1].0, ctx, PRODUCT)
+        )),
+        Mul(v) => {
+            let mut s = group(&v[0].0, ctx, PRODUCT);
+            for (factor, _) in &v[1..] {
+                // Juxtapose factors unless they would run together as a
+                // number or a mixed fraction
+                if level(factor) >= PREFIX && starts_with_number(factor) {
+                    s += &mo(DOT);
+                } else {
+                    s += &mo(INVISIBLE_TIMES);
+                }
+                s += &match unit_name(factor) {
+                    Some(unit) => unit_mathml(&unit),
+                    None => group(factor, ctx, PREFIX),
+                };
+            }
+            mrow(s)
+        }
+        Frac(args) => format!(
+            "<mfrac>{}{}</mfrac>",
+            expr_mathml(&args[0].0, ctx),
+            expr_mathml(&args[1].0, ctx)
+        ),
+        Exp(args) => format!(
+            "<msup>{}{}</msup>",
+            group(&args[0].0, ctx, INDEX),
+            expr_mathml(&args[1].0, ctx)
+        ),
+        Root(args) => match &args[1].0 {
+            Val(Value::Ratio(n)) if *n == Ratio::from_integer(2) => {
+                format!("<msqrt>{}</msqrt>", expr_mathml(&args[0].0, ctx))
+            }
+            n => format!(
+                "<mroot>{}{}</mroot>",
+                expr_mathml(&args[0].0, ctx),
+                expr_mathml(n, ctx)
+            ),
+        },
+        Const(con) => const_mathml(*con),
+        Func(func, e) => func_mathml(*func, &e.0, ctx),
+        Var(var) => name_mathml(var),
+        Let(var, e) => mrow(format!(
+            "{}{}{}",
+            name_mathml(var),
+            mo("="),
+            group(&e.0, ctx, LAMBDA)
+        )),
+        Call(name, args) => call_mathml(name_mathml(name), items(args.iter().map(|a| &a.0), ctx)),
+        Lambda(params, body) => lambda_mathml(params, &body.0, ctx),
+        Convert(args) => mrow(format!(
+            "{}{}{}",
+            group(&args[0].0, ctx, CONVERT),
+            mo("\u{2192}"),
+            match unit_name(&args[1].0) {
+                Some(unit) => unit_mathml(&unit),
+                None => group(&args[1].0, ctx, SUM),
+            }
+        )),
+        List(v) => fenced(items(v.iter().map(|a| &a.0), ctx), "[", "]"),
+        Index(args) => mrow(format!(
+            "{}{}",
+            group(&args[0].0, ctx, INDEX),
+            fenced(expr_mathml(&args[1].0, ctx), "[", "]")
+        )),
+        Range(args) => mrow(format!(
+            "{}{}{}",
+            group(&args[0].0, ctx, CONVERT),
+            mo("\u{2026}"),
+            group(&args[1].0, ctx, CONVERT)
+        )),
+        Sum(var, args) => series_mathml("\u{2211}", var, args, ctx),
+        Product(var, args) => series_mathml("\u{220f}", var, args, ctx),
+        Integral(var, args) => {
+            let [lower, upper, body] = args.as_ref();
+            mrow(format!(
+                "<msubsup>{}{}{}</msubsup>{}{}{}{}",
+                mo("\u{222b}"),
+                expr_mathml(&lower.0, ctx),
+                expr_mathml(&upper.0, ctx),
+                group(&body.0, ctx, SUM),
+                mo(INVISIBLE_TIMES),
+                mi_normal("d"),
+                name_mathml(var)
+            ))
+        }
+        Equation(args) => mrow(format!(
+            "{}{}{}",
+            group(&args[0].0, ctx, LAMBDA),
+            mo("="),
+            group(&args[1].0, ctx, LAMBDA)
+        )),
+        Solve(var, eq, guesses) => {
+            let var = Var(var.clone());
+            let args = std::iter::once(&eq.0).chain(std::iter::once(&var));
+            let args = items(args.chain(guesses.iter().map(|g| &g.0)), ctx);
+            call_mathml(mi(&Function::Solve.to_string()), args)
+        }
+        Derivative(var, e) => mrow(format!(
+            "<mfrac>{}{}</mfrac>{}",
+            mi_normal("d"),
+            mrow(format!("{}{}", mi_normal("d"), name_mathml(var))),
+            group(&e.0, ctx, PREFIX)
+        )),
+        Simplify(e) => call_mathml(mi(&Function::Simplify.to_string()), group(&e.0, ctx, COMMA)),
+        Comma(v) => items(v.iter().map(|a| &a.0), ctx),
+    }
+}
+
+/// Render a formatted number, with a minus sign for a leading hyphen.
+fn number(s: &str) -> String {
+    match s.strip_prefix('-') {
+        Some(s) => mrow(format!("{}{}", mo(MINUS), mn(s))),
+        None => mn(s),
+    }
+}
+
+/// Render an integer with the context's digit grouping.
+fn int_mathml(ctx: &Context, val: Value) -> String {
+    number(&ctx.display(&val).to_string())
+}
+
+/// Render a fraction of two formatted integers, with the sign in front.
+fn fraction(numer: String, denom: String) -> String {
+    match numer.strip_prefix('-') {
+        Some(numer) => mrow(format!(
+            "{}<mfrac>{}{}</mfrac>",
+            mo(MINUS),
+            mn(numer),
+            mn(&denom)
+        )),
+        None => format!("<mfrac>{}{}</mfrac>", mn(&numer), mn(&denom)),
+    }
+}
+
+/// Render a float, with scientific notation as a power of ten.
+fn float_mathml(ctx: &Context, num: f64) -> String {
+    if num.is_nan() {
+        return mi("NaN");
+    } else if num.is_infinite() {
+        let inf = mi("\u{221e}");
+        return if num < 0.0 {
+            mrow(format!("{}{}", mo(MINUS), inf))
+        } else {
+            inf
+        };
+    }
+    let s = FloatFormat { ctx, num }.to_string();
+    if let Some((mantissa, exp)) = s.split_once('e') {
+        let exp = exp.trim_start_matches('+');
+        mrow(format!(
+            "{}{}<msup>{}{}</msup>",
+            number(mantissa),
+            mo("\u{d7}"),
+            mn("10"),
+            number(exp)
+        ))
+    } else {
+        match s.rsplit_once(' ') {
+            // An SI prefix, such as `4.7 k`
+            Some((mantissa, prefix)) if !prefix.contains(|c: char| c.is_ascii_digit()) => {
+                mrow(format!("{}{}", number(mantissa), mi_normal(prefix)))
+            }
+            _ => number(&s),
+        }
+    }
+}
+
+/// Render a unit, such as `m/s^2`.
+fn unit_mathml(unit: &str) -> String {
+    let mut parts: Vec<String> = vec![];
+    let mut chars = unit.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => parts.push(mo(DOT)),
+            '/' => parts.push(mo("/")),
+            '^' => {
+                let mut exp = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '-' && exp.is_empty() {
+                        exp.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let base = parts.pop().unwrap_or_default();
+                parts.push(format!("<msup>{}{}</msup>", base, number(&exp)));
+            }
+            _ => {
+                let mut name = ch.to_string();
+                while let Some(&c) = chars.peek() {
+                    if matches!(c, '*' | '/' | '^') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                parts.push(mi_normal(&name));
+            }
+        }
+    }
+    mrow(parts.concat())
+}
+
+/// Render an exact symbolic value, such as `2π/3`.
+fn symbolic_mathml(ctx: &Context, sym: Symbolic) -> String {
+    let power = sym.power.abs();
+    let mut term = const_mathml(sym.base);
+    if !power.is_one() {
+        term = format!(
+            "<msup>{}{}</msup>",
+            term,
+            value_mathml(&Value::Ratio(power), ctx)
+        );
+    }
+    let numer = sym.coef.numer().abs();
+    let denom = *sym.coef.denom();
+    let int = |n: i64| int_mathml(ctx, Value::Ratio(Ratio::from_integer(n)));
+    let (top, bottom) = if sym.power > Ratio::zero() {
+        let top = if numer == 1 {
+            term
+        } else {
+            mrow(format!("{}{}{}", int(numer), mo(INVISIBLE_TIMES), term))
+        };
+        (top, (denom != 1).then(|| int(denom)))
+    } else if denom == 1 {
+        (int(numer), Some(term))
+    } else {
+        let bottom = mrow(format!("{}{}{}", int(denom), mo(INVISIBLE_TIMES), term));
+        (int(numer), Some(bottom))
+    };
+    let s = match bottom {
+        Some(bottom) => format!("<mfrac>{}{}</mfrac>", top, bottom),
+        None => top,
+    };
+    if sym.coef.is_negative() {
+        mrow(format!("{}{}", mo(MINUS), s))
+    } else {
+        s
+    }
+}
+
+fn value_mathml(val: &Value, ctx: &Context) -> String {
+    match val {
+        Value::Ratio(r) => {
+            let int = |n| {
+                let val = Value::Ratio(Ratio::from_integer(n));
+                let s = ctx.display(&val).to_string();
+                s
+            };
+            let numer = int(*r.numer());
+            if r.is_integer() {
+                number(&numer)
+            } else {
+                fraction(numer, int(*r.denom()))
+            }
+        }
+        Value::BigRatio(r) => {
+            let int = |n| {
+                let val = Value::BigRatio(BigRational::from_integer(n));
+                let s = ctx.display(&val).to_string();
+                s
+            };
+            let numer = int(r.numer().clone());
+            if r.is_integer() {
+                number(&numer)
+            } else {
+                fraction(numer, int(r.denom().clone()))
+            }
+        }
+        Value::Float(num) => float_mathml(ctx, *num),
+        Value::Symbolic(sym) => symbolic_mathml(ctx, *sym),
+        Value::Quantity(q) => {
+            let (num, unit) = q.display_parts();
+            mrow(format!(
+                "{}{}{}",
+                float_mathml(&quantity_options(ctx), num),
+                mo(INVISIBLE_TIMES),
+                unit_mathml(&unit)
+            ))
+        }
+        Value::Factors(factors) if factors.is_empty() => mn("1"),
+        Value::Factors(factors) => {
+            let factors: Vec<_> = factors
+                .iter()
+                .map(|&(p, e)| {
+                    let p = int_mathml(ctx, Value::Ratio(Ratio::from_integer(p)));
+                    if e > 1 {
+                        format!("<msup>{}{}</msup>", p, mn(&e.to_string()))
+                    } else {
+                        p
+                    }
+                })
+                .collect();
+            mrow(factors.join(&mo(DOT)))
+        }
+        Value::List(items) => {
+            let items: Vec<_> = items.iter().map(|v| value_mathml(v, ctx)).collect();
+            fenced(mrow(items.join(&mo(","))), "[", "]")
+        }
+        Value::Matrix(m) => {
+            let rows: String = (0..m.rows())
+                .map(|i| {
+                    let row: String = m
+                        .row(i)
+                        .iter()
+                        .map(|v| format!("<mtd>{}</mtd>", value_mathml(v, ctx)))
+                        .collect();
+                    format!("<mtr>{}</mtr>", row)
+                })
+                .collect();
+            fenced(format!("<mtable>{}</mtable>", rows), "[", "]")
+        }
+        Value::Func(params, body) => lambda_mathml(params, &body.0, ctx),
+        Value::Builtin(func) => mi(&func.to_string()),
+    }
+}
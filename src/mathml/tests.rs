@@ -0,0 +1,82 @@
+use crate::{eval, lex, parse, Context, ToMathMl};
+
+/// Strip the `<math>` element around the rendering.
+fn inner(s: String) -> String {
+    let prefix = "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">";
+    assert!(s.starts_with(prefix) && s.ends_with("</math>"), "{}", s);
+    s[prefix.len()..s.len() - "</math>".len()].to_string()
+}
+
+fn mathml(s: &str) -> String {
+    inner(
+        parse(lex(s, None))
+            .unwrap()
+            .0
+            .to_mathml(&Context::default()),
+    )
+}
+
+fn result(s: &str) -> String {
+    let mut ctx = Context::default();
+    let val = eval(&parse(lex(s, None)).unwrap(), &mut ctx).unwrap();
+    inner(val.to_mathml(&ctx))
+}
+
+#[test]
+fn expressions() {
+    assert_eq!(mathml("1 / 2"), "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+    assert_eq!(mathml("x^2"), "<msup><mi>x</mi><mn>2</mn></msup>");
+    assert_eq!(
+        mathml("a - b"),
+        "<mrow><mi>a</mi><mo>−</mo><mi>b</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("(a + b) * c"),
+        "<mrow><mrow><mo>(</mo><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mo>)</mo></mrow>\
+         <mo>\u{2062}</mo><mi>c</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("sin(x)"),
+        "<mrow><mi>sin</mi><mo>\u{2061}</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>"
+    );
+    assert_eq!(
+        mathml("alpha + x_1"),
+        "<mrow><mi>α</mi><mo>+</mo><msub><mi>x</mi><mn>1</mn></msub></mrow>"
+    );
+    assert_eq!(
+        mathml("abs(x)"),
+        "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>"
+    );
+    assert_eq!(
+        mathml("sum(k, 1, n, k^2)"),
+        "<mrow><munderover><mo>∑</mo><mrow><mi>k</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi>\
+         </munderover><msup><mi>k</mi><mn>2</mn></msup></mrow>"
+    );
+}
+
+#[test]
+fn values() {
+    assert_eq!(
+        result("-3 / 4"),
+        "<mrow><mo>−</mo><mfrac><mn>3</mn><mn>4</mn></mfrac></mrow>"
+    );
+    assert_eq!(
+        result("1.5e-9"),
+        "<mrow><mn>1.5</mn><mo>×</mo><msup><mn>10</mn><mrow><mo>−</mo><mn>9</mn></mrow>\
+         </msup></mrow>"
+    );
+    assert_eq!(
+        result("2 * pi / 3"),
+        "<mfrac><mrow><mn>2</mn><mo>\u{2062}</mo><mi>π</mi></mrow><mn>3</mn></mfrac>"
+    );
+    assert_eq!(
+        result("[[1, 2], [3, 4]]"),
+        "<mrow><mo>[</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>\
+         <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr></mtable><mo>]</mo></mrow>"
+    );
+    assert_eq!(
+        result("9.8 m/s^2"),
+        "<mrow><mn>9.8</mn><mo>\u{2062}</mo><mrow><mi mathvariant=\"normal\">m</mi><mo>/</mo>\
+         <msup><mi mathvariant=\"normal\">s</mi><mn>2</mn></msup></mrow></mrow>"
+    );
+}
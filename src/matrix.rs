@@ -0,0 +1,313 @@
+//! Matrices and linear algebra.
+
+use num::rational::BigRational;
+use num::traits::{Num, Signed, Zero};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Value;
+
+#[cfg(test)]
+mod tests;
+
+/// A matrix of values with at least one row and one column.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    /// The entries in row-major order.
+    entries: Vec<Value>,
+}
+
+impl Matrix {
+    /// Create a matrix from its rows.
+    ///
+    /// Returns `None` if there are no rows or columns, or if the rows have
+    /// different lengths.
+    pub fn from_rows(rows: Vec<Vec<Value>>) -> Option<Matrix> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        Some(Matrix {
+            rows: rows.len(),
+            cols,
+            entries: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Create a matrix with one column from a list of values.
+    ///
+    /// Returns `None` if the list is empty.
+    pub fn column(items: Vec<Value>) -> Option<Matrix> {
+        Matrix::from_rows(items.into_iter().map(|x| vec![x]).collect())
+    }
+
+    /// Create the `n` by `n` identity matrix.
+    pub fn identity(n: usize) -> Matrix {
+        let entries = (0..n * n)
+            .map(|i| if i % (n + 1) == 0 { 1 } else { 0 }.into())
+            .collect();
+        Matrix {
+            rows: n,
+            cols: n,
+            entries,
+        }
+    }
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Test whether the matrix has as many rows as columns.
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Get the entry at row `i` and column `j`, counting from zero.
+    pub fn get(&self, i: usize, j: usize) -> &Value {
+        &self.entries[i * self.cols + j]
+    }
+
+    /// Get a row of the matrix, counting from zero.
+    pub fn row(&self, i: usize) -> &[Value] {
+        &self.entries[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Convert the matrix into its entries in row-major order.
+    pub fn into_entries(self) -> Vec<Value> {
+        self.entries
+    }
+
+    /// Get the transpose of the matrix.
+    pub fn transpose(&self) -> Matrix {
+        let entries = (0..self.cols)
+            .flat_map(|j| (0..self.rows).map(move |i| (i, j)))
+            .map(|(i, j)| self.get(i, j).clone())
+            .collect();
+        Matrix {
+            rows: self.cols,
+            cols: self.rows,
+            entries,
+        }
+    }
+
+    /// Apply a fallible function to each entry.
+    pub fn try_map<E>(&self, f: impl FnMut(&Value) -> Result<Value, E>) -> Result<Matrix, E> {
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            entries: self.entries.iter().map(f).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Build a matrix of the given shape from a fallible function of each
+    /// row and column.
+    pub fn try_build<E>(
+        rows: usize,
+        cols: usize,
+        mut f: impl FnMut(usize, usize) -> Result<Value, E>,
+    ) -> Result<Matrix, E> {
+        let mut entries = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                entries.push(f(i, j)?);
+            }
+        }
+        Ok(Matrix {
+            rows,
+            cols,
+            entries,
+        })
+    }
+
+    /// Get the entries as numbers for elimination: big rationals if they are
+    /// all rational, or floats otherwise.
+    ///
+    /// Returns `None` if an entry is not a number.
+    fn numbers(&self) -> Option<Numbers> {
+        if let Some(exact) = self.entries.iter().map(Value::to_big).collect() {
+            return Some(Numbers::Exact(self.split_rows(exact)));
+        }
+        let floats = self
+            .entries
+            .iter()
+            .map(Value::as_f64)
+            .collect::<Option<_>>()?;
+        Some(Numbers::Float(self.split_rows(floats)))
+    }
+
+    fn split_rows<T: Clone>(&self, entries: Vec<T>) -> Vec<Vec<T>> {
+        entries.chunks(self.cols).map(<[T]>::to_vec).collect()
+    }
+
+    /// Join the columns of `other` to the right of this matrix.
+    ///
+    /// Panics if the matrices have different numbers of rows.
+    fn augment(&self, other: &Matrix) -> Matrix {
+        assert_eq!(self.rows, other.rows);
+        let rows = (0..self.rows)
+            .map(|i| [self.row(i), other.row(i)].concat())
+            .collect();
+        Matrix::from_rows(rows).unwrap()
+    }
+
+    /// Get the determinant of a square matrix.
+    ///
+    /// Returns `None` if an entry is not a number. Panics if the matrix is
+    /// not square.
+    pub fn det(&self) -> Option<Value> {
+        assert!(self.is_square());
+        Some(match self.numbers()? {
+            Numbers::Exact(m) => det(m).into(),
+            Numbers::Float(m) => det(m).into(),
+        })
+    }
+
+    /// Get the rank of the matrix.
+    ///
+    /// Returns `None` if an entry is not a number.
+    pub fn rank(&self) -> Option<usize> {
+        Some(match self.numbers()? {
+            Numbers::Exact(mut m) => row_reduce(&mut m, self.cols).0.len(),
+            Numbers::Float(mut m) => row_reduce(&mut m, self.cols).0.len(),
+        })
+    }
+
+    /// Get the inverse of a square matrix.
+    ///
+    /// Returns `None` if an entry is not a number, or `Some(None)` if the
+    /// matrix is singular. Panics if the matrix is not square.
+    pub fn inverse(&self) -> Option<Option<Matrix>> {
+        self.solve(&Matrix::identity(self.rows))
+    }
+
+    /// Solve the linear system `self * x = b` for `x`, where this matrix is
+    /// square and `b` has as many rows.
+    ///
+    /// Returns `None` if an entry is not a number, or `Some(None)` if this
+    /// matrix is singular. Panics if the shapes are wrong.
+    pub fn solve(&self, b: &Matrix) -> Option<Option<Matrix>> {
+        assert!(self.is_square());
+        let n = self.rows;
+        let aug = self.augment(b);
+        let rows = match aug.numbers()? {
+            Numbers::Exact(m) => solve_reduced(m, n),
+            Numbers::Float(m) => solve_reduced(m, n),
+        };
+        Some(rows.map(|rows| Matrix::from_rows(rows).unwrap()))
+    }
+}
+
+/// The entries of a matrix, as the numbers that elimination is done with.
+enum Numbers {
+    Exact(Vec<Vec<BigRational>>),
+    Float(Vec<Vec<f64>>),
+}
+
+/// A number that Gaussian elimination can be done with.
+trait Scalar: Num + Signed + PartialOrd + Clone + Into<Value> {
+    /// Get the magnitude at or below which entries of `m` are treated as
+    /// zero, to allow for rounding errors.
+    fn tolerance(m: &[Vec<Self>]) -> Self;
+}
+
+impl Scalar for BigRational {
+    fn tolerance(_: &[Vec<Self>]) -> Self {
+        BigRational::zero()
+    }
+}
+
+impl Scalar for f64 {
+    fn tolerance(m: &[Vec<Self>]) -> Self {
+        let max = m.iter().flatten().fold(0.0, |a: f64, x| a.max(x.abs()));
+        let size = m.len().max(m.first().map_or(0, Vec::len));
+        max * size as f64 * f64::EPSILON
+    }
+}
+
+/// Reduce the first `cols` columns of `m` to reduced row echelon form by
+/// Gauss-Jordan elimination, applying the same row operations to any
+/// further columns.
+///
+/// Returns the columns that contain pivots, and the determinant of the
+/// operations, which is the determinant of `m` if it is square and
+/// non-singular.
+fn row_reduce<T: Scalar>(m: &mut [Vec<T>], cols: usize) -> (Vec<usize>, T) {
+    let tol = T::tolerance(m);
+    let mut pivots = Vec::new();
+    let mut det = T::one();
+    for j in 0..cols {
+        let r = pivots.len();
+        if r == m.len() {
+            break;
+        }
+        // Use the largest entry as the pivot, for numerical stability
+        let (best, _) = (r..m.len())
+            .map(|i| (i, m[i][j].abs()))
+            .fold((r, T::zero()), |a, b| if b.1 > a.1 { b } else { a });
+        if m[best][j].abs() <= tol {
+            continue;
+        }
+        if best != r {
+            m.swap(best, r);
+            det = -det;
+        }
+        let pivot = m[r][j].clone();
+        det = det * pivot.clone();
+        for x in m[r].iter_mut() {
+            *x = x.clone() / pivot.clone();
+        }
+        for i in 0..m.len() {
+            if i != r && !m[i][j].is_zero() {
+                let factor = m[i][j].clone();
+                for k in 0..m[i].len() {
+                    let delta = factor.clone() * m[r][k].clone();
+                    m[i][k] = m[i][k].clone() - delta;
+                }
+            }
+        }
+        pivots.push(j);
+    }
+    (pivots, det)
+}
+
+/// Get the determinant of a square matrix.
+fn det<T: Scalar>(mut m: Vec<Vec<T>>) -> T {
+    let n = m.len();
+    let (pivots, det) = row_reduce(&mut m, n);
+    if pivots.len() == n {
+        det
+    } else {
+        T::zero()
+    }
+}
+
+/// Solve the system whose coefficients are the first `n` columns of `m` and
+/// whose right-hand sides are the remaining columns.
+///
+/// Returns `None` if the coefficients are singular.
+fn solve_reduced<T: Scalar>(mut m: Vec<Vec<T>>, n: usize) -> Option<Vec<Vec<Value>>> {
+    let (pivots, _) = row_reduce(&mut m, n);
+    if pivots.len() < n {
+        return None;
+    }
+    let rows = m
+        .into_iter()
+        .map(|row| row.into_iter().skip(n).map(Into::into).collect())
+        .collect();
+    Some(rows)
+}
+
+impl From<Matrix> for Value {
+    fn from(val: Matrix) -> Value {
+        Value::Matrix(val)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{Matrix, Quantity, Value};
+
+/// Create a matrix of integers.
+fn ints(rows: &[&[i64]]) -> Matrix {
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(|&x| x.into()).collect())
+        .collect();
+    Matrix::from_rows(rows).unwrap()
+}
+
+/// Create a matrix of floats.
+fn floats(rows: &[&[f64]]) -> Matrix {
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(|&x| x.into()).collect())
+        .collect();
+    Matrix::from_rows(rows).unwrap()
+}
+
+#[test]
+fn construct() {
+    let m = ints(&[&[1, 2, 3], &[4, 5, 6]]);
+    assert_eq!((m.rows(), m.cols()), (2, 3));
+    assert_eq!(m.get(1, 0), &Value::from(4));
+    assert_eq!(m.row(1), &[4.into(), 5.into(), 6.into()]);
+    assert_eq!(m.transpose(), ints(&[&[1, 4], &[2, 5], &[3, 6]]));
+    assert_eq!(Matrix::identity(2), ints(&[&[1, 0], &[0, 1]]));
+    assert_eq!(
+        Matrix::column(vec![1.into(), 2.into()]),
+        Some(ints(&[&[1], &[2]]))
+    );
+    assert_eq!(Matrix::from_rows(vec![]), None);
+    assert_eq!(Matrix::from_rows(vec![vec![]]), None);
+    assert_eq!(
+        Matrix::from_rows(vec![vec![1.into()], vec![1.into(), 2.into()]]),
+        None
+    );
+}
+
+#[test]
+fn determinant() {
+    assert_eq!(ints(&[&[1, 2], &[3, 4]]).det(), Some((-2).into()));
+    assert_eq!(ints(&[&[0, 1], &[1, 0]]).det(), Some((-1).into()));
+    assert_eq!(ints(&[&[1, 2], &[2, 4]]).det(), Some(0.into()));
+    assert_eq!(
+        ints(&[&[2, 0, 1], &[1, 3, 2], &[1, 1, 2]]).det(),
+        Some(6.into())
+    );
+    assert_eq!(Matrix::identity(5).det(), Some(1.into()));
+    assert_eq!(floats(&[&[1.5, 2.0], &[3.0, 4.5]]).det(), Some(0.75.into()));
+    let m = Matrix::from_rows(vec![vec![Value::Quantity(Quantity::scalar(1.0))]]);
+    assert_eq!(m.unwrap().det(), None);
+}
+
+#[test]
+fn rank() {
+    assert_eq!(ints(&[&[1, 2], &[2, 4]]).rank(), Some(1));
+    assert_eq!(ints(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]]).rank(), Some(2));
+    assert_eq!(ints(&[&[1, 2, 3], &[4, 5, 7]]).rank(), Some(2));
+    assert_eq!(ints(&[&[0, 0], &[0, 0]]).rank(), Some(0));
+    assert_eq!(
+        floats(&[&[0.1, 0.2, 0.3], &[0.4, 0.5, 0.6], &[0.7, 0.8, 0.9]]).rank(),
+        Some(2)
+    );
+}
+
+#[test]
+fn inverse() {
+    let inv = ints(&[&[1, 2], &[3, 4]]).inverse().unwrap().unwrap();
+    let expected = vec![
+        vec![(-2).into(), 1.into()],
+        vec![(3, 2).into(), (-1, 2).into()],
+    ];
+    assert_eq!(inv, Matrix::from_rows(expected).unwrap());
+    assert_eq!(ints(&[&[1, 2], &[2, 4]]).inverse(), Some(None));
+    let inv = floats(&[&[4.0, 7.0], &[2.0, 6.0]])
+        .inverse()
+        .unwrap()
+        .unwrap();
+    let expected = [0.6, -0.7, -0.2, 0.4];
+    for (x, y) in inv.into_entries().iter().zip(&expected) {
+        assert!((x.as_f64().unwrap() - y).abs() < 1e-15);
+    }
+}
+
+#[test]
+fn solve() {
+    let a = ints(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+    let b = ints(&[&[8], &[-11], &[-3]]);
+    assert_eq!(a.solve(&b), Some(Some(ints(&[&[2], &[3], &[-1]]))));
+    // Requires a row swap
+    let a = ints(&[&[0, 1], &[1, 0]]);
+    let b = ints(&[&[5, 1], &[7, 2]]);
+    assert_eq!(a.solve(&b), Some(Some(ints(&[&[7, 2], &[5, 1]]))));
+    let a = ints(&[&[1, 1], &[1, 1]]);
+    assert_eq!(a.solve(&ints(&[&[1], &[2]])), Some(None));
+}
@@ -0,0 +1,225 @@
+//! Number theory on exact integers.
+
+use std::convert::TryFrom;
+
+#[cfg(test)]
+mod tests;
+
+/// Get the greatest common divisor of two integers, which is never negative.
+///
+/// Returns `None` if the result does not fit in an `i64`, which only happens
+/// for `gcd(i64::MIN, i64::MIN)` and similar.
+pub fn gcd(a: i64, b: i64) -> Option<i64> {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    i64::try_from(a).ok()
+}
+
+/// Get the least common multiple of two integers, which is never negative.
+///
+/// Returns `None` if the result overflows.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let g = gcd(a, b)?;
+    (a / g).checked_mul(b)?.checked_abs()
+}
+
+/// Compute `a * b mod m` without overflow.
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// Compute `a^b mod m` by repeated squaring.
+fn pow_mod(mut a: u64, mut b: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    a %= m;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = mul_mod(result, a, m);
+        }
+        a = mul_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+/// Bases for which the Miller-Rabin test is deterministic below 2^64.
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Test whether `n` is prime using the deterministic Miller-Rabin test.
+fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'witness: for &a in &WITNESSES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Test whether an integer is prime. Negative numbers are never prime.
+pub fn is_prime(n: i64) -> bool {
+    n > 0 && is_prime_u64(n as u64)
+}
+
+/// Get the smallest prime greater than `n`.
+///
+/// Returns `None` if there is no such prime that fits in an `i64`.
+pub fn next_prime(n: i64) -> Option<i64> {
+    if n < 2 {
+        return Some(2);
+    }
+    let mut p = n.checked_add(1)?;
+    while !is_prime(p) {
+        p = p.checked_add(1)?;
+    }
+    Some(p)
+}
+
+/// Find a non-trivial factor of the odd composite `n` using Pollard's rho
+/// algorithm.
+fn pollard_rho(n: u64) -> u64 {
+    let mut c = 1;
+    loop {
+        let f = |x| (mul_mod(x, x, n) + c) % n;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd_u64(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Push the prime factors of `n`, with repetition, onto `out`.
+fn push_factors(n: u64, out: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime_u64(n) {
+        out.push(n);
+        return;
+    }
+    for &p in &WITNESSES {
+        if n.is_multiple_of(p) {
+            out.push(p);
+            return push_factors(n / p, out);
+        }
+    }
+    let d = pollard_rho(n);
+    push_factors(d, out);
+    push_factors(n / d, out);
+}
+
+/// Factorise a non-zero integer into primes and their exponents, in
+/// ascending order. Negative numbers have a leading factor of -1.
+///
+/// Returns `None` if `n` is zero.
+pub fn factorize(n: i64) -> Option<Vec<(i64, u32)>> {
+    if n == 0 {
+        return None;
+    }
+    let mut primes = Vec::new();
+    push_factors(n.unsigned_abs(), &mut primes);
+    primes.sort_unstable();
+    let mut factors: Vec<(i64, u32)> = Vec::new();
+    if n < 0 {
+        factors.push((-1, 1));
+    }
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, e)) if *q == p as i64 => *e += 1,
+            _ => factors.push((p as i64, 1)),
+        }
+    }
+    Some(factors)
+}
+
+/// Get Euler's totient of a positive integer, i.e., how many integers in
+/// `1..=n` are coprime to `n`.
+///
+/// Returns `None` if `n` is not positive.
+pub fn totient(n: i64) -> Option<i64> {
+    if n <= 0 {
+        return None;
+    }
+    let mut result = n;
+    for (p, _) in factorize(n)? {
+        result = result / p * (p - 1);
+    }
+    Some(result)
+}
+
+/// Get the inverse of `a` modulo `m`, in the range `0..m`.
+///
+/// Returns `None` if `m` is not positive or `a` is not coprime to `m`.
+pub fn mod_inv(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let m = m as i128;
+    let (mut r0, mut r1) = (m, (a as i128).rem_euclid(m));
+    let (mut t0, mut t1) = (0_i128, 1_i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 != 1 {
+        return None;
+    }
+    Some(t0.rem_euclid(m) as i64)
+}
+
+/// Compute `a^b mod m`, in the range `0..m`. A negative exponent uses the
+/// modular inverse of `a`.
+///
+/// Returns `None` if `m` is not positive, or if `b` is negative and `a` has
+/// no inverse.
+pub fn mod_pow(a: i64, b: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let base = if b < 0 {
+        mod_inv(a, m)?
+    } else {
+        a.rem_euclid(m)
+    };
+    Some(pow_mod(base as u64, b.unsigned_abs(), m as u64) as i64)
+}
@@ -0,0 +1,70 @@
+use crate::{factorize, gcd, is_prime, lcm, mod_inv, mod_pow, next_prime, totient};
+
+#[test]
+fn gcd_lcm() {
+    assert_eq!(gcd(12, 18), Some(6));
+    assert_eq!(gcd(-12, 18), Some(6));
+    assert_eq!(gcd(0, 5), Some(5));
+    assert_eq!(gcd(0, 0), Some(0));
+    assert_eq!(gcd(i64::MIN, 0), None);
+    assert_eq!(lcm(4, 6), Some(12));
+    assert_eq!(lcm(-4, 6), Some(12));
+    assert_eq!(lcm(0, 6), Some(0));
+    assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+}
+
+#[test]
+fn primes() {
+    let small: Vec<i64> = (0..30).filter(|&n| is_prime(n)).collect();
+    assert_eq!(small, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert!(!is_prime(-7));
+    assert!(is_prime(1_000_000_007));
+    assert!(!is_prime(3_215_031_751));
+    assert!(is_prime(9_223_372_036_854_775_783));
+    assert_eq!(next_prime(-5), Some(2));
+    assert_eq!(next_prime(13), Some(17));
+    assert_eq!(next_prime(1_000_000_000), Some(1_000_000_007));
+    assert_eq!(next_prime(i64::MAX - 1), None);
+}
+
+#[test]
+fn factors() {
+    assert_eq!(factorize(360), Some(vec![(2, 3), (3, 2), (5, 1)]));
+    assert_eq!(factorize(-6), Some(vec![(-1, 1), (2, 1), (3, 1)]));
+    assert_eq!(factorize(1), Some(vec![]));
+    assert_eq!(factorize(0), None);
+    assert_eq!(
+        factorize(600_851_475_143),
+        Some(vec![(71, 1), (839, 1), (1471, 1), (6857, 1)])
+    );
+    assert_eq!(
+        factorize(999_999_000_001 * 7),
+        Some(vec![(7, 1), (999_999_000_001, 1)])
+    );
+    assert_eq!(factorize(i64::MIN), Some(vec![(-1, 1), (2, 63)]));
+}
+
+#[test]
+fn euler_totient() {
+    assert_eq!(totient(1), Some(1));
+    assert_eq!(totient(36), Some(12));
+    assert_eq!(totient(97), Some(96));
+    assert_eq!(totient(0), None);
+}
+
+#[test]
+fn modular() {
+    assert_eq!(mod_pow(4, 13, 497), Some(445));
+    assert_eq!(mod_pow(-2, 3, 5), Some(2));
+    assert_eq!(mod_pow(3, -1, 7), Some(5));
+    assert_eq!(mod_pow(2, -1, 4), None);
+    assert_eq!(mod_pow(5, 0, 1), Some(0));
+    assert_eq!(mod_pow(2, 10, 0), None);
+    assert_eq!(mod_inv(3, 11), Some(4));
+    assert_eq!(mod_inv(-3, 11), Some(7));
+    assert_eq!(mod_inv(6, 9), None);
+    assert_eq!(
+        mod_pow(123_456_789, 987_654_321, 1_000_000_007),
+        Some(652_541_198)
+    );
+}
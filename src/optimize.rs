@@ -0,0 +1,119 @@
+//! Optimisation of function bodies before they are evaluated.
+//!
+//! A function body is evaluated in a fresh context each time that the
+//! function is called, so any subexpression without free variables or
+//! assignments has the same value on every call. Such subexpressions are
+//! evaluated once, when the function is defined, and replaced by their
+//! values. A subexpression that fails to evaluate is kept, so that the error
+//! is still reported when the function is called.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{eval, Context, Expression, Span, Value};
+
+type ExprSpan = (Expression, Span);
+
+/// Test whether an expression assigns to a variable.
+fn has_let(expr: &Expression) -> bool {
+    match expr {
+        Expression::Let(..) => true,
+        _ => expr.children().into_iter().any(|(e, _)| has_let(e)),
+    }
+}
+
+/// Evaluate an expression if it has the same value wherever it is
+/// evaluated.
+fn fold((expr, span): &ExprSpan) -> Option<ExprSpan> {
+    use Expression::*;
+    match expr {
+        Val(_) | Lambda(..) | Equation(_) => return None,
+        // An exact constant stays a name for differentiation and printing
+        Const(con) if matches!(con.value(), Value::Symbolic(_)) => return None,
+        _ if has_let(expr) || !expr.free_vars().is_empty() => return None,
+        _ => (),
+    }
+    let val = eval(&(expr.clone(), span.clone()), &mut Context::default()).ok()?;
+    Some((Val(val), span.clone()))
+}
+
+/// Optimise the arguments of a built-in function, which are separated by
+/// commas but are not a sequence of expressions.
+fn optimize_args((expr, span): &ExprSpan) -> ExprSpan {
+    match expr {
+        Expression::Comma(v) => (
+            Expression::Comma(v.iter().map(optimize).collect()),
+            span.clone(),
+        ),
+        _ => optimize(&(expr.clone(), span.clone())),
+    }
+}
+
+/// Fold the constants at the start of a sum or product, which are combined
+/// first when it is evaluated.
+fn fold_prefix(v: Vec<ExprSpan>, span: &Span, make: fn(Vec<ExprSpan>) -> Expression) -> Expression {
+    let n = v
+        .iter()
+        .take_while(|(e, _)| matches!(e, Expression::Val(_)))
+        .count();
+    if n < 2 {
+        return make(v);
+    }
+    let mut rest = v;
+    let prefix: Vec<_> = rest.drain(..n).collect();
+    match fold(&(make(prefix.clone()), span.clone())) {
+        Some(folded) if rest.is_empty() => folded.0,
+        Some(folded) => make(std::iter::once(folded).chain(rest).collect()),
+        None => make(prefix.into_iter().chain(rest).collect()),
+    }
+}
+
+/// Optimise an expression by evaluating its constant subexpressions and
+/// removing constants from sequences where their values are unused.
+pub fn optimize(expr: &ExprSpan) -> ExprSpan {
+    use Expression::*;
+    if let Some(folded) = fold(expr) {
+        return folded;
+    }
+    let (expr, span) = expr;
+    let opt_all = |v: &[ExprSpan]| v.iter().map(optimize).collect::<Vec<_>>();
+    let opt_pair = |[a, b]: &[ExprSpan; 2]| Box::new([optimize(a), optimize(b)]);
+    let opt_triple = |[a, b, c]: &[ExprSpan; 3]| Box::new([optimize(a), optimize(b), optimize(c)]);
+    let expr = match expr {
+        Val(_) | Const(_) | Var(_) => expr.clone(),
+        Neg(e) => Neg(Box::new(optimize(e))),
+        Add(v) => fold_prefix(opt_all(v), span, Add),
+        Sub(args) => Sub(opt_pair(args)),
+        Mul(v) => fold_prefix(opt_all(v), span, Mul),
+        Frac(args) => Frac(opt_pair(args)),
+        Exp(args) => Exp(opt_pair(args)),
+        Root(args) => Root(opt_pair(args)),
+        Func(f, e) => Func(*f, Box::new(optimize_args(e))),
+        Let(var, e) => Let(var.clone(), Box::new(optimize(e))),
+        Call(name, args) => Call(name.clone(), opt_all(args)),
+        Lambda(params, body) => Lambda(params.clone(), Box::new(optimize(body))),
+        Convert(args) => Convert(opt_pair(args)),
+        List(v) => List(opt_all(v)),
+        Index(args) => Index(opt_pair(args)),
+        Range(args) => Range(opt_pair(args)),
+        Sum(var, args) => Sum(var.clone(), opt_triple(args)),
+        Product(var, args) => Product(var.clone(), opt_triple(args)),
+        Integral(var, args) => Integral(var.clone(), opt_triple(args)),
+        Equation(args) => Equation(opt_pair(args)),
+        Solve(var, eq, guesses) => Solve(var.clone(), Box::new(optimize(eq)), opt_all(guesses)),
+        // These work on the form of the expression rather than its value
+        Derivative(..) | Simplify(_) => expr.clone(),
+        Comma(v) => {
+            // Only the value of the last expression is used
+            let mut v = opt_all(v);
+            let last = v.pop().unwrap();
+            v.retain(|(e, _)| !matches!(e, Val(_) | Const(_)));
+            if v.is_empty() {
+                return last;
+            }
+            v.push(last);
+            Comma(v)
+        }
+    };
+    (expr, span.clone())
+}
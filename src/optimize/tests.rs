@@ -0,0 +1,82 @@
+use crate::{eval, lex, optimize, parse, Context, Expression, Span, Value};
+
+fn optimized(s: &str) -> String {
+    optimize(&parse(lex(s, None)).unwrap()).0.to_string()
+}
+
+/// Evaluate an expression with `x` set, as in a call to a function of `x`.
+fn call(expr: &(Expression, Span), x: Value) -> Value {
+    let mut ctx = Context::default();
+    ctx.vars.insert("x".to_string(), x);
+    eval(expr, &mut ctx).unwrap()
+}
+
+#[test]
+fn folds_constants() {
+    assert_eq!(optimized("2 * pi / 360 * x"), "1 / 180 * pi * x");
+    assert_eq!(optimized("x + (1 + 2) * 3"), "x + 9");
+    assert_eq!(optimized("1 + 2 + x + 3"), "3 + x + 3");
+    assert_eq!(optimized("sin(x) * (2^10 - 24)"), "sin(x) * 1000");
+    assert_eq!(optimized("x * phys.g0"), "x * (9.80665 m/s^2)");
+    assert_eq!(optimized("e^x + pi"), "e^x + pi");
+    assert_eq!(optimized("sum(k, 1, 4, k^2) * x"), "30 * x");
+    assert_eq!(
+        optimized("sum(k, 1, x, k * (1 + 1))"),
+        "sum(k, 1, x, k * 2)"
+    );
+}
+
+#[test]
+fn removes_dead_values() {
+    assert_eq!(optimized("x, 1 + 2"), "x, 3");
+    assert_eq!(optimized("1, y = 2, pi, y * x"), "y = 2, y * x");
+    assert_eq!(optimized("(1, 2, x) + 1"), "x + 1");
+    assert_eq!(optimized("max(1, 2, x)"), "max(1, 2, x)");
+}
+
+#[test]
+fn keeps_side_effects_and_errors() {
+    assert_eq!(optimized("(y = 2) + y * x"), "(y = 2) + y * x");
+    assert_eq!(optimized("x + [1, 2][5]"), "x + [1, 2][5]");
+    assert_eq!(optimized("x + log(0 - 1, 2)"), "x + log(-1, 2)");
+    assert_eq!(optimized("f(2) + x"), "f(2) + x");
+}
+
+#[test]
+fn preserves_values() {
+    for s in [
+        "2 * pi / 360 * x",
+        // Integer arithmetic that overflows falls back to floats
+        "x + 2^70",
+        "x * 2^62 * 4",
+        "1 / 3 * 3 + x",
+        "fact(25) / x",
+        "2^62 * 4 + x - 2^64",
+        "(1, 2) + x * sqrt2^2",
+        "[1, 1 / 2] * x + e",
+        "integrate(t, 0, 1, t^2) * x",
+        "x -> x + 2 * 3",
+    ] {
+        let expr = parse(lex(s, None)).unwrap();
+        let opt = optimize(&expr);
+        for x in [Value::from(3), Value::from((1, 7)), Value::from(0.5)] {
+            assert_eq!(call(&expr, x.clone()), call(&opt, x), "{}", s);
+        }
+    }
+}
+
+#[test]
+fn function_definition() {
+    let mut ctx = Context::default();
+    let f = eval(
+        &parse(lex("x -> x * (1 / 2 + 1 / 3)", None)).unwrap(),
+        &mut ctx,
+    )
+    .unwrap();
+    assert_eq!(ctx.display(&f).to_string(), "x -> x * (5 / 6)");
+    let f = eval(&parse(lex("x -> x + 2^64", None)).unwrap(), &mut ctx).unwrap();
+    assert_eq!(
+        ctx.display(&f).to_string(),
+        "x -> x + 1.8446744073709552e19"
+    );
+}
@@ -1,6 +1,10 @@
 //! Parse a string into an AST.
 
-use crate::{Error, Expression, Result, Span, Token, TokenStream, Value};
+use std::convert::TryFrom;
+
+use num::BigInt;
+
+use crate::{parse_unit, Error, Expression, Function, Result, Span, Token, TokenStream, Value};
 
 #[cfg(test)]
 mod tests;
@@ -22,23 +26,72 @@ fn parse_0<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     }
 }
 
-/// Parse a first-level expression: variable assignment.
+/// Parse a first-level expression: variable assignment, or an equation if
+/// the left-hand side is not a variable.
 fn parse_1<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
-    let expr = parse_2(it)?;
+    let expr = parse_lambda(it)?;
     if let (Token::Equals, _) = it.peek()? {
         let (_, span) = it.next()?;
+        let rhs = parse_lambda(it)?;
         match expr {
-            (Expression::Var(s), _) => {
-                let rhs = parse_2(it)?;
-                Ok((Expression::Let(s, Box::new(rhs)), span))
-            }
-            _ => Err((Error::Syntax, span)),
+            (Expression::Var(s), _) => Ok((Expression::Let(s, Box::new(rhs)), span)),
+            lhs => Ok((Expression::Equation(Box::new([lhs, rhs])), span)),
         }
     } else {
         Ok(expr)
     }
 }
 
+/// Parse an anonymous function, such as `x -> x^2` or `(x, y) -> x * y`,
+/// whose parameters and body are ranges.
+fn parse_lambda<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
+    let expr = parse_range(it)?;
+    if let (Token::Arrow, _) = it.peek()? {
+        let (_, span) = it.next()?;
+        let params = match expr {
+            (Expression::Var(s), _) => vec![s],
+            (Expression::Comma(v), _) => v
+                .into_iter()
+                .map(|param| match param {
+                    (Expression::Var(s), _) => Ok(s),
+                    (_, span) => Err((Error::Syntax, span)),
+                })
+                .collect::<Result<_>>()?,
+            (_, span) => return Err((Error::Syntax, span)),
+        };
+        // Right associative
+        let body = parse_lambda(it)?;
+        Ok((Expression::Lambda(params, Box::new(body)), span))
+    } else {
+        Ok(expr)
+    }
+}
+
+/// Parse a range, such as `1..n`, whose bounds are unit conversions.
+fn parse_range<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
+    let expr = parse_convert(it)?;
+    if let (Token::DotDot, _) = it.peek()? {
+        let (_, span) = it.next()?;
+        let rhs = parse_convert(it)?;
+        Ok((Expression::Range(Box::new([expr, rhs])), span))
+    } else {
+        Ok(expr)
+    }
+}
+
+/// Parse unit conversions, such as `x to km/h` or `x in ft`, whose operands
+/// are second-level expressions.
+fn parse_convert<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
+    let mut expr = parse_2(it)?;
+    // Keep grabbing conversions (left associative)
+    while let (Token::Ident("to"), _) | (Token::Ident("in"), _) = it.peek()? {
+        let (_, span) = it.next()?;
+        let rhs = resolve_units(parse_2(it)?);
+        expr = (Expression::Convert(Box::new([expr, rhs])), span);
+    }
+    Ok(expr)
+}
+
 /// Parse a second-level expression: addition and subtraction.
 fn parse_2<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     let mut expr = parse_3(it)?;
@@ -67,12 +120,18 @@ fn parse_2<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
 /// Parse a third-level expression: multiplication and division.
 fn parse_3<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     let mut expr = parse_4(it)?;
+    if is_number_literal(&expr.0) {
+        expr = parse_unit_suffix(it, expr)?;
+    }
     // Keep grabbing multiplications and divisions (left associative)
     loop {
         match it.peek()? {
             (Token::Times, _) => {
                 let (_, span) = it.next()?;
-                let rhs = parse_4(it)?;
+                let mut rhs = unit_after(&expr, parse_4(it)?);
+                if is_number_literal(&rhs.0) {
+                    rhs = parse_unit_suffix(it, rhs)?;
+                }
                 if let (Expression::Mul(ref mut v), _) = expr {
                     v.push(rhs);
                 } else {
@@ -81,8 +140,20 @@ fn parse_3<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
             }
             (Token::Divide, _) => {
                 let (_, span) = it.next()?;
-                let rhs = parse_4(it)?;
-                expr = (Expression::Frac(Box::new([expr, rhs])), span);
+                let rhs = unit_after(&expr, parse_4(it)?);
+                if is_number_literal(&expr.0) && is_number_literal(&rhs.0) {
+                    // The unit of a fraction, as in `1 / 2 m`, applies to the
+                    // whole fraction
+                    expr = (Expression::Frac(Box::new([expr, rhs])), span);
+                    expr = parse_unit_suffix(it, expr)?;
+                } else {
+                    let rhs = if is_number_literal(&rhs.0) {
+                        parse_unit_suffix(it, rhs)?
+                    } else {
+                        rhs
+                    };
+                    expr = (Expression::Frac(Box::new([expr, rhs])), span);
+                }
             }
             _ => break Ok(expr),
         }
@@ -98,9 +169,37 @@ fn parse_4<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
         }
         (Token::Ident(id), _) => {
             if let Ok(func) = id.parse() {
+                let id = *id;
                 let (_, span) = it.next()?;
+                let is_call = matches!(it.peek()?.0, Token::LeftParen | Token::LeftBracket);
+                if parse_unit(id).is_some() && !is_call {
+                    // A unit with the same name as a function, such as `min`
+                    return parse_exponent(it, (Expression::Var(id.to_string()), span));
+                }
+                if let Token::Comma | Token::RightParen | Token::RightBracket | Token::Eof =
+                    it.peek()?.0
+                {
+                    // The function itself, as in `map(sin, v)`
+                    return Ok((Expression::Val(Value::Builtin(func)), span));
+                }
                 let expr = parse_4(it)?;
-                Ok((Expression::Func(func, Box::new(expr)), span))
+                match (func, expr) {
+                    (
+                        Function::Sum | Function::Prod | Function::Integrate,
+                        (Expression::Comma(args), _),
+                    ) if args.len() == 4 => parse_series(func, args, span),
+                    (Function::Diff, (Expression::Comma(args), _)) if args.len() == 2 => {
+                        parse_derivative(args, span)
+                    }
+                    (Function::Simplify, (Expression::Comma(_), _)) => Err((Error::Syntax, span)),
+                    (Function::Simplify, expr) => Ok((Expression::Simplify(Box::new(expr)), span)),
+                    (Function::Solve, (Expression::Comma(args), _))
+                        if matches!(args[0].0, Expression::Equation(_) | Expression::Let(..)) =>
+                    {
+                        parse_solve(args, span)
+                    }
+                    (_, expr) => Ok((Expression::Func(func, Box::new(expr)), span)),
+                }
             } else {
                 parse_5(it)
             }
@@ -109,9 +208,79 @@ fn parse_4<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     }
 }
 
+/// Turn the arguments of `sum`, `prod` or `integrate` into an expression
+/// over a bound variable, such as `sum(k, 1, n, k^2)`.
+fn parse_series(
+    func: Function,
+    args: Vec<(Expression, Span)>,
+    span: Span,
+) -> Result<(Expression, Span)> {
+    let mut args = args.into_iter();
+    let var = match args.next() {
+        Some((Expression::Var(var), _)) => var,
+        Some((_, var_span)) => return Err((Error::Syntax, var_span)),
+        None => unreachable!(),
+    };
+    let args = Box::new([
+        args.next().unwrap(),
+        args.next().unwrap(),
+        args.next().unwrap(),
+    ]);
+    match func {
+        Function::Sum => Ok((Expression::Sum(var, args), span)),
+        Function::Prod => Ok((Expression::Product(var, args), span)),
+        _ => Ok((Expression::Integral(var, args), span)),
+    }
+}
+
+/// Turn the arguments of `diff` into the derivative of an expression with
+/// respect to a variable, such as `diff(x^2, x)`.
+fn parse_derivative(args: Vec<(Expression, Span)>, span: Span) -> Result<(Expression, Span)> {
+    let mut args = args.into_iter();
+    let expr = args.next().unwrap();
+    match args.next() {
+        Some((Expression::Var(var), _)) => Ok((Expression::Derivative(var, Box::new(expr)), span)),
+        Some((_, var_span)) => Err((Error::Syntax, var_span)),
+        None => unreachable!(),
+    }
+}
+
+/// Turn the arguments of `solve` into the solution of an equation for a
+/// variable, such as `solve(x^2 = 2, x, 1)`.
+fn parse_solve(args: Vec<(Expression, Span)>, span: Span) -> Result<(Expression, Span)> {
+    if args.len() > 4 {
+        return Err((Error::Syntax, span));
+    }
+    let mut args = args.into_iter();
+    let eq = match args.next() {
+        // An equation with a variable on the left is parsed as an assignment
+        Some((Expression::Let(var, rhs), eq_span)) => (
+            Expression::Equation(Box::new([(Expression::Var(var), eq_span.clone()), *rhs])),
+            eq_span,
+        ),
+        Some(eq) => eq,
+        None => unreachable!(),
+    };
+    let var = match args.next() {
+        Some((Expression::Var(var), _)) => var,
+        Some((_, var_span)) => return Err((Error::Syntax, var_span)),
+        None => return Err((Error::Syntax, span)),
+    };
+    Ok((Expression::Solve(var, Box::new(eq), args.collect()), span))
+}
+
 /// Parse a fifth-level expression: exponentiation.
 fn parse_5<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
-    let lhs = parse_6(it)?;
+    let lhs = parse_index(it)?;
+    parse_exponent(it, lhs)
+}
+
+/// Parse an exponent following the already parsed base `lhs`, if there is
+/// one.
+fn parse_exponent<'a>(
+    it: &mut impl TokenStream<'a>,
+    lhs: (Expression, Span),
+) -> Result<(Expression, Span)> {
     match it.peek()? {
         (Token::Exponent, _) => {
             let (_, span) = it.next()?;
@@ -123,8 +292,44 @@ fn parse_5<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     }
 }
 
-/// Parse a sixth-level expression: numeric literals, function calls, and
-/// parentheses.
+/// Parse list indexing, such as `v[0]`, whose operand is a sixth-level
+/// expression.
+fn parse_index<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
+    let mut expr = parse_6(it)?;
+    while let (Token::LeftBracket, _) = it.peek()? {
+        let (_, span) = it.next()?;
+        let index = parse_1(it)?;
+        let (tok, close_span) = it.next()?;
+        if tok != Token::RightBracket {
+            return Err((Error::Syntax, close_span));
+        }
+        expr = (Expression::Index(Box::new([expr, index])), span);
+    }
+    Ok(expr)
+}
+
+/// Parse comma-separated expressions up to and including the `close` token.
+fn parse_items<'a>(
+    it: &mut impl TokenStream<'a>,
+    close: Token<'a>,
+) -> Result<Vec<(Expression, Span)>> {
+    let mut items = vec![];
+    if it.peek()?.0 == close {
+        it.next()?;
+        return Ok(items);
+    }
+    loop {
+        items.push(parse_1(it)?);
+        match it.next()? {
+            (Token::Comma, _) => (),
+            (tok, _) if tok == close => break Ok(items),
+            (_, span) => break Err((Error::Syntax, span)),
+        }
+    }
+}
+
+/// Parse a sixth-level expression: numeric literals, lists, function calls,
+/// and parentheses.
 fn parse_6<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     let (tok, span) = it.next()?;
     match tok {
@@ -137,26 +342,25 @@ fn parse_6<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
                 Ok(expr)
             }
         }
-        Token::Integer(i) => Ok((Expression::Val(Value::Ratio((i as i64).into())), span)),
+        Token::LeftBracket => Ok((
+            Expression::List(parse_items(it, Token::RightBracket)?),
+            span,
+        )),
+        Token::Integer(i) => {
+            // Literals too large for an `i64` are big integers
+            let val = match i64::try_from(i) {
+                Ok(i) => Value::from(i),
+                Err(_) => Value::from(BigInt::from(i)),
+            };
+            Ok((Expression::Val(val), span))
+        }
         Token::Float(n) => Ok((Expression::Val(Value::Float(n)), span)),
         Token::Ident(id) => {
             if let Ok(con) = id.parse() {
                 Ok((Expression::Const(con), span))
             } else if let (Token::LeftParen, _) = it.peek()? {
                 it.next()?;
-                let mut args = vec![];
-                if it.peek()?.0 == Token::RightParen {
-                    it.next()?;
-                } else {
-                    loop {
-                        args.push(parse_1(it)?);
-                        match it.next()? {
-                            (Token::RightParen, _) => break,
-                            (Token::Comma, _) => (),
-                            (_, span) => return Err((Error::Syntax, span)),
-                        }
-                    }
-                }
+                let args = parse_items(it, Token::RightParen)?;
                 Ok((Expression::Call(id.to_string(), args), span))
             } else {
                 Ok((Expression::Var(id.to_string()), span))
@@ -166,6 +370,110 @@ fn parse_6<'a>(it: &mut impl TokenStream<'a>) -> Result<(Expression, Span)> {
     }
 }
 
+/// Resolve the variables that name units in an expression of units, such
+/// as the `km/h` in `x to km/h`, to quantities. Units take precedence over
+/// variables with the same names.
+fn resolve_units((expr, span): (Expression, Span)) -> (Expression, Span) {
+    use Expression::*;
+    let expr = match expr {
+        Var(name) => match parse_unit(&name) {
+            Some(q) => Val(Value::Quantity(q)),
+            None => Var(name),
+        },
+        Exp(args) => {
+            let [base, exp] = *args;
+            Exp(Box::new([resolve_units(base), exp]))
+        }
+        Mul(v) => Mul(v.into_iter().map(resolve_units).collect()),
+        Frac(args) => {
+            let [numer, denom] = *args;
+            Frac(Box::new([resolve_units(numer), resolve_units(denom)]))
+        }
+        expr => expr,
+    };
+    (expr, span)
+}
+
+/// Get the name of the unit that an expression resolves to, such as `km/h`,
+/// if it is a product or quotient of units and integer powers of units.
+pub(crate) fn unit_name(expr: &Expression) -> Option<String> {
+    use Expression::*;
+    match expr {
+        Val(Value::Quantity(q)) => match q.display_parts() {
+            (num, name) if num == 1.0 && q.unit.is_some() => Some(name),
+            _ => None,
+        },
+        Exp(args) => match &args[1].0 {
+            Val(Value::Ratio(n)) if n.is_integer() && *n.numer() > 0 => {
+                Some(format!("{}^{}", unit_name(&args[0].0)?, n.numer()))
+            }
+            _ => None,
+        },
+        Mul(v) => v
+            .iter()
+            .map(|(e, _)| unit_name(e))
+            .collect::<Option<Vec<_>>>()
+            .map(|v| v.join("*")),
+        Frac(args) => match &args[1].0 {
+            Mul(_) | Frac(_) => None,
+            denom => Some(format!("{}/{}", unit_name(&args[0].0)?, unit_name(denom)?)),
+        },
+        _ => None,
+    }
+}
+
+/// Test whether an expression ends with a unit, so that a unit may follow it
+/// after `*` or `/`, as in `9.8 m/s^2`.
+pub(crate) fn ends_with_unit(expr: &Expression) -> bool {
+    match expr {
+        Expression::Mul(v) => v.last().is_some_and(|(e, _)| ends_with_unit(e)),
+        Expression::Frac(args) => ends_with_unit(&args[1].0),
+        _ => unit_name(expr).is_some(),
+    }
+}
+
+/// Resolve the operand after `*` or `/` to a unit if the expression before
+/// it ends with a unit.
+fn unit_after(lhs: &(Expression, Span), rhs: (Expression, Span)) -> (Expression, Span) {
+    if !ends_with_unit(&lhs.0) {
+        return rhs;
+    }
+    let unit = resolve_units(rhs.clone());
+    if unit_name(&unit.0).is_some() {
+        unit
+    } else {
+        rhs
+    }
+}
+
+/// Test whether an expression is a numeric literal, possibly negated, which
+/// a unit may be written after.
+pub(crate) fn is_number_literal(expr: &Expression) -> bool {
+    match expr {
+        Expression::Val(Value::Ratio(r)) => r.is_integer(),
+        Expression::Val(Value::BigRatio(r)) => r.is_integer(),
+        Expression::Val(Value::Float(x)) => x.is_finite(),
+        Expression::Neg(e) => matches!(e.0, Expression::Val(_)) && is_number_literal(&e.0),
+        _ => false,
+    }
+}
+
+/// Parse a unit written after a numeric literal, such as the `km^2` in
+/// `3 km^2`, as a multiplication.
+fn parse_unit_suffix<'a>(
+    it: &mut impl TokenStream<'a>,
+    num: (Expression, Span),
+) -> Result<(Expression, Span)> {
+    match it.peek()? {
+        (Token::Ident(id), span) if parse_unit(id).is_some() => {
+            let span = span.clone();
+            let unit = resolve_units(parse_5(it)?);
+            Ok((Expression::Mul(vec![num, unit]), span))
+        }
+        _ => Ok(num),
+    }
+}
+
 /// Parse a stream of tokens into an abstract syntax tree.
 pub fn parse<'a>(mut it: impl TokenStream<'a>) -> Result<(Expression, Span)> {
     let expr = parse_0(&mut it)?;
@@ -1,8 +1,13 @@
 use std::iter::Peekable;
 
+use num::BigInt;
+
 use crate::Expression::*;
 use crate::Token::*;
-use crate::{parse, Constant, Error, Expression, Function, Result, Span, Token, TokenStream};
+use crate::{
+    parse, parse_unit, Constant, Error, Expression, Function, Result, Span, Token, TokenStream,
+    Value,
+};
 
 fn sp() -> Span {
     Span {
@@ -59,6 +64,13 @@ fn int() {
     assert_eq!(parse(tok(tokens)), Ok((64.into(), sp())));
 }
 
+#[test]
+fn big_int() {
+    let tokens = vec![9_223_372_036_854_775_808.into(), Eof];
+    let big = BigInt::from(9_223_372_036_854_775_807_i64) + 1;
+    assert_eq!(parse(tok(tokens)), Ok((Value::from(big).into(), sp())));
+}
+
 #[test]
 fn paren() {
     let tokens = vec![LeftParen, 2.5.into(), RightParen, Eof];
@@ -523,3 +535,449 @@ fn call() {
         )),
     );
 }
+
+#[test]
+fn convert() {
+    let tokens = vec![2.5.into(), Ident("to"), Ident("km"), Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Convert(Box::new([
+                (2.5.into(), sp()),
+                (Val(Value::Quantity(parse_unit("km").unwrap())), sp())
+            ])),
+            sp()
+        )),
+    );
+}
+
+#[test]
+fn unit_suffix() {
+    let tokens = vec![
+        3.into(),
+        Ident("m"),
+        Exponent,
+        2.into(),
+        Plus,
+        1.into(),
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Add(vec![
+                (
+                    Mul(vec![
+                        (3.into(), sp()),
+                        (
+                            Exp(Box::new([
+                                (Val(Value::Quantity(parse_unit("m").unwrap())), sp()),
+                                (2.into(), sp())
+                            ])),
+                            sp()
+                        ),
+                    ]),
+                    sp()
+                ),
+                (1.into(), sp()),
+            ]),
+            sp()
+        )),
+    );
+    // Not a unit
+    let tokens = vec![3.into(), Ident("x"), Eof];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn unit_named_like_function() {
+    // `min` is both a unit and a function
+    let tokens = vec![2.into(), Ident("to"), Ident("min"), Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Convert(Box::new([
+                (2.into(), sp()),
+                (Val(Value::Quantity(parse_unit("min").unwrap())), sp())
+            ])),
+            sp()
+        )),
+    );
+    let tokens = vec![Ident("min"), LeftParen, 2.into(), RightParen, Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((Func(Function::Min, Box::new((2.into(), sp()))), sp())),
+    );
+}
+
+#[test]
+fn list() {
+    let tokens = vec![
+        LeftBracket,
+        1.into(),
+        Token::Comma,
+        Ident("x"),
+        RightBracket,
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            List(vec![(1.into(), sp()), (Var("x".to_string()), sp())]),
+            sp()
+        )),
+    );
+    let tokens = vec![LeftBracket, RightBracket, Eof];
+    assert_eq!(parse(tok(tokens)), Ok((List(vec![]), sp())));
+    let tokens = vec![LeftBracket, 1.into(), Token::Comma, Eof];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+    let tokens = vec![LeftBracket, 1.into(), RightParen, Eof];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn index() {
+    // v[0][1]^2
+    let tokens = vec![
+        (Ident("v"), spa(1, 1)),
+        (LeftBracket, spa(2, 2)),
+        (0.into(), spa(3, 3)),
+        (RightBracket, spa(4, 4)),
+        (LeftBracket, spa(5, 5)),
+        (1.into(), spa(6, 6)),
+        (RightBracket, spa(7, 7)),
+        (Exponent, spa(8, 8)),
+        (2.into(), spa(9, 9)),
+        (Eof, spa(10, 10)),
+    ];
+    let inner = (
+        Index(Box::new([
+            (Var("v".to_string()), spa(1, 1)),
+            (0.into(), spa(3, 3)),
+        ])),
+        spa(2, 2),
+    );
+    assert_eq!(
+        parse(tok2(tokens)),
+        Ok((
+            Exp(Box::new([
+                (Index(Box::new([inner, (1.into(), spa(6, 6))])), spa(5, 5)),
+                (2.into(), spa(9, 9)),
+            ])),
+            spa(8, 8)
+        )),
+    );
+    let tokens = vec![Ident("v"), LeftBracket, 0.into(), Eof];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn range() {
+    // 1..n+1
+    let tokens = vec![1.into(), DotDot, Ident("n"), Plus, 1.into(), Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Range(Box::new([
+                (1.into(), sp()),
+                (
+                    Add(vec![(Var("n".to_string()), sp()), (1.into(), sp())]),
+                    sp()
+                ),
+            ])),
+            sp()
+        )),
+    );
+}
+
+#[test]
+fn lambda() {
+    // f = (x, y) -> x -> y
+    let tokens = vec![
+        Ident("f"),
+        Equals,
+        LeftParen,
+        Ident("x"),
+        Token::Comma,
+        Ident("y"),
+        RightParen,
+        Arrow,
+        Ident("x"),
+        Arrow,
+        Ident("y"),
+        Eof,
+    ];
+    let inner = (
+        Lambda(
+            vec!["x".to_string()],
+            Box::new((Var("y".to_string()), sp())),
+        ),
+        sp(),
+    );
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Let(
+                "f".to_string(),
+                Box::new((
+                    Lambda(vec!["x".to_string(), "y".to_string()], Box::new(inner)),
+                    sp()
+                )),
+            ),
+            sp()
+        )),
+    );
+    let tokens = vec![1.into(), Arrow, 2.into(), Eof];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn builtin_value() {
+    let tokens = vec![
+        Ident("map"),
+        LeftParen,
+        Ident("sin"),
+        Token::Comma,
+        Ident("v"),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Func(
+                Function::Map,
+                Box::new((
+                    Expression::Comma(vec![
+                        (Function::Sin.into(), sp()),
+                        (Var("v".to_string()), sp())
+                    ]),
+                    sp()
+                ))
+            ),
+            sp()
+        )),
+    );
+    let tokens = vec![Ident("sin"), Eof];
+    assert_eq!(parse(tok(tokens)), Ok((Function::Sin.into(), sp())));
+}
+
+#[test]
+fn series() {
+    // sum(k, 1, n, k^2)
+    let tokens = vec![
+        Ident("sum"),
+        LeftParen,
+        Ident("k"),
+        Token::Comma,
+        1.into(),
+        Token::Comma,
+        Ident("n"),
+        Token::Comma,
+        Ident("k"),
+        Exponent,
+        2.into(),
+        RightParen,
+        Eof,
+    ];
+    let var = |s: &str| (Var(s.to_string()), sp());
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Sum(
+                "k".to_string(),
+                Box::new([
+                    (1.into(), sp()),
+                    var("n"),
+                    (Exp(Box::new([var("k"), (2.into(), sp())])), sp()),
+                ])
+            ),
+            sp()
+        )),
+    );
+    // integrate(x, 0, 1, x)
+    let tokens = vec![
+        Ident("integrate"),
+        LeftParen,
+        Ident("x"),
+        Token::Comma,
+        0.into(),
+        Token::Comma,
+        1.into(),
+        Token::Comma,
+        Ident("x"),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Integral(
+                "x".to_string(),
+                Box::new([(0.into(), sp()), (1.into(), sp()), var("x")])
+            ),
+            sp()
+        )),
+    );
+    // The list function is unaffected
+    let tokens = vec![Ident("prod"), Ident("v"), Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((Func(Function::Prod, Box::new(var("v"))), sp())),
+    );
+    let tokens = vec![
+        Ident("prod"),
+        LeftParen,
+        2.into(),
+        Token::Comma,
+        1.into(),
+        Token::Comma,
+        2.into(),
+        Token::Comma,
+        3.into(),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn derivative() {
+    // diff(x^2, x)
+    let tokens = vec![
+        Ident("diff"),
+        LeftParen,
+        Ident("x"),
+        Exponent,
+        2.into(),
+        Token::Comma,
+        Ident("x"),
+        RightParen,
+        Eof,
+    ];
+    let var = |s: &str| (Var(s.to_string()), sp());
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Derivative(
+                "x".to_string(),
+                Box::new((Exp(Box::new([var("x"), (2.into(), sp())])), sp()))
+            ),
+            sp()
+        )),
+    );
+    // diff(f)
+    let tokens = vec![Ident("diff"), LeftParen, Ident("f"), RightParen, Eof];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((Func(Function::Diff, Box::new(var("f"))), sp())),
+    );
+}
+
+#[test]
+fn simplify() {
+    // simplify(x / x)
+    let tokens = vec![
+        Ident("simplify"),
+        LeftParen,
+        Ident("x"),
+        Divide,
+        Ident("x"),
+        RightParen,
+        Eof,
+    ];
+    let var = || (Var("x".to_string()), sp());
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Simplify(Box::new((Frac(Box::new([var(), var()])), sp()))),
+            sp()
+        )),
+    );
+    let tokens = vec![
+        Ident("simplify"),
+        LeftParen,
+        Ident("x"),
+        Token::Comma,
+        Ident("y"),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(parse(tok(tokens)), Err((Error::Syntax, sp())));
+}
+
+#[test]
+fn solve() {
+    let var = |s: &str| (Var(s.to_string()), sp());
+    // solve(x^2 = 2, x, 1)
+    let tokens = vec![
+        Ident("solve"),
+        LeftParen,
+        Ident("x"),
+        Exponent,
+        2.into(),
+        Equals,
+        2.into(),
+        Token::Comma,
+        Ident("x"),
+        Token::Comma,
+        1.into(),
+        RightParen,
+        Eof,
+    ];
+    let square = (Exp(Box::new([var("x"), (2.into(), sp())])), sp());
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Solve(
+                "x".to_string(),
+                Box::new((Equation(Box::new([square, (2.into(), sp())])), sp())),
+                vec![(1.into(), sp())],
+            ),
+            sp()
+        )),
+    );
+    // solve(x = 2, x)
+    let tokens = vec![
+        Ident("solve"),
+        LeftParen,
+        Ident("x"),
+        Equals,
+        2.into(),
+        Token::Comma,
+        Ident("x"),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Solve(
+                "x".to_string(),
+                Box::new((Equation(Box::new([var("x"), (2.into(), sp())])), sp())),
+                vec![],
+            ),
+            sp()
+        )),
+    );
+    // The linear system solver is unaffected
+    let tokens = vec![
+        Ident("solve"),
+        LeftParen,
+        Ident("a"),
+        Token::Comma,
+        Ident("b"),
+        RightParen,
+        Eof,
+    ];
+    assert_eq!(
+        parse(tok(tokens)),
+        Ok((
+            Func(
+                Function::Solve,
+                Box::new((Expression::Comma(vec![var("a"), var("b")]), sp()))
+            ),
+            sp()
+        )),
+    );
+}
@@ -0,0 +1,47 @@
+//! SI prefixes, such as kilo and micro.
+
+/// The SI prefixes with their symbols and powers of ten, in increasing order.
+pub const SI_PREFIXES: [(&str, i32); 20] = [
+    ("q", -30),
+    ("r", -27),
+    ("y", -24),
+    ("z", -21),
+    ("a", -18),
+    ("f", -15),
+    ("p", -12),
+    ("n", -9),
+    ("\u{b5}", -6),
+    ("m", -3),
+    ("k", 3),
+    ("M", 6),
+    ("G", 9),
+    ("T", 12),
+    ("P", 15),
+    ("E", 18),
+    ("Z", 21),
+    ("Y", 24),
+    ("R", 27),
+    ("Q", 30),
+];
+
+/// Get the power of ten of an SI prefix symbol.
+///
+/// The letter `u` is accepted as an ASCII substitute for the micro sign.
+pub fn si_prefix_exponent(symbol: &str) -> Option<i32> {
+    if symbol == "u" {
+        return Some(-6);
+    }
+    SI_PREFIXES
+        .iter()
+        .find(|(s, _)| *s == symbol)
+        .map(|&(_, e)| e)
+}
+
+/// Get the SI prefix symbol for a power of ten, or `None` if there is no
+/// such prefix.
+pub fn si_prefix_symbol(exp: i32) -> Option<&'static str> {
+    SI_PREFIXES
+        .iter()
+        .find(|&&(_, e)| e == exp)
+        .map(|&(s, _)| s)
+}
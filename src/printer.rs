@@ -0,0 +1,339 @@
+//! Print expressions as source code that parses back to the same tree.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::iter::once;
+
+use num::traits::{One, Signed};
+
+use crate::parser::{ends_with_unit, is_number_literal, unit_name};
+use crate::{Expression, Function, Span, Value};
+
+#[cfg(test)]
+mod tests;
+
+// The levels of the grammar in `parser.rs`, from the loosest binding. An
+// expression needs parentheses where the parser expects a higher level.
+pub(crate) const COMMA: u8 = 0;
+pub(crate) const LET: u8 = 1;
+pub(crate) const LAMBDA: u8 = 2;
+pub(crate) const RANGE: u8 = 3;
+pub(crate) const CONVERT: u8 = 4;
+pub(crate) const SUM: u8 = 5;
+pub(crate) const PRODUCT: u8 = 6;
+pub(crate) const PREFIX: u8 = 7;
+pub(crate) const POWER: u8 = 8;
+pub(crate) const INDEX: u8 = 9;
+pub(crate) const ATOM: u8 = 10;
+
+/// Get the level of the grammar that an expression is parsed at.
+pub(crate) fn precedence(expr: &Expression) -> u8 {
+    use Expression::*;
+    match expr {
+        Val(val) => value_precedence(val),
+        Comma(_) => COMMA,
+        Let(..) | Equation(_) => LET,
+        Lambda(..) => LAMBDA,
+        Range(_) => RANGE,
+        Convert(_) => CONVERT,
+        Add(_) | Sub(_) => SUM,
+        Mul(_) | Frac(_) => PRODUCT,
+        Neg(_) | Func(..) | Sum(..) | Product(..) | Integral(..) | Solve(..) | Derivative(..)
+        | Simplify(_) => PREFIX,
+        Exp(_) | Root(_) => POWER,
+        Index(_) => INDEX,
+        Const(_) | Var(_) | Call(..) | List(_) => ATOM,
+    }
+}
+
+/// Get the level of the grammar that a value is written at.
+fn value_precedence(val: &Value) -> u8 {
+    match val {
+        Value::Ratio(r) if !r.is_integer() => PRODUCT,
+        Value::Ratio(r) if r.is_negative() => PREFIX,
+        Value::BigRatio(r) if !r.is_integer() => PRODUCT,
+        Value::BigRatio(r) if r.is_negative() => PREFIX,
+        Value::Float(x) if x.is_sign_negative() && !x.is_nan() => PREFIX,
+        Value::Symbolic(_) | Value::Quantity(_) => PRODUCT,
+        Value::Factors(_) => value_precedence(&val.clone().expand()),
+        Value::Func(..) => LAMBDA,
+        // A bare function name is only parsed before a comma or a closing
+        // bracket
+        Value::Builtin(_) => LET,
+        _ => ATOM,
+    }
+}
+
+/// Write an expression, in parentheses if it is below the `level` of the
+/// grammar.
+fn write_at(f: &mut Formatter, expr: &Expression, level: u8) -> fmt::Result {
+    if precedence(expr) < level {
+        write!(f, "(")?;
+        write_expr(f, expr)?;
+        write!(f, ")")
+    } else {
+        write_expr(f, expr)
+    }
+}
+
+/// Write expressions separated by `sep`, each at the `level` of the grammar
+/// except the first, which is at `first`.
+fn write_list(
+    f: &mut Formatter,
+    items: &[(Expression, Span)],
+    sep: &str,
+    first: u8,
+    level: u8,
+) -> fmt::Result {
+    for (i, (item, _)) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", sep)?;
+        }
+        write_at(f, item, if i == 0 { first } else { level })?;
+    }
+    Ok(())
+}
+
+/// Test whether an expression is a literal with a unit, such as `2 m`.
+fn is_quantity(expr: &Expression) -> bool {
+    match expr {
+        Expression::Mul(v) => {
+            v.len() == 2 && is_number_literal(&v[0].0) && unit_name(&v[1].0).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Get the name of a unit that follows an expression in a product or
+/// quotient, where the parser reads it as a unit rather than a variable.
+fn unit_after(prev: &Expression, unit: &Expression) -> Option<String> {
+    match unit {
+        Expression::Mul(_) | Expression::Frac(_) => None,
+        _ if ends_with_unit(prev) => unit_name(unit),
+        _ => None,
+    }
+}
+
+/// Write a call to a function, such as `f(x, 2)`, whose arguments are at the
+/// level of an assignment.
+fn write_call<'a>(
+    f: &mut Formatter,
+    name: impl Display,
+    args: impl IntoIterator<Item = &'a Expression>,
+) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write_at(f, arg, LET)?;
+    }
+    write!(f, ")")
+}
+
+/// Write a function of the named parameters, such as `(x, y) -> x * y`.
+pub(crate) fn write_lambda(f: &mut Formatter, params: &[String], body: &Expression) -> fmt::Result {
+    if params.len() == 1 {
+        write!(f, "{} -> ", params[0])?;
+    } else {
+        write!(f, "({}) -> ", params.join(", "))?;
+    }
+    // Right associative
+    write_at(f, body, LAMBDA)
+}
+
+/// Write an expression without enclosing parentheses.
+fn write_expr(f: &mut Formatter, expr: &Expression) -> fmt::Result {
+    use Expression::*;
+    match expr {
+        Val(val) => write_value(f, val),
+        Neg(e) => {
+            write!(f, "-")?;
+            write_at(f, &e.0, PREFIX)
+        }
+        // Left associative
+        Add(v) => write_list(f, v, " + ", SUM, PRODUCT),
+        Mul(v) => {
+            write_at(f, &v[0].0, PRODUCT)?;
+            for (i, pair) in v.windows(2).enumerate() {
+                let (prev, factor) = (&pair[0].0, &pair[1].0);
+                // A unit after a quotient, as in `1 / 2 m`, is only parsed at
+                // the start of a product
+                let literal = match prev {
+                    Frac(args) => {
+                        i == 0 && is_number_literal(&args[0].0) && is_number_literal(&args[1].0)
+                    }
+                    _ => is_number_literal(prev),
+                };
+                let after_literal = match factor {
+                    Mul(_) | Frac(_) => None,
+                    _ if literal => unit_name(factor),
+                    _ => None,
+                };
+                match (after_literal, unit_after(prev, factor)) {
+                    (Some(unit), _) => write!(f, " {}", unit)?,
+                    (_, Some(unit)) => write!(f, " * {}", unit)?,
+                    // A quantity, such as `2 m`, is parsed as a single factor
+                    _ if is_quantity(factor) => {
+                        write!(f, " * ")?;
+                        write_expr(f, factor)?;
+                    }
+                    _ => {
+                        write!(f, " * ")?;
+                        write_at(f, factor, PREFIX)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Sub(args) => write_list(f, &args[..], " - ", SUM, PRODUCT),
+        Frac(args) => match unit_after(&args[0].0, &args[1].0) {
+            Some(unit) => {
+                write_at(f, &args[0].0, PRODUCT)?;
+                write!(f, " / {}", unit)
+            }
+            None => write_list(f, &args[..], " / ", PRODUCT, PREFIX),
+        },
+        Convert(args) => match unit_name(&args[1].0) {
+            Some(unit) => {
+                write_at(f, &args[0].0, CONVERT)?;
+                write!(f, " to {}", unit)
+            }
+            None => write_list(f, &args[..], " to ", CONVERT, SUM),
+        },
+        Index(args) => {
+            write_at(f, &args[0].0, INDEX)?;
+            write!(f, "[")?;
+            write_at(f, &args[1].0, LET)?;
+            write!(f, "]")
+        }
+        // Right associative
+        Exp(args) => write_list(f, &args[..], "^", INDEX, POWER),
+        // There is no syntax for radicals, so write a fractional power
+        Root(args) => {
+            write_at(f, &args[0].0, INDEX)?;
+            write!(f, "^(1 / ")?;
+            write_at(f, &args[1].0, PREFIX)?;
+            write!(f, ")")
+        }
+        Range(args) => write_list(f, &args[..], "..", CONVERT, CONVERT),
+        Equation(args) => write_list(f, &args[..], " = ", LAMBDA, LAMBDA),
+        Const(con) => write!(f, "{}", con),
+        Var(var) => write!(f, "{}", var),
+        Func(func, e) => {
+            write!(f, "{}(", func)?;
+            write_at(f, &e.0, COMMA)?;
+            write!(f, ")")
+        }
+        Let(var, e) => {
+            write!(f, "{} = ", var)?;
+            write_at(f, &e.0, LAMBDA)
+        }
+        Call(name, args) => write_call(f, name, args.iter().map(|(e, _)| e)),
+        Lambda(params, body) => write_lambda(f, params, &body.0),
+        List(v) => {
+            write!(f, "[")?;
+            write_list(f, v, ", ", LET, LET)?;
+            write!(f, "]")
+        }
+        Sum(var, args) | Product(var, args) | Integral(var, args) => {
+            let func = match expr {
+                Sum(..) => Function::Sum,
+                Product(..) => Function::Prod,
+                _ => Function::Integrate,
+            };
+            let var = Var(var.clone());
+            write_call(f, func, once(&var).chain(args.iter().map(|(e, _)| e)))
+        }
+        Solve(var, eq, guesses) => {
+            let var = Var(var.clone());
+            let args = once(&eq.0).chain(once(&var));
+            write_call(
+                f,
+                Function::Solve,
+                args.chain(guesses.iter().map(|(e, _)| e)),
+            )
+        }
+        Derivative(var, e) => write_call(f, Function::Diff, [&e.0, &Var(var.clone())]),
+        Simplify(e) => write_call(f, Function::Simplify, once(&e.0)),
+        Comma(v) => write_list(f, v, ", ", LET, LET),
+    }
+}
+
+/// Write a float so that it reads back as the same float.
+fn write_float(f: &mut Formatter, x: f64) -> fmt::Result {
+    if x.is_nan() {
+        write!(f, "nan")
+    } else if x.is_infinite() {
+        write!(f, "{}inf", if x < 0.0 { "-" } else { "" })
+    } else {
+        // Unlike `Display`, `Debug` uses an exponent for large and small
+        // floats, and always includes a decimal point or an exponent
+        write!(f, "{:?}", x)
+    }
+}
+
+/// Write a value as a literal or an expression that evaluates to it.
+fn write_value(f: &mut Formatter, val: &Value) -> fmt::Result {
+    match val {
+        Value::Ratio(r) => {
+            write!(f, "{}", r.numer())?;
+            if !r.is_integer() {
+                write!(f, " / {}", r.denom())?;
+            }
+            Ok(())
+        }
+        Value::BigRatio(r) => {
+            write!(f, "{}", r.numer())?;
+            if !r.is_integer() {
+                write!(f, " / {}", r.denom())?;
+            }
+            Ok(())
+        }
+        Value::Float(x) => write_float(f, *x),
+        Value::Symbolic(sym) => {
+            if !sym.coef.is_one() {
+                write_value(f, &Value::Ratio(sym.coef))?;
+                write!(f, " * ")?;
+            }
+            write!(f, "{}", sym.base)?;
+            if !sym.power.is_one() {
+                write!(f, "^")?;
+                write_at(f, &Expression::Val(Value::Ratio(sym.power)), POWER)?;
+            }
+            Ok(())
+        }
+        Value::Quantity(q) => {
+            let (num, unit) = q.display_parts();
+            write_float(f, num)?;
+            write!(f, " {}", unit)
+        }
+        Value::Factors(_) => write_value(f, &val.clone().expand()),
+        Value::List(items) => {
+            write!(f, "[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(f, item)?;
+            }
+            write!(f, "]")
+        }
+        Value::Matrix(m) => {
+            let rows: Vec<_> = (0..m.rows())
+                .map(|i| Value::List(m.row(i).to_vec()))
+                .collect();
+            write_value(f, &Value::List(rows))
+        }
+        Value::Func(params, body) => write_lambda(f, params, &body.0),
+        Value::Builtin(func) => write!(f, "{}", func),
+    }
+}
+
+impl Display for Expression {
+    /// Write the expression as source code with as few parentheses as the
+    /// grammar allows.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_expr(f, self)
+    }
+}
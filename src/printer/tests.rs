@@ -0,0 +1,232 @@
+use std::mem::discriminant;
+
+use crate::Expression::*;
+use crate::{lex, parse, Constant, Context, Expression, Function, Span, Value};
+
+fn print(s: &str) -> String {
+    parse(lex(s, None)).unwrap().0.to_string()
+}
+
+/// Test whether two expressions are the same tree, ignoring the spans.
+fn same(a: &Expression, b: &Expression) -> bool {
+    fn label(e: &Expression) -> String {
+        match e {
+            Val(v) => format!("{:?}", v),
+            Const(con) => con.to_string(),
+            Func(func, _) => func.to_string(),
+            Var(s)
+            | Let(s, _)
+            | Call(s, _)
+            | Sum(s, _)
+            | Product(s, _)
+            | Integral(s, _)
+            | Solve(s, ..)
+            | Derivative(s, _) => s.clone(),
+            Lambda(params, _) => params.join(", "),
+            _ => String::new(),
+        }
+    }
+    let (xs, ys) = (a.children(), b.children());
+    discriminant(a) == discriminant(b)
+        && label(a) == label(b)
+        && xs.len() == ys.len()
+        && xs.iter().zip(&ys).all(|(x, y)| same(&x.0, &y.0))
+}
+
+#[test]
+fn minimal_parentheses() {
+    for s in [
+        "1 + 2 * 3",
+        "(1 + 2) * 3",
+        "a - (b - c)",
+        "a - b - c",
+        "a / (b * c)",
+        "a + (b + c)",
+        "2^3^4",
+        "(2^3)^4",
+        "-x^2",
+        "(-x)^2",
+        "2^(-1)",
+        "--x",
+        "(sin(x))^2",
+        "sin(x^2)",
+        "2^(sin(x))",
+        "-sin(x) * cos(x)",
+        "besselj(0, x)",
+        "v[0][1]",
+        "(x^2)[0]",
+        "x^v[0]",
+        "f(x, y)[0]",
+        "f = x -> x^2",
+        "x -> y -> x + y",
+        "(x -> x) + 1",
+        "(x, y) -> x * y",
+        "1..n + 1",
+        "(1..2)..3",
+        "3 * km to m to ft",
+        "3 km to m to ft",
+        "3 km^2 + 1",
+        "9.8 m / s^2 * x",
+        "10 kg * 9.8 m / s^2 to N",
+        "x to km/h",
+        "x * 2 m",
+        "1 / 2 m",
+        "-2 m * x",
+        "x = 1, y = 2",
+        "[1, (2, 3)]",
+        "map(sin, [0.5, 1e-7])",
+        "(sin) + 1",
+        "sum(k, 1, 10, k^2)",
+        "prod(k, 1, n, k)",
+        "integrate(x, 0, 1, x^2)",
+        "solve(x^2 = 2, x, 1)",
+        "solve(x^2 - 1 = 0, x, 0, 3)",
+        "diff(x^2, x)",
+        "simplify(x + x)",
+        "1 + 1 = 2",
+        "(y = 2) + 1",
+        "phys.c * 2",
+    ] {
+        assert_eq!(print(s), s);
+    }
+    assert_eq!(print("((x))"), "x");
+    assert_eq!(print("(a * b) * c"), "a * b * c");
+    assert_eq!(print("2^(x)"), "2^x");
+    assert_eq!(print("x / 2 m"), "x / (2 m)");
+}
+
+#[test]
+fn values() {
+    let span = Span {
+        file: None,
+        line: 1,
+        start: 1,
+        end: 1,
+    };
+    let exp = |a: Expression, b: Expression| Exp(Box::new([(a, span.clone()), (b, span.clone())]));
+    assert_eq!(exp((-3).into(), 2.into()).to_string(), "(-3)^2");
+    assert_eq!(exp(2.into(), (1, 2).into()).to_string(), "2^(1 / 2)");
+    assert_eq!(exp((1, 2).into(), 2.into()).to_string(), "(1 / 2)^2");
+    assert_eq!(exp(2.5.into(), (-1.0).into()).to_string(), "2.5^(-1.0)");
+    assert_eq!(
+        exp(1e300.into(), f64::INFINITY.into()).to_string(),
+        "1e300^inf"
+    );
+    let root = Root(Box::new([
+        (Var("x".to_string()), span.clone()),
+        (3.into(), span),
+    ]));
+    assert_eq!(root.to_string(), "x^(1 / 3)");
+    assert_eq!(Expression::from(Function::Sin).to_string(), "sin");
+    assert_eq!(Expression::from(Constant::Pi).to_string(), "pi");
+    let list = Value::List(vec![1.into(), (-2, 3).into()]);
+    assert_eq!(Val(list).to_string(), "[1, -2 / 3]");
+}
+
+#[test]
+fn functions() {
+    let show = |s: &str| {
+        let mut ctx = Context::default();
+        let val = crate::eval(&parse(lex(s, None)).unwrap(), &mut ctx).unwrap();
+        let s = ctx.display(&val).to_string();
+        s
+    };
+    assert_eq!(show("x -> x^2"), "x -> x^2");
+    assert_eq!(show("(x, y) -> (x + y) / 2"), "(x, y) -> (x + y) / 2");
+    assert_eq!(show("[x -> 2 * x, sin]"), "[x -> 2 * x, sin]");
+    assert_eq!(show("diff(x^3, x)"), "x -> 3 * x^2");
+}
+
+/// A linear congruential generator, for reproducible random trees.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) % n
+    }
+}
+
+/// Generate a random expression of the shape that the parser produces.
+fn generate(rng: &mut Random, depth: u32) -> (Expression, Span) {
+    let span = Span {
+        file: None,
+        line: 1,
+        start: 1,
+        end: 1,
+    };
+    let sub = |rng: &mut Random| generate(rng, depth - 1);
+    let leaf = depth == 0 || rng.below(4) == 0;
+    let expr = match if leaf {
+        rng.below(5)
+    } else {
+        5 + rng.below(19)
+    } {
+        0 => Val((rng.below(20) as i64).into()),
+        1 => Val([0.5, 2.25, 1e-9, 3e20][rng.below(4) as usize].into()),
+        2 => Var(["x", "y", "f"][rng.below(3) as usize].to_string()),
+        3 => Const(Constant::Pi),
+        4 => Val(Value::Builtin(Function::Cos)),
+        5 => Neg(Box::new(sub(rng))),
+        // The parser flattens a sum or product on the left into a longer one
+        6 | 7 => {
+            let mut v = vec![sub(rng), sub(rng)];
+            if rng.below(2) == 0 {
+                v.push(sub(rng));
+            }
+            while let Add(_) | Mul(_) = v[0].0 {
+                v[0] = sub(rng);
+            }
+            if rng.below(2) == 0 {
+                Add(v)
+            } else {
+                Mul(v)
+            }
+        }
+        8 => Sub(Box::new([sub(rng), sub(rng)])),
+        9 => Frac(Box::new([sub(rng), sub(rng)])),
+        10 | 11 => Exp(Box::new([sub(rng), sub(rng)])),
+        12 => Func(Function::Sin, Box::new(sub(rng))),
+        13 => Func(Function::Abs, Box::new(sub(rng))),
+        14 => Index(Box::new([sub(rng), sub(rng)])),
+        15 => Call("g".to_string(), vec![sub(rng), sub(rng)]),
+        16 => List(vec![sub(rng), sub(rng)]),
+        17 => Lambda(vec!["x".to_string()], Box::new(sub(rng))),
+        18 => Let("y".to_string(), Box::new(sub(rng))),
+        19 => Range(Box::new([sub(rng), sub(rng)])),
+        20 => Convert(Box::new([sub(rng), sub(rng)])),
+        // As it does a comma, even as the first argument of `diff`
+        21 => {
+            let mut v = vec![sub(rng), sub(rng)];
+            while let Comma(_) = v[0].0 {
+                v[0] = sub(rng);
+            }
+            Comma(v)
+        }
+        22 => Sum("k".to_string(), Box::new([sub(rng), sub(rng), sub(rng)])),
+        _ => {
+            let mut body = sub(rng);
+            while let Comma(_) = body.0 {
+                body = sub(rng);
+            }
+            Derivative("x".to_string(), Box::new(body))
+        }
+    };
+    (expr, span)
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = Random(2024);
+    for _ in 0..2000 {
+        let (expr, _) = generate(&mut rng, 5);
+        let printed = expr.to_string();
+        match parse(lex(&printed, None)) {
+            Ok((parsed, _)) => assert!(same(&parsed, &expr), "{} parses differently", printed),
+            Err(err) => panic!("{} doesn't parse: {:?}", printed, err.0),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::f64::consts;
+
+use crate::{integrate, Integral};
+
+fn value(f: impl Fn(f64) -> f64, a: f64, b: f64) -> Option<f64> {
+    let integral: Option<Integral> = integrate(|x| Ok::<_, ()>(f(x)), a, b).unwrap();
+    integral.map(|i| i.value)
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!(
+        (actual - expected).abs() <= 1e-10 * expected.abs().max(1.0),
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn finite() {
+    assert_close(value(|x| x * x, 0.0, 3.0), 9.0);
+    assert_close(value(f64::sin, 0.0, consts::PI), 2.0);
+    assert_close(value(f64::exp, 0.0, 1.0), consts::E - 1.0);
+    assert_close(value(|x| 1.0 / x, 1.0, 2.0), consts::LN_2);
+    assert_close(value(f64::cos, -50.0, 50.0), 2.0 * 50f64.sin());
+    assert_eq!(value(f64::exp, 2.0, 2.0), Some(0.0));
+}
+
+#[test]
+fn reversed() {
+    assert_close(value(|x| x, 2.0, 0.0), -2.0);
+}
+
+#[test]
+fn singular_endpoint() {
+    assert_close(value(|x| 1.0 / x.sqrt(), 0.0, 1.0), 2.0);
+    assert_close(value(f64::ln, 0.0, 1.0), -1.0);
+}
+
+#[test]
+fn infinite() {
+    let inf = f64::INFINITY;
+    assert_close(value(|x| (-x).exp(), 0.0, inf), 1.0);
+    assert_close(value(|x| 1.0 / (x * x), 1.0, inf), 1.0);
+    assert_close(value(f64::exp, -inf, 0.0), 1.0);
+    assert_close(value(|x| (-x * x).exp(), -inf, inf), consts::PI.sqrt());
+    assert_close(value(|x| 1.0 / (1.0 + x * x), inf, -inf), -consts::PI);
+}
+
+#[test]
+fn divergent() {
+    assert_eq!(value(|x| 1.0 / x, 0.0, 1.0), None);
+    assert_eq!(value(|x| 1.0 / x, 1.0, f64::INFINITY), None);
+    assert_eq!(value(|x| x, f64::NAN, 1.0), None);
+}
+
+#[test]
+fn error_estimate() {
+    let integral = integrate(|x| Ok::<_, ()>(x.exp()), 0.0, 1.0).unwrap();
+    let integral = integral.unwrap();
+    assert!(integral.error <= 1e-10);
+    assert!((integral.value - (consts::E - 1.0)).abs() <= integral.error.max(1e-15));
+}
+
+#[test]
+fn function_error() {
+    let result = integrate(|x| if x > 0.5 { Err("oops") } else { Ok(x) }, 0.0, 1.0);
+    assert_eq!(result, Err("oops"));
+}
@@ -0,0 +1,168 @@
+//! Numerical root finding.
+
+#[cfg(test)]
+mod tests;
+
+/// The largest number of iterations of a method.
+const MAX_ITERATIONS: usize = 200;
+
+/// The largest magnitude of the points tried when searching for a sign
+/// change, as a power of two.
+const MAX_SEARCH_EXP: i32 = 40;
+
+/// The relative step size of the central difference approximation to the
+/// derivative, which is about the cube root of the machine epsilon.
+const DIFF_STEP: f64 = 6e-6;
+
+/// Test whether two non-zero numbers have opposite signs.
+fn changes_sign(a: f64, b: f64) -> bool {
+    a < 0.0 && b > 0.0 || a > 0.0 && b < 0.0
+}
+
+/// Find a root of `f` between `a` and `b` by Brent's method, which combines
+/// bisection with inverse quadratic interpolation.
+///
+/// Returns `Ok(None)` if `f(a)` and `f(b)` have the same sign, or an error
+/// that `f` returns.
+fn brent<E>(
+    f: &mut impl FnMut(f64) -> Result<f64, E>,
+    mut a: f64,
+    mut b: f64,
+) -> Result<Option<f64>, E> {
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    if fa == 0.0 {
+        return Ok(Some(a));
+    }
+    if fb == 0.0 {
+        return Ok(Some(b));
+    }
+    if !changes_sign(fa, fb) {
+        return Ok(None);
+    }
+    // The root is always between `b` and `c`, and `b` is the best estimate
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for _ in 0..MAX_ITERATIONS {
+        if (fb > 0.0) == (fc > 0.0) {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tol = 2.0 * f64::EPSILON * b.abs() + f64::MIN_POSITIVE;
+        let m = 0.5 * (c - b);
+        if m.abs() <= tol || fb == 0.0 {
+            return Ok(Some(b));
+        }
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // Interpolate, falling back on bisection if the step is too large
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * m * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * m * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            if 2.0 * p < (3.0 * m * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = m;
+                e = m;
+            }
+        } else {
+            d = m;
+            e = m;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol { d } else { tol.copysign(m) };
+        fb = f(b)?;
+        if fb.is_nan() {
+            return Ok(None);
+        }
+    }
+    Ok(Some(b))
+}
+
+/// Find a root of `f` by Newton's method from `x`, approximating the
+/// derivative by central differences.
+///
+/// Returns `Ok(None)` if the method does not converge, or an error that `f`
+/// returns.
+fn newton<E>(f: &mut impl FnMut(f64) -> Result<f64, E>, mut x: f64) -> Result<Option<f64>, E> {
+    for _ in 0..MAX_ITERATIONS {
+        let fx = f(x)?;
+        if fx == 0.0 {
+            return Ok(Some(x));
+        }
+        let h = DIFF_STEP * x.abs().max(1.0);
+        let slope = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        let step = fx / slope;
+        if !step.is_finite() {
+            return Ok(None);
+        }
+        x -= step;
+        if step.abs() <= 4.0 * f64::EPSILON * x.abs().max(1.0) {
+            return Ok(Some(x));
+        }
+    }
+    Ok(None)
+}
+
+/// Find a root of `f`, which is a number `x` at which `f(x) = 0`.
+///
+/// With two `guesses`, Brent's method is used on the interval between them,
+/// over which `f` must change sign. With one, Newton's method is used from
+/// it. With none, increasingly large intervals on either side of zero are
+/// searched for a sign change, and otherwise Newton's method is used from
+/// zero.
+///
+/// Returns `Ok(None)` if no root is found, or an error that `f` returns.
+/// Panics if there are more than two guesses.
+pub fn find_root<E>(
+    mut f: impl FnMut(f64) -> Result<f64, E>,
+    guesses: &[f64],
+) -> Result<Option<f64>, E> {
+    match *guesses {
+        [a, b] => brent(&mut f, a, b),
+        [x] => newton(&mut f, x),
+        [] => {
+            let f0 = f(0.0)?;
+            if f0 == 0.0 {
+                return Ok(Some(0.0));
+            }
+            // The last points tried on the positive and negative sides
+            let (mut pos, mut neg) = ((0.0, f0), (0.0, f0));
+            for exp in 0..=MAX_SEARCH_EXP {
+                let x = 2f64.powi(exp);
+                for (last, x) in [(&mut pos, x), (&mut neg, -x)] {
+                    let fx = f(x)?;
+                    if fx == 0.0 || changes_sign(last.1, fx) {
+                        return brent(&mut f, last.0, x);
+                    }
+                    *last = (x, fx);
+                }
+            }
+            newton(&mut f, 0.0)
+        }
+        _ => panic!("too many guesses"),
+    }
+}
@@ -0,0 +1,58 @@
+use std::f64::consts;
+
+use crate::find_root;
+
+fn root(f: impl Fn(f64) -> f64, guesses: &[f64]) -> Option<f64> {
+    find_root(|x| Ok::<_, ()>(f(x)), guesses).unwrap()
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!(
+        (actual - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn bracketed() {
+    assert_close(root(|x| x * x - 2.0, &[0.0, 2.0]), consts::SQRT_2);
+    assert_close(root(f64::cos, &[3.0, 0.0]), consts::FRAC_PI_2);
+    assert_close(
+        root(|x| x.powi(3) - x - 1.0, &[1.0, 2.0]),
+        1.324717957244746,
+    );
+    assert_eq!(root(|x| x - 1.0, &[1.0, 5.0]), Some(1.0));
+    // A discontinuity, rather than a root, is found
+    assert_close(root(|x| 1.0 / x, &[-1.0, 2.0]), 0.0);
+    assert_eq!(root(|x| x * x + 1.0, &[-1.0, 1.0]), None);
+}
+
+#[test]
+fn newton() {
+    assert_close(root(|x| x * x - 2.0, &[1.0]), consts::SQRT_2);
+    assert_close(root(|x| x * x - 2.0, &[-1.0]), -consts::SQRT_2);
+    assert_close(root(|x| x.exp() - 10.0, &[0.0]), consts::LN_10);
+    let double = root(|x| (x - 3.0).powi(2), &[1.0]).unwrap();
+    assert!((double - 3.0).abs() < 1e-6);
+    assert_eq!(root(|x| x * x + 1.0, &[1.0]), None);
+    assert_eq!(root(|_| 1.0, &[1.0]), None);
+}
+
+#[test]
+fn search() {
+    assert_close(root(|x| x * x - 2.0, &[]), consts::SQRT_2);
+    assert_close(root(|x| x + 1000.0, &[]), -1000.0);
+    assert_close(root(|x| x.ln() - 1.0, &[]), consts::E);
+    assert_eq!(root(f64::sin, &[]), Some(0.0));
+    assert_eq!(root(|x| x * x + 1.0, &[]), None);
+}
+
+#[test]
+fn function_error() {
+    let f = |x: f64| if x > 1.0 { Err("oops") } else { Ok(x) };
+    assert_eq!(find_root(f, &[0.5]), Ok(Some(0.0)));
+    assert_eq!(find_root(f, &[2.0]), Err("oops"));
+}
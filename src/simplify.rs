@@ -0,0 +1,563 @@
+//! Algebraic simplification of expressions.
+//!
+//! An expression is first brought into a normal form, which uses only sums,
+//! products and powers, with the rational constants of each sum and product
+//! combined. It is then tidied up into the differences, fractions and
+//! negations that a person would write.
+
+#[cfg(test)]
+mod tests;
+
+use std::cmp::Ordering;
+
+use if_chain::if_chain;
+use num::traits::{One, Signed, Zero};
+
+use crate::interpreter::{binary, do_exp, Op};
+use crate::{Context, Expression, Span, Value};
+
+type ExprSpan = (Expression, Span);
+
+/// Get the value of an expression if it is a number.
+fn number(expr: &Expression) -> Option<&Value> {
+    match expr {
+        Expression::Val(v @ (Value::Ratio(_) | Value::BigRatio(_) | Value::Float(_))) => Some(v),
+        _ => None,
+    }
+}
+
+/// Test whether a number is zero.
+fn is_zero(x: &Value) -> bool {
+    match x {
+        Value::Ratio(r) => r.is_zero(),
+        Value::BigRatio(r) => r.is_zero(),
+        Value::Float(f) => *f == 0.0,
+        _ => false,
+    }
+}
+
+/// Test whether a number is one.
+fn is_one(x: &Value) -> bool {
+    match x {
+        Value::Ratio(r) => r.is_one(),
+        Value::BigRatio(r) => r.is_one(),
+        Value::Float(f) => *f == 1.0,
+        _ => false,
+    }
+}
+
+/// Test whether a number is negative.
+fn is_negative(x: &Value) -> bool {
+    match x {
+        Value::Ratio(r) => r.is_negative(),
+        Value::BigRatio(r) => r.is_negative(),
+        Value::Float(f) => *f < 0.0,
+        _ => false,
+    }
+}
+
+/// Get the absolute value of a number.
+fn abs(x: &Value) -> Value {
+    match x {
+        Value::Ratio(r) => r.abs().into(),
+        Value::BigRatio(r) => Value::BigRatio(r.abs()),
+        Value::Float(f) => f.abs().into(),
+        x => x.clone(),
+    }
+}
+
+/// Test whether a number is an integer that is exactly representable.
+fn is_integer(x: &Value) -> bool {
+    match x {
+        Value::Ratio(r) => r.is_integer(),
+        _ => false,
+    }
+}
+
+/// Make a constant expression.
+fn val(x: impl Into<Value>, span: &Span) -> ExprSpan {
+    (Expression::Val(x.into()), span.clone())
+}
+
+/// Combine two numbers with an operator, if the result is a number.
+fn arith(op: Op, a: &Value, b: &Value, span: &Span) -> Option<Value> {
+    binary(op, a, b, span).ok()
+}
+
+/// Get a string that identifies an expression regardless of its spans, for
+/// collecting like terms and ordering them.
+fn key(expr: &Expression) -> String {
+    use Expression::*;
+    let join =
+        |v: &[ExprSpan], sep: &str| v.iter().map(|e| key(&e.0)).collect::<Vec<_>>().join(sep);
+    match expr {
+        Val(v) => format!("{:?}", v),
+        Const(c) => c.to_string(),
+        Var(var) => var.clone(),
+        Neg(e) => format!("-({})", key(&e.0)),
+        Add(v) => format!("+({})", join(v, ",")),
+        Mul(v) => format!("*({})", join(v, ",")),
+        Sub(args) => format!("-({})", join(&args[..], ",")),
+        Frac(args) => format!("/({})", join(&args[..], ",")),
+        Exp(args) => format!("^({})", join(&args[..], ",")),
+        Root(args) => format!("root({})", join(&args[..], ",")),
+        Func(f, e) => format!("{}({})", f, key(&e.0)),
+        Call(f, v) => format!("{}({})", f, join(v, ",")),
+        List(v) => format!("[{}]", join(v, ",")),
+        Comma(v) => join(v, ","),
+        _ => format!("{:?}", expr),
+    }
+}
+
+/// Apply `f` to each immediate subexpression of an expression.
+fn map_children(expr: &ExprSpan, f: &impl Fn(&ExprSpan) -> ExprSpan) -> ExprSpan {
+    use Expression::*;
+    let (e, span) = expr;
+    let all = |v: &[ExprSpan]| v.iter().map(f).collect();
+    let pair = |[a, b]: &[ExprSpan; 2]| Box::new([f(a), f(b)]);
+    let triple = |[a, b, c]: &[ExprSpan; 3]| Box::new([f(a), f(b), f(c)]);
+    let e = match e {
+        Val(_) | Const(_) | Var(_) => e.clone(),
+        Neg(e) => Neg(Box::new(f(e))),
+        Add(v) => Add(all(v)),
+        Sub(args) => Sub(pair(args)),
+        Mul(v) => Mul(all(v)),
+        Frac(args) => Frac(pair(args)),
+        Exp(args) => Exp(pair(args)),
+        Root(args) => Root(pair(args)),
+        Func(func, e) => Func(*func, Box::new(f(e))),
+        Let(var, e) => Let(var.clone(), Box::new(f(e))),
+        Call(func, v) => Call(func.clone(), all(v)),
+        Lambda(params, body) => Lambda(params.clone(), Box::new(f(body))),
+        Convert(args) => Convert(pair(args)),
+        List(v) => List(all(v)),
+        Index(args) => Index(pair(args)),
+        Range(args) => Range(pair(args)),
+        Sum(var, args) => Sum(var.clone(), triple(args)),
+        Product(var, args) => Product(var.clone(), triple(args)),
+        Integral(var, args) => Integral(var.clone(), triple(args)),
+        Equation(args) => Equation(pair(args)),
+        Solve(var, eq, guesses) => Solve(var.clone(), Box::new(f(eq)), all(guesses)),
+        Derivative(var, e) => Derivative(var.clone(), Box::new(f(e))),
+        Simplify(e) => Simplify(Box::new(f(e))),
+        Comma(v) => Comma(all(v)),
+    };
+    (e, span.clone())
+}
+
+/// Split a normal form into its base and exponent.
+fn split_power(expr: &ExprSpan) -> (ExprSpan, ExprSpan) {
+    match &expr.0 {
+        Expression::Exp(args) => (args[0].clone(), args[1].clone()),
+        _ => (expr.clone(), val(1, &expr.1)),
+    }
+}
+
+/// Split a normal form into its numeric coefficient and the rest, which is
+/// `None` if the expression is a number.
+fn split_coef(expr: ExprSpan) -> (Value, Option<ExprSpan>) {
+    if let Some(x) = number(&expr.0) {
+        return (x.clone(), None);
+    }
+    match expr {
+        (Expression::Mul(mut v), span) if number(&v[0].0).is_some() => {
+            let (coef, _) = v.remove(0);
+            let coef = number(&coef).unwrap().clone();
+            let rest = if v.len() == 1 {
+                v.pop().unwrap()
+            } else {
+                (Expression::Mul(v), span)
+            };
+            (coef, Some(rest))
+        }
+        expr => (1.into(), Some(expr)),
+    }
+}
+
+/// Rank the kinds of factors in a product, for ordering them.
+fn factor_rank(expr: &Expression) -> u8 {
+    match expr {
+        Expression::Const(_) => 0,
+        Expression::Var(_) => 1,
+        Expression::Func(..) | Expression::Call(..) => 3,
+        _ => 2,
+    }
+}
+
+/// Order the factors of a product by the kinds and then the names of their
+/// bases, so that constants come before variables.
+fn cmp_factors(a: &ExprSpan, b: &ExprSpan) -> Ordering {
+    let (a, _) = split_power(a);
+    let (b, _) = split_power(b);
+    factor_rank(&a.0)
+        .cmp(&factor_rank(&b.0))
+        .then_with(|| key(&a.0).cmp(&key(&b.0)))
+}
+
+/// Get the variables of a term and their numeric exponents.
+fn monomial(term: &ExprSpan) -> Vec<(String, f64)> {
+    let factors = match &term.0 {
+        Expression::Mul(v) => v.iter().collect(),
+        _ => vec![term],
+    };
+    factors
+        .into_iter()
+        .filter_map(|factor| match split_power(factor) {
+            ((Expression::Var(var), _), (exp, _)) => {
+                Some((var, number(&exp).and_then(Value::as_f64).unwrap_or(0.0)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Order the terms of a sum by descending degree, and then lexicographically
+/// by their variables, so that `x^2 + 2 x y + y^2` is in order.
+fn cmp_terms(a: &ExprSpan, b: &ExprSpan) -> Ordering {
+    let (ma, mb) = (monomial(a), monomial(b));
+    let degree = |m: &[(String, f64)]| m.iter().map(|(_, e)| e).sum::<f64>();
+    let by_degree = degree(&mb)
+        .partial_cmp(&degree(&ma))
+        .unwrap_or(Ordering::Equal);
+    let by_vars = ma
+        .iter()
+        .zip(&mb)
+        .map(|((va, ea), (vb, eb))| {
+            va.cmp(vb)
+                .then_with(|| eb.partial_cmp(ea).unwrap_or(Ordering::Equal))
+        })
+        .find(|&o| o != Ordering::Equal)
+        .unwrap_or_else(|| mb.len().cmp(&ma.len()));
+    by_degree
+        .then(by_vars)
+        .then_with(|| key(&a.0).cmp(&key(&b.0)))
+}
+
+/// Collect the terms of a sum of normal forms by their non-numeric parts,
+/// adding up the numbers.
+fn add_terms(
+    terms: Vec<ExprSpan>,
+    constant: &mut Value,
+    like: &mut Vec<(Value, ExprSpan)>,
+    span: &Span,
+) {
+    for term in terms {
+        if let (Expression::Add(v), _) = term {
+            add_terms(v, constant, like, span);
+            continue;
+        }
+        match split_coef(term) {
+            (x, None) => *constant = arith(Op::Add, constant, &x, span).unwrap(),
+            // Distribute a number over a sum
+            (c, Some((Expression::Add(v), _))) => {
+                let v = v
+                    .into_iter()
+                    .map(|t| mul(vec![val(c.clone(), span), t], span));
+                add_terms(v.collect(), constant, like, span);
+            }
+            (c, Some(rest)) => {
+                let k = key(&rest.0);
+                match like.iter_mut().find(|(_, e)| key(&e.0) == k) {
+                    Some((total, _)) => *total = arith(Op::Add, total, &c, span).unwrap(),
+                    None => like.push((c, rest)),
+                }
+            }
+        }
+    }
+}
+
+/// Add normal forms, giving a normal form.
+fn add(terms: Vec<ExprSpan>, span: &Span) -> ExprSpan {
+    let mut constant = 0.into();
+    let mut like = vec![];
+    add_terms(terms, &mut constant, &mut like, span);
+    let mut terms: Vec<_> = like
+        .into_iter()
+        .filter(|(c, _)| !is_zero(c))
+        .map(|(c, rest)| mul(vec![val(c, span), rest], span))
+        .collect();
+    terms.sort_by(cmp_terms);
+    if !is_zero(&constant) || terms.is_empty() {
+        terms.push(val(constant, span));
+    }
+    match terms.len() {
+        1 => terms.pop().unwrap(),
+        _ => (Expression::Add(terms), span.clone()),
+    }
+}
+
+/// Collect the factors of a product of normal forms, multiplying together
+/// the numbers.
+fn mul_factors(factors: Vec<ExprSpan>, coef: &mut Value, rest: &mut Vec<ExprSpan>, span: &Span) {
+    for factor in factors {
+        match factor {
+            (Expression::Mul(v), _) => mul_factors(v, coef, rest, span),
+            factor => match number(&factor.0) {
+                Some(x) => *coef = arith(Op::Mul, coef, x, span).unwrap(),
+                None => rest.push(factor),
+            },
+        }
+    }
+}
+
+/// Test whether a factor is a power of zero whose exponent may not be
+/// positive, such as `0^-1`.
+fn divides_by_zero(factor: &Expression) -> bool {
+    match factor {
+        Expression::Exp(args) => {
+            number(&args[0].0).is_some_and(is_zero)
+                && !matches!(number(&args[1].0), Some(n) if n.as_f64().is_some_and(|n| n > 0.0))
+        }
+        _ => false,
+    }
+}
+
+/// Multiply normal forms, giving a normal form.
+fn mul(factors: Vec<ExprSpan>, span: &Span) -> ExprSpan {
+    let mut coef = 1.into();
+    let mut rest = vec![];
+    mul_factors(factors, &mut coef, &mut rest, span);
+    // Collect the exponents of like bases
+    let mut like: Vec<(ExprSpan, Vec<ExprSpan>)> = vec![];
+    for factor in rest {
+        let (base, exp) = split_power(&factor);
+        let k = key(&base.0);
+        match like.iter_mut().find(|(b, _)| key(&b.0) == k) {
+            Some((_, exps)) => exps.push(exp),
+            None => like.push((base, vec![exp])),
+        }
+    }
+    let mut rest = vec![];
+    for (base, exps) in like {
+        let factor = pow(base, add(exps, span), span);
+        mul_factors(vec![factor], &mut coef, &mut rest, span);
+    }
+    // Zero cancels the other factors unless one of them divides by zero
+    if is_zero(&coef) && !rest.iter().any(|f| divides_by_zero(&f.0)) || rest.is_empty() {
+        return val(coef, span);
+    }
+    rest.sort_by(cmp_factors);
+    if !is_one(&coef) {
+        rest.insert(0, val(coef, span));
+    }
+    match rest.len() {
+        1 => rest.pop().unwrap(),
+        _ => (Expression::Mul(rest), span.clone()),
+    }
+}
+
+/// Raise a normal form to the power of another, giving a normal form.
+fn pow(base: ExprSpan, exp: ExprSpan, span: &Span) -> ExprSpan {
+    let n = number(&exp.0).cloned();
+    match n {
+        Some(n) if is_zero(&n) => return val(1, span),
+        Some(n) if is_one(&n) => return base,
+        _ => (),
+    }
+    if let Some(b) = number(&base.0) {
+        if is_one(b) {
+            return val(1, span);
+        }
+        if let Some(n) = &n {
+            // Keep irrational powers of rationals, such as 2^(1/2), exact
+            let rational = |x: &Value| matches!(x, Value::Ratio(_) | Value::BigRatio(_));
+            // Take the reciprocal first, since negative powers give floats
+            let (b, n) = match (b, n) {
+                (Value::Ratio(r), Value::Ratio(k)) if k.is_negative() && !r.is_zero() => {
+                    (&Value::Ratio(r.recip()), &Value::Ratio(-k))
+                }
+                _ => (b, n),
+            };
+            if_chain! {
+                if let Ok(x) = do_exp(b.clone(), n.clone(), span);
+                if rational(&x) || number(&Expression::Val(x.clone())).is_some() && !rational(b);
+                then {
+                    return val(x, span);
+                }
+            }
+        }
+    }
+    match (base, n) {
+        // (b^e)^n = b^(e n) for an integer n
+        ((Expression::Exp(args), _), Some(n)) if is_integer(&n) => {
+            let [b, e] = *args;
+            pow(b, mul(vec![e, val(n, span)], span), span)
+        }
+        // (a b)^n = a^n b^n for an integer n
+        ((Expression::Mul(v), _), Some(n)) if is_integer(&n) => {
+            let v = v.into_iter().map(|f| pow(f, val(n.clone(), span), span));
+            mul(v.collect(), span)
+        }
+        (base, _) => (Expression::Exp(Box::new([base, exp])), span.clone()),
+    }
+}
+
+/// Bring an expression into normal form.
+fn normalize(expr: &ExprSpan) -> ExprSpan {
+    use Expression::*;
+    let span = &expr.1;
+    let expr = map_children(expr, &normalize);
+    match expr.0 {
+        Neg(e) => mul(vec![val(-1, span), *e], span),
+        Add(v) => add(v, span),
+        Sub(args) => {
+            let [a, b] = *args;
+            add(vec![a, mul(vec![val(-1, span), b], span)], span)
+        }
+        Mul(v) => mul(v, span),
+        Frac(args) => {
+            let [a, b] = *args;
+            mul(vec![a, pow(b, val(-1, span), span)], span)
+        }
+        Exp(args) => {
+            let [b, e] = *args;
+            pow(b, e, span)
+        }
+        Root(args) => {
+            let [u, n] = *args;
+            pow(u, pow(n, val(-1, span), span), span)
+        }
+        Func(f, arg) => {
+            // Fold a function of numbers if the result is exact
+            let args = match &arg.0 {
+                Comma(v) => v.iter().collect(),
+                _ => vec![arg.as_ref()],
+            };
+            let values: Option<Vec<_>> = args
+                .iter()
+                .map(|(e, s)| number(e).map(|x| (x.clone(), s.clone())))
+                .collect();
+            if_chain! {
+                if let Some(values) = values;
+                if values.iter().all(|(x, _)| !matches!(x, Value::Float(_)));
+                if let Ok(x @ Value::Ratio(_)) = f.apply_args(values, &Context::default(), span);
+                then {
+                    return val(x, span);
+                }
+            }
+            (Func(f, arg), span.clone())
+        }
+        e => (e, span.clone()),
+    }
+}
+
+/// Put a negation on the first factor of a tidied product or fraction, as
+/// it would be written.
+fn negate(expr: ExprSpan) -> ExprSpan {
+    match expr {
+        (Expression::Mul(mut v), span) => {
+            let first = v.remove(0);
+            v.insert(0, negate(first));
+            (Expression::Mul(v), span)
+        }
+        (Expression::Frac(args), span) => {
+            let [a, b] = *args;
+            (Expression::Frac(Box::new([negate(a), b])), span)
+        }
+        (e, span) => (Expression::Neg(Box::new((e, span.clone()))), span),
+    }
+}
+
+/// Tidy up a power of normal forms.
+fn tidy_power(base: &ExprSpan, exp: &ExprSpan) -> ExprSpan {
+    match number(&exp.0) {
+        Some(n) if is_one(n) => tidy(base),
+        _ => (
+            Expression::Exp(Box::new([tidy(base), tidy(exp)])),
+            base.1.clone(),
+        ),
+    }
+}
+
+/// Tidy up a normal form, giving its sign separately.
+fn tidy_signed(expr: &ExprSpan) -> (bool, ExprSpan) {
+    let span = &expr.1;
+    match &expr.0 {
+        Expression::Val(x) if is_negative(x) => (true, val(abs(x), span)),
+        Expression::Mul(_) | Expression::Exp(_) => {
+            let (coef, rest) = split_coef(expr.clone());
+            let factors = match rest {
+                Some((Expression::Mul(v), _)) => v,
+                Some(rest) => vec![rest],
+                None => vec![],
+            };
+            let (mut numer, mut denom) = (vec![], vec![]);
+            match abs(&coef) {
+                Value::Ratio(r) => {
+                    if !r.numer().is_one() {
+                        numer.push(val(*r.numer(), span));
+                    }
+                    if !r.denom().is_one() {
+                        denom.push(val(*r.denom(), span));
+                    }
+                }
+                x => numer.push(val(x, span)),
+            }
+            for factor in &factors {
+                match split_power(factor) {
+                    (base, (Expression::Val(n), _)) if is_negative(&n) => {
+                        denom.push(tidy_power(&base, &val(abs(&n), span)));
+                    }
+                    (base, exp) => numer.push(tidy_power(&base, &exp)),
+                }
+            }
+            let product = |mut v: Vec<ExprSpan>| match v.len() {
+                0 => val(1, span),
+                1 => v.pop().unwrap(),
+                _ => (Expression::Mul(v), span.clone()),
+            };
+            let numer = product(numer);
+            let expr = if denom.is_empty() {
+                numer
+            } else {
+                (
+                    Expression::Frac(Box::new([numer, product(denom)])),
+                    span.clone(),
+                )
+            };
+            (is_negative(&coef), expr)
+        }
+        _ => (false, tidy(expr)),
+    }
+}
+
+/// Tidy up a normal form into the differences, fractions and negations that
+/// a person would write.
+fn tidy(expr: &ExprSpan) -> ExprSpan {
+    let span = &expr.1;
+    match &expr.0 {
+        Expression::Add(terms) => {
+            let mut terms = terms.iter().map(tidy_signed);
+            let (negative, first) = terms.next().unwrap();
+            let mut sum = if negative { negate(first) } else { first };
+            for (negative, term) in terms {
+                sum = match sum {
+                    _ if negative => (Expression::Sub(Box::new([sum, term])), span.clone()),
+                    (Expression::Add(mut v), s) => {
+                        v.push(term);
+                        (Expression::Add(v), s)
+                    }
+                    sum => (Expression::Add(vec![sum, term]), span.clone()),
+                };
+            }
+            sum
+        }
+        Expression::Val(x) if is_negative(x) => negate(val(abs(x), span)),
+        Expression::Mul(_) | Expression::Exp(_) => match tidy_signed(expr) {
+            (true, expr) => negate(expr),
+            (false, expr) => expr,
+        },
+        _ => map_children(expr, &tidy),
+    }
+}
+
+/// Simplify an expression algebraically.
+///
+/// Constants are folded, nested sums and products are flattened, like terms
+/// and like factors are collected, such as `2 x + 3 x` into `5 x` and
+/// `x / x` into `1`, powers of powers and products are expanded, and terms
+/// and factors are put in a canonical order. Rational constants are kept
+/// exact. The result has the same value wherever the original is defined.
+pub fn simplify(expr: &ExprSpan) -> ExprSpan {
+    tidy(&normalize(expr))
+}
@@ -0,0 +1,122 @@
+use super::key;
+use crate::{eval, lex, parse, simplify, Context, Expression};
+
+fn simp(s: &str) -> Expression {
+    simplify(&parse(lex(s, None)).unwrap()).0
+}
+
+/// Check that `s` simplifies to the same expression that `expected` parses
+/// to, apart from the spans.
+fn check(s: &str, expected: &str) {
+    let expected = parse(lex(expected, None)).unwrap().0;
+    assert_eq!(key(&simp(s)), key(&expected), "simplifying {}", s);
+}
+
+#[test]
+fn constants() {
+    check("1 + 2 * 3", "7");
+    assert!(matches!(simp("2 / 4 - 1"), Expression::Neg(e) if e.0 == (1, 2).into()));
+    check("abs(-3) * x", "3 * x");
+    check("x^0", "1");
+    check("0 * x", "0");
+    // Not zero, since 0 / 0 is undefined
+    assert_ne!(simp("0 / 0"), 0.into());
+    assert_ne!(simp("0 * x / 0"), 0.into());
+    check("1^x", "1");
+    assert_eq!(simp("2^(-2)"), (1, 4).into());
+    assert_eq!(simp("1.5 + 1"), 2.5.into());
+}
+
+#[test]
+fn like_terms() {
+    check("2 * x + 3 * x", "5 * x");
+    check("x + 1 + x", "2 * x + 1");
+    check("x - x", "0");
+    check("x - 2 * y + y", "x - y");
+    check("2 * (x + 1) - 2 * x", "2");
+    check("x * y + y * x", "2 * x * y");
+    check("sin(x) + 2 * sin(x)", "3 * sin(x)");
+    check("1.5 * x + x", "2.5 * x");
+}
+
+#[test]
+fn like_factors() {
+    check("x / x", "1");
+    check("x * x", "x^2");
+    check("x^2 * x^3", "x^5");
+    check("x * y / (x * z)", "y / z");
+    check("(x + 1)^2 / (x + 1)", "x + 1");
+    check("sin(x) * sin(x)", "(sin(x))^2");
+    check("e^x * e^x", "e^(2 * x)");
+    check("x^a * x^b", "x^(a + b)");
+}
+
+#[test]
+fn powers() {
+    check("(x^2)^3", "x^6");
+    check("(x * y)^2", "x^2 * y^2");
+    check("(2 * x)^3", "8 * x^3");
+    check("1 / x", "1 / x");
+    check("2 / x^2", "2 / x^2");
+    // The square root of a square is only the base if it isn't negative
+    assert!(matches!(
+        simp("(x^2)^(1 / 2)"),
+        Expression::Exp(args) if matches!(args[0].0, Expression::Exp(_))
+    ));
+}
+
+#[test]
+fn canonical_order() {
+    check("y * x * 2", "2 * x * y");
+    check("x * 2 * pi", "2 * pi * x");
+    check("1 + x + x^2", "x^2 + x + 1");
+    check("y^2 + x^2 + 2 * y * x", "x^2 + 2 * x * y + y^2");
+    check("-x + 3", "-x + 3");
+}
+
+#[test]
+fn signs() {
+    check("-x", "-x");
+    check("-(2 * x)", "-2 * x");
+    check("-x / 2", "-x / 2");
+    check("3 * x / 4", "3 * x / 4");
+    check("x - 3 * y", "x - 3 * y");
+    check("-(-x)", "x");
+}
+
+#[test]
+fn idempotent() {
+    for s in [
+        "x^2 - 2 * x * y / 3 + 1",
+        "-x / (2 * y^2) + sin(x)^2",
+        "a * (b + c) - 4^(1 / 2)",
+    ] {
+        let once = simplify(&parse(lex(s, None)).unwrap());
+        assert_eq!(key(&simplify(&once).0), key(&once.0), "simplifying {}", s);
+    }
+}
+
+#[test]
+fn same_value() {
+    for s in [
+        "(x + 1)^2 / (x + 1) - 3 * x / x",
+        "x^3 * x^(-2) + 2 * (x - 1) - x / 4",
+        "(2 * x * y)^2 / (y * 4) + sin(x) * sin(x) / 3",
+        "e^x * e^(-x) + 2^(1 / 2) * y",
+    ] {
+        let expr = parse(lex(s, None)).unwrap();
+        let simplified = simplify(&expr);
+        let mut ctx = Context::default();
+        ctx.vars.insert("x".to_string(), 1.75.into());
+        ctx.vars.insert("y".to_string(), (-3).into());
+        let before = eval(&expr, &mut ctx).unwrap().as_f64().unwrap();
+        let after = eval(&simplified, &mut ctx).unwrap().as_f64().unwrap();
+        assert!(
+            (before - after).abs() < 1e-12,
+            "{}: {} != {}",
+            s,
+            before,
+            after
+        );
+    }
+}
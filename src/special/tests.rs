@@ -0,0 +1,25 @@
+use std::f64::consts;
+
+use crate::gamma;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-12 * b.abs().max(1.0)
+}
+
+#[test]
+fn gamma_integers() {
+    assert_eq!(gamma(1.0), 1.0);
+    assert_eq!(gamma(5.0), 24.0);
+    assert_eq!(gamma(21.0), 2_432_902_008_176_640_000.0);
+    assert_eq!(gamma(0.0), f64::INFINITY);
+    assert_eq!(gamma(-3.0), f64::INFINITY);
+    assert_eq!(gamma(200.0), f64::INFINITY);
+}
+
+#[test]
+fn gamma_reals() {
+    assert!(close(gamma(0.5), consts::PI.sqrt()));
+    assert!(close(gamma(1.5), consts::PI.sqrt() / 2.0));
+    assert!(close(gamma(-0.5), -2.0 * consts::PI.sqrt()));
+    assert!(close(gamma(30.5), 4.8226969334909095e31));
+}
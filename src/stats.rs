@@ -0,0 +1,376 @@
+//! Statistics over lists of numbers.
+
+use std::cmp::Ordering;
+
+use num::rational::BigRational;
+use num::traits::{Num, ToPrimitive};
+
+use crate::Value;
+
+#[cfg(test)]
+mod tests;
+
+/// A list of numbers, which are exact if they are all rational, or floats
+/// otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Data {
+    /// Rational numbers.
+    Exact(Vec<BigRational>),
+    /// Floats.
+    Float(Vec<f64>),
+}
+
+impl Data {
+    /// Convert a list of values into numbers.
+    ///
+    /// Returns `None` if a value is not a number.
+    pub fn new(items: &[Value]) -> Option<Data> {
+        if let Some(exact) = items.iter().map(Value::to_big).collect() {
+            return Some(Data::Exact(exact));
+        }
+        items
+            .iter()
+            .map(Value::as_f64)
+            .collect::<Option<_>>()
+            .map(Data::Float)
+    }
+
+    /// Get the number of numbers.
+    pub fn len(&self) -> usize {
+        match self {
+            Data::Exact(xs) => xs.len(),
+            Data::Float(xs) => xs.len(),
+        }
+    }
+
+    /// Test whether there are no numbers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Convert the numbers to floats.
+    pub fn into_f64(self) -> Vec<f64> {
+        match self {
+            Data::Exact(xs) => xs.iter().map(ratio_to_f64).collect(),
+            Data::Float(xs) => xs,
+        }
+    }
+}
+
+fn ratio_to_f64(x: &BigRational) -> f64 {
+    Value::BigRatio(x.clone()).as_f64().unwrap()
+}
+
+/// A number that statistics can be calculated with.
+trait Number: Num + PartialOrd + Clone + Into<Value> {
+    /// Convert a count into a number.
+    fn count(n: usize) -> Self;
+
+    /// Get the sum of the numbers.
+    fn total(xs: &[Self]) -> Self;
+
+    /// Get the largest count that is no larger than this number, which must
+    /// not be negative.
+    fn floor_count(&self) -> usize;
+
+    /// Convert the number to a float.
+    fn to_f64(&self) -> f64;
+}
+
+impl Number for BigRational {
+    fn count(n: usize) -> Self {
+        BigRational::from_integer(n.into())
+    }
+
+    fn total(xs: &[Self]) -> Self {
+        xs.iter()
+            .fold(BigRational::from_integer(0.into()), |a, x| a + x)
+    }
+
+    fn floor_count(&self) -> usize {
+        self.floor().to_integer().to_usize().unwrap_or(usize::MAX)
+    }
+
+    fn to_f64(&self) -> f64 {
+        ratio_to_f64(self)
+    }
+}
+
+impl Number for f64 {
+    fn count(n: usize) -> Self {
+        n as f64
+    }
+
+    /// Kahan-Babuska (Neumaier) compensated summation.
+    fn total(xs: &[Self]) -> Self {
+        let (mut sum, mut comp) = (0.0, 0.0);
+        for &x in xs {
+            let t = sum + x;
+            if sum.abs() >= x.abs() {
+                comp += (sum - t) + x;
+            } else {
+                comp += (x - t) + sum;
+            }
+            sum = t;
+        }
+        sum + comp
+    }
+
+    fn floor_count(&self) -> usize {
+        self.floor() as usize
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+}
+
+/// Sums of the deviations from the means of paired data.
+struct Moments<T> {
+    mean_x: T,
+    mean_y: T,
+    /// The sum of the squared deviations of `x`.
+    ssx: T,
+    /// The sum of the squared deviations of `y`.
+    ssy: T,
+    /// The sum of the products of the deviations of `x` and `y`.
+    sxy: T,
+}
+
+/// Calculate the moments of paired data with Welford's algorithm, which is
+/// exact for rationals and numerically stable for floats.
+fn moments<T: Number>(xs: &[T], ys: &[T]) -> Moments<T> {
+    let mut m = Moments {
+        mean_x: T::zero(),
+        mean_y: T::zero(),
+        ssx: T::zero(),
+        ssy: T::zero(),
+        sxy: T::zero(),
+    };
+    for (i, (x, y)) in xs.iter().zip(ys).enumerate() {
+        let n = T::count(i + 1);
+        let dx = x.clone() - m.mean_x.clone();
+        let dy = y.clone() - m.mean_y.clone();
+        m.mean_x = m.mean_x + dx.clone() / n.clone();
+        m.mean_y = m.mean_y + dy.clone() / n;
+        let dx2 = x.clone() - m.mean_x.clone();
+        let dy2 = y.clone() - m.mean_y.clone();
+        m.ssx = m.ssx + dx.clone() * dx2;
+        m.ssy = m.ssy + dy * dy2.clone();
+        m.sxy = m.sxy + dx * dy2;
+    }
+    m
+}
+
+/// Sort numbers in ascending order.
+fn sorted<T: Number>(xs: &[T]) -> Vec<T> {
+    let mut xs = xs.to_vec();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    xs
+}
+
+fn mean_of<T: Number>(xs: &[T]) -> Option<Value> {
+    if xs.is_empty() {
+        return None;
+    }
+    Some((T::total(xs) / T::count(xs.len())).into())
+}
+
+fn median_of<T: Number>(xs: &[T]) -> Option<Value> {
+    let xs = sorted(xs);
+    let n = xs.len();
+    if n == 0 {
+        None
+    } else if n % 2 == 1 {
+        Some(xs[n / 2].clone().into())
+    } else {
+        let two = T::count(2);
+        Some(((xs[n / 2 - 1].clone() + xs[n / 2].clone()) / two).into())
+    }
+}
+
+fn mode_of<T: Number>(xs: &[T]) -> Option<Value> {
+    let xs = sorted(xs);
+    let mut best: Option<(&T, usize)> = None;
+    let mut i = 0;
+    while i < xs.len() {
+        let run = xs[i..].iter().take_while(|&x| *x == xs[i]).count().max(1);
+        if best.is_none_or(|(_, count)| run > count) {
+            best = Some((&xs[i], run));
+        }
+        i += run;
+    }
+    best.map(|(x, _)| x.clone().into())
+}
+
+fn variance_of<T: Number>(xs: &[T], ddof: usize) -> Option<Value> {
+    if xs.len() <= ddof {
+        return None;
+    }
+    let m = moments(xs, xs);
+    Some((m.ssx / T::count(xs.len() - ddof)).into())
+}
+
+fn quantile_of<T: Number>(xs: &[T], q: T) -> Option<Value> {
+    // NaN is not in the range
+    if xs.is_empty() || !(q >= T::zero() && q <= T::one()) {
+        return None;
+    }
+    let xs = sorted(xs);
+    let h = T::count(xs.len() - 1) * q;
+    let lo = h.floor_count().min(xs.len() - 1);
+    let frac = h - T::count(lo);
+    if frac.is_zero() || lo + 1 == xs.len() {
+        return Some(xs[lo].clone().into());
+    }
+    let lower = xs[lo].clone();
+    let upper = xs[lo + 1].clone();
+    Some((lower.clone() + frac * (upper - lower)).into())
+}
+
+fn extreme_of<T: Number>(xs: &[T], ordering: Ordering) -> Option<usize> {
+    (0..xs.len()).reduce(|best, i| {
+        if xs[i].partial_cmp(&xs[best]) == Some(ordering) {
+            i
+        } else {
+            best
+        }
+    })
+}
+
+fn covariance_of<T: Number>(xs: &[T], ys: &[T], ddof: usize) -> Option<Value> {
+    if xs.len() <= ddof {
+        return None;
+    }
+    let m = moments(xs, ys);
+    Some((m.sxy / T::count(xs.len() - ddof)).into())
+}
+
+fn correlation_of<T: Number>(xs: &[T], ys: &[T]) -> Option<f64> {
+    let m = moments(xs, ys);
+    if m.ssx.is_zero() || m.ssy.is_zero() {
+        return None;
+    }
+    let r = m.sxy.to_f64() / (m.ssx * m.ssy).to_f64().sqrt();
+    Some(r.clamp(-1.0, 1.0))
+}
+
+fn regression_of<T: Number>(xs: &[T], ys: &[T]) -> Option<(Value, Value)> {
+    let m = moments(xs, ys);
+    if m.ssx.is_zero() {
+        return None;
+    }
+    let slope = m.sxy / m.ssx;
+    let intercept = m.mean_y - slope.clone() * m.mean_x;
+    Some((slope.into(), intercept.into()))
+}
+
+/// Get the arithmetic mean. Floats are summed with compensated summation.
+///
+/// Returns `None` if there is no data.
+pub fn mean(data: &Data) -> Option<Value> {
+    match data {
+        Data::Exact(xs) => mean_of(xs),
+        Data::Float(xs) => mean_of(xs),
+    }
+}
+
+/// Get the median, which is the mean of the middle two numbers if there is
+/// an even number of them.
+///
+/// Returns `None` if there is no data.
+pub fn median(data: &Data) -> Option<Value> {
+    match data {
+        Data::Exact(xs) => median_of(xs),
+        Data::Float(xs) => median_of(xs),
+    }
+}
+
+/// Get the most common number, or the smallest of them if there is a tie.
+///
+/// Returns `None` if there is no data.
+pub fn mode(data: &Data) -> Option<Value> {
+    match data {
+        Data::Exact(xs) => mode_of(xs),
+        Data::Float(xs) => mode_of(xs),
+    }
+}
+
+/// Get the variance, dividing by `n - ddof` where `n` is the number of
+/// numbers. The sample variance has `ddof = 1`, and the population variance
+/// has `ddof = 0`.
+///
+/// Returns `None` if `n <= ddof`.
+pub fn variance(data: &Data, ddof: usize) -> Option<Value> {
+    match data {
+        Data::Exact(xs) => variance_of(xs, ddof),
+        Data::Float(xs) => variance_of(xs, ddof),
+    }
+}
+
+/// Get the standard deviation, which is the square root of the variance.
+///
+/// Returns `None` if `n <= ddof`.
+pub fn std_dev(data: &Data, ddof: usize) -> Option<f64> {
+    variance(data, ddof)?.as_f64().map(f64::sqrt)
+}
+
+/// Get the `q` quantile, interpolating linearly between the closest
+/// numbers. For example, the 0.5 quantile is the median.
+///
+/// Returns `None` if there is no data or `q` is outside of `[0, 1]`.
+pub fn quantile(data: &Data, q: &Value) -> Option<Value> {
+    match (data, q.to_big()) {
+        (Data::Exact(xs), Some(q)) => quantile_of(xs, q),
+        _ => quantile_of(&data.clone().into_f64(), q.as_f64()?),
+    }
+}
+
+/// Get the position of the smallest number, or of the largest if `max` is
+/// set. The first is used if there is a tie.
+///
+/// Returns `None` if there is no data.
+pub fn extreme(data: &Data, max: bool) -> Option<usize> {
+    let ordering = if max {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    };
+    match data {
+        Data::Exact(xs) => extreme_of(xs, ordering),
+        Data::Float(xs) => extreme_of(xs, ordering),
+    }
+}
+
+/// Get the covariance of paired data of the same length, dividing by
+/// `n - ddof` as for `variance`.
+///
+/// Returns `None` if `n <= ddof`.
+pub fn covariance(xs: &Data, ys: &Data, ddof: usize) -> Option<Value> {
+    match (xs, ys) {
+        (Data::Exact(xs), Data::Exact(ys)) => covariance_of(xs, ys, ddof),
+        _ => covariance_of(&xs.clone().into_f64(), &ys.clone().into_f64(), ddof),
+    }
+}
+
+/// Get the Pearson correlation coefficient of paired data of the same
+/// length.
+///
+/// Returns `None` if either list has no variation.
+pub fn correlation(xs: &Data, ys: &Data) -> Option<f64> {
+    match (xs, ys) {
+        (Data::Exact(xs), Data::Exact(ys)) => correlation_of(xs, ys),
+        _ => correlation_of(&xs.clone().into_f64(), &ys.clone().into_f64()),
+    }
+}
+
+/// Fit a line `y = slope * x + intercept` to paired data of the same length
+/// by least squares, returning the slope and intercept.
+///
+/// Returns `None` if the `x` values are all the same.
+pub fn linear_regression(xs: &Data, ys: &Data) -> Option<(Value, Value)> {
+    match (xs, ys) {
+        (Data::Exact(xs), Data::Exact(ys)) => regression_of(xs, ys),
+        _ => regression_of(&xs.clone().into_f64(), &ys.clone().into_f64()),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    correlation, covariance, extreme, linear_regression, mean, median, mode, quantile, std_dev,
+    variance, Data, Value,
+};
+
+fn ints(xs: &[i64]) -> Data {
+    Data::new(&xs.iter().map(|&x| x.into()).collect::<Vec<Value>>()).unwrap()
+}
+
+fn floats(xs: &[f64]) -> Data {
+    Data::Float(xs.to_vec())
+}
+
+#[test]
+fn data() {
+    let items = vec![1.into(), (1, 2).into()];
+    assert!(matches!(Data::new(&items), Some(Data::Exact(_))));
+    let items = vec![1.into(), 0.5.into()];
+    assert_eq!(Data::new(&items), Some(floats(&[1.0, 0.5])));
+    let items = vec![Value::List(vec![])];
+    assert_eq!(Data::new(&items), None);
+}
+
+#[test]
+fn averages() {
+    assert_eq!(mean(&ints(&[1, 2, 3, 4])), Some((5, 2).into()));
+    assert_eq!(mean(&ints(&[])), None);
+    assert_eq!(median(&ints(&[3, 1, 2])), Some(2.into()));
+    assert_eq!(median(&ints(&[4, 1, 3, 2])), Some((5, 2).into()));
+    assert_eq!(median(&floats(&[2.5, -1.0])), Some(0.75.into()));
+    assert_eq!(mode(&ints(&[3, 1, 3, 2, 1])), Some(1.into()));
+    assert_eq!(mode(&floats(&[0.5, 0.25, 0.5])), Some(0.5.into()));
+    assert_eq!(extreme(&ints(&[3, 1, 4, 1]), false), Some(1));
+    assert_eq!(extreme(&ints(&[3, 1, 4, 1]), true), Some(2));
+    assert_eq!(extreme(&ints(&[]), true), None);
+}
+
+#[test]
+fn compensated_sum() {
+    // Naive summation loses the small terms entirely
+    let mut xs = vec![1e16];
+    xs.extend([1.0; 10].iter());
+    xs.push(-1e16);
+    assert_eq!(mean(&floats(&xs)), Some((10.0 / 12.0).into()));
+}
+
+#[test]
+fn spread() {
+    let data = ints(&[2, 4, 4, 4, 5, 5, 7, 9]);
+    assert_eq!(variance(&data, 0), Some(4.into()));
+    assert_eq!(variance(&data, 1), Some((32, 7).into()));
+    assert_eq!(std_dev(&data, 0), Some(2.0));
+    assert_eq!(variance(&ints(&[5]), 1), None);
+    assert_eq!(variance(&ints(&[5]), 0), Some(0.into()));
+    // Welford's algorithm is not thrown off by a large offset
+    let data = floats(&[1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0]);
+    assert_eq!(variance(&data, 1), Some(30.0.into()));
+}
+
+#[test]
+fn quantiles() {
+    let data = ints(&[4, 1, 3, 2]);
+    assert_eq!(quantile(&data, &0.into()), Some(1.into()));
+    assert_eq!(quantile(&data, &(1, 4).into()), Some((7, 4).into()));
+    assert_eq!(quantile(&data, &(1, 2).into()), Some((5, 2).into()));
+    assert_eq!(quantile(&data, &1.into()), Some(4.into()));
+    assert_eq!(quantile(&data, &0.5.into()), Some(2.5.into()));
+    assert_eq!(quantile(&data, &(3, 2).into()), None);
+    assert_eq!(quantile(&data, &f64::NAN.into()), None);
+    assert_eq!(quantile(&data, &1.0.into()), Some(4.0.into()));
+    assert_eq!(quantile(&ints(&[]), &0.into()), None);
+}
+
+#[test]
+fn paired() {
+    let xs = ints(&[1, 2, 3]);
+    let ys = ints(&[2, 4, 7]);
+    assert_eq!(covariance(&xs, &ys, 1), Some((5, 2).into()));
+    assert_eq!(covariance(&xs, &ys, 0), Some((5, 3).into()));
+    assert_eq!(correlation(&xs, &ints(&[6, 4, 2])), Some(-1.0));
+    assert_eq!(correlation(&xs, &ints(&[1, 1, 1])), None);
+    assert_eq!(
+        linear_regression(&xs, &ys),
+        Some(((5, 2).into(), (-2, 3).into()))
+    );
+    assert_eq!(
+        linear_regression(&xs, &floats(&[1.0, 3.0, 5.0])),
+        Some((2.0.into(), (-1.0).into()))
+    );
+    assert_eq!(linear_regression(&ints(&[2, 2]), &ints(&[1, 2])), None);
+}
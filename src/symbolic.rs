@@ -0,0 +1,245 @@
+//! Exact rational multiples of powers of constants, such as `2 pi / 3`.
+
+use std::convert::TryFrom;
+use std::ops::Neg;
+
+use num::pow::checked_pow;
+use num::rational::Ratio;
+use num::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Constant, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// An exact number of the form `coef * base^power`, such as `2 pi / 3` or
+/// `e^2`.
+///
+/// Symbolic values are kept exact through multiplication and division by
+/// rationals, and through addition of like terms. Any other combination
+/// produces a float.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Symbolic {
+    pub coef: Ratio<i64>,
+    /// The constant, which is either pi or e.
+    pub base: Constant,
+    pub power: Ratio<i64>,
+}
+
+impl Symbolic {
+    /// Create a symbolic value equal to one of the constant.
+    pub fn constant(base: Constant) -> Symbolic {
+        Symbolic {
+            coef: Ratio::one(),
+            base,
+            power: Ratio::one(),
+        }
+    }
+
+    /// Create a symbolic value equal to the rational `r`.
+    fn rational(r: Ratio<i64>, base: Constant) -> Symbolic {
+        Symbolic {
+            coef: r,
+            base,
+            power: Ratio::zero(),
+        }
+    }
+
+    /// Convert the value to a float.
+    pub fn to_f64(self) -> f64 {
+        let base = self.base.to_f64();
+        let coef = *self.coef.numer() as f64 / *self.coef.denom() as f64;
+        let power = *self.power.numer() as f64 / *self.power.denom() as f64;
+        coef * base.powf(power)
+    }
+
+    /// Convert the symbolic value into a value, which is a rational if the
+    /// constant has cancelled out or the coefficient is zero.
+    pub fn into_value(self) -> Value {
+        if self.power.is_zero() || self.coef.is_zero() {
+            Value::Ratio(self.coef)
+        } else {
+            Value::Symbolic(self)
+        }
+    }
+
+    /// Get the absolute value.
+    pub fn abs(self) -> Symbolic {
+        Symbolic {
+            coef: if self.coef < Ratio::zero() {
+                -self.coef
+            } else {
+                self.coef
+            },
+            ..self
+        }
+    }
+
+    /// Add another symbolic value with the same base and power.
+    pub fn checked_add(self, rhs: Symbolic) -> Option<Symbolic> {
+        let base = common_base(self, rhs)?;
+        if self.power != rhs.power {
+            return None;
+        }
+        Some(Symbolic {
+            coef: self.coef.checked_add(&rhs.coef)?,
+            base,
+            power: self.power,
+        })
+    }
+
+    /// Subtract another symbolic value with the same base and power.
+    pub fn checked_sub(self, rhs: Symbolic) -> Option<Symbolic> {
+        let base = common_base(self, rhs)?;
+        if self.power != rhs.power {
+            return None;
+        }
+        Some(Symbolic {
+            coef: self.coef.checked_sub(&rhs.coef)?,
+            base,
+            power: self.power,
+        })
+    }
+
+    /// Multiply by another symbolic value with the same base.
+    pub fn checked_mul(self, rhs: Symbolic) -> Option<Symbolic> {
+        Some(Symbolic {
+            base: common_base(self, rhs)?,
+            coef: self.coef.checked_mul(&rhs.coef)?,
+            power: self.power.checked_add(&rhs.power)?,
+        })
+    }
+
+    /// Divide by another symbolic value with the same base.
+    pub fn checked_div(self, rhs: Symbolic) -> Option<Symbolic> {
+        if rhs.coef.is_zero() {
+            return None;
+        }
+        Some(Symbolic {
+            base: common_base(self, rhs)?,
+            coef: self.coef.checked_div(&rhs.coef)?,
+            power: self.power.checked_sub(&rhs.power)?,
+        })
+    }
+
+    /// Raise to a rational power.
+    ///
+    /// Non-integer powers are only exact if the coefficient is one.
+    pub fn pow(self, exp: Ratio<i64>) -> Option<Symbolic> {
+        let coef = if exp.is_integer() {
+            let n = *exp.numer();
+            let coef = if n < 0 {
+                if self.coef.is_zero() {
+                    return None;
+                }
+                self.coef.recip()
+            } else {
+                self.coef
+            };
+            checked_pow(coef, usize::try_from(n.checked_abs()?).ok()?)?
+        } else if self.coef.is_one() {
+            self.coef
+        } else {
+            return None;
+        };
+        Some(Symbolic {
+            coef,
+            base: self.base,
+            power: self.power.checked_mul(&exp)?,
+        })
+    }
+}
+
+impl Neg for Symbolic {
+    type Output = Symbolic;
+
+    fn neg(self) -> Symbolic {
+        Symbolic {
+            coef: -self.coef,
+            ..self
+        }
+    }
+}
+
+/// Get the base shared by two symbolic values, where a value whose power is
+/// zero is compatible with any base.
+fn common_base(a: Symbolic, b: Symbolic) -> Option<Constant> {
+    if a.power.is_zero() || a.base == b.base {
+        Some(b.base)
+    } else if b.power.is_zero() {
+        Some(a.base)
+    } else {
+        None
+    }
+}
+
+/// Convert a value to a symbolic value, if it is exact.
+fn to_symbolic(val: &Value, base: Constant) -> Option<Symbolic> {
+    match val {
+        Value::Ratio(r) => Some(Symbolic::rational(*r, base)),
+        Value::Symbolic(s) => Some(*s),
+        _ => None,
+    }
+}
+
+/// Apply a binary arithmetic operation to two values, at least one of which
+/// is symbolic, keeping the result exact.
+///
+/// Returns `None` if the result cannot be represented exactly.
+pub fn symbolic_binary<F>(lhs: &Value, rhs: &Value, f: F) -> Option<Value>
+where
+    F: FnOnce(Symbolic, Symbolic) -> Option<Symbolic>,
+{
+    let base = match (lhs, rhs) {
+        (Value::Symbolic(s), _) | (_, Value::Symbolic(s)) => s.base,
+        _ => return None,
+    };
+    let lhs = to_symbolic(lhs, base)?;
+    let rhs = to_symbolic(rhs, base)?;
+    f(lhs, rhs).map(Symbolic::into_value)
+}
+
+/// Reduce an angle, given as a multiple of pi, into the range `[0, 2)`.
+fn reduce_angle(q: Ratio<i64>) -> Option<Ratio<i64>> {
+    let two = Ratio::from_integer(2);
+    let turns = q.checked_div(&two)?.floor();
+    q.checked_sub(&turns.checked_mul(&two)?)
+}
+
+/// Get the exact sine of `q * pi`, if it is rational.
+pub fn exact_sin(q: Ratio<i64>) -> Option<Ratio<i64>> {
+    let q = reduce_angle(q)?;
+    let r = match (*q.numer(), *q.denom()) {
+        (0, 1) | (1, 1) => (0, 1),
+        (1, 2) => (1, 1),
+        (3, 2) => (-1, 1),
+        (1, 6) | (5, 6) => (1, 2),
+        (7, 6) | (11, 6) => (-1, 2),
+        _ => return None,
+    };
+    Some(r.into())
+}
+
+/// Get the exact cosine of `q * pi`, if it is rational.
+pub fn exact_cos(q: Ratio<i64>) -> Option<Ratio<i64>> {
+    exact_sin(q.checked_add(&Ratio::new(1, 2))?)
+}
+
+/// Get the exact tangent of `q * pi`, if it is rational.
+///
+/// The outer `Option` is `None` if the tangent is irrational; the inner one
+/// is `None` if the tangent is undefined.
+pub fn exact_tan(q: Ratio<i64>) -> Option<Option<Ratio<i64>>> {
+    let q = reduce_angle(q)?;
+    let r = match (*q.numer(), *q.denom()) {
+        (0, 1) | (1, 1) => (0, 1),
+        (1, 4) | (5, 4) => (1, 1),
+        (3, 4) | (7, 4) => (-1, 1),
+        (1, 2) | (3, 2) => return Some(None),
+        _ => return None,
+    };
+    Some(Some(r.into()))
+}
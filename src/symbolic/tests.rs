@@ -0,0 +1,87 @@
+use std::f64::consts;
+
+use num::rational::Ratio;
+
+use crate::{exact_cos, exact_sin, exact_tan, symbolic_binary, Constant, Symbolic, Value};
+
+fn pi(n: i64, d: i64) -> Symbolic {
+    Symbolic {
+        coef: Ratio::new(n, d),
+        ..Symbolic::constant(Constant::Pi)
+    }
+}
+
+#[test]
+fn to_f64() {
+    assert_eq!(Symbolic::constant(Constant::Pi).to_f64(), consts::PI);
+    assert_eq!(Symbolic::constant(Constant::E).to_f64(), consts::E);
+    assert_eq!(pi(1, 2).to_f64(), consts::FRAC_PI_2);
+    assert_eq!(
+        pi(1, 1).pow(Ratio::new(1, 2)).unwrap().to_f64(),
+        consts::PI.sqrt()
+    );
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(pi(1, 3).checked_add(pi(2, 3)), Some(pi(1, 1)));
+    assert_eq!(
+        pi(1, 1).checked_sub(pi(1, 1)).unwrap().into_value(),
+        Value::Ratio(0.into())
+    );
+    assert_eq!(pi(1, 1).checked_add(pi(1, 1).pow(2.into()).unwrap()), None);
+    assert_eq!(
+        pi(2, 1).checked_div(pi(1, 1)).unwrap().into_value(),
+        Value::Ratio(2.into())
+    );
+    assert_eq!(pi(1, 1).checked_mul(Symbolic::constant(Constant::E)), None);
+    assert_eq!(-pi(1, 2), pi(-1, 2));
+    assert_eq!(pi(-1, 2).abs(), pi(1, 2));
+}
+
+#[test]
+fn pow() {
+    let sq = pi(2, 1).pow(2.into()).unwrap();
+    assert_eq!(sq.coef, 4.into());
+    assert_eq!(sq.power, 2.into());
+    let inv = pi(2, 1).pow((-1).into()).unwrap();
+    assert_eq!(inv.coef, Ratio::new(1, 2));
+    assert_eq!(inv.power, (-1).into());
+    assert_eq!(pi(2, 1).pow(Ratio::new(1, 2)), None);
+}
+
+#[test]
+fn binary_with_values() {
+    let pi_val = Value::Symbolic(pi(1, 1));
+    let two = Value::Ratio(2.into());
+    assert_eq!(
+        symbolic_binary(&pi_val, &two, |a, b| a.checked_div(b)),
+        Some(Value::Symbolic(pi(1, 2))),
+    );
+    assert_eq!(
+        symbolic_binary(&two, &pi_val, |a, b| a.checked_mul(b)),
+        Some(Value::Symbolic(pi(2, 1))),
+    );
+    assert_eq!(
+        symbolic_binary(&pi_val, &two, |a, b| a.checked_add(b)),
+        None
+    );
+    assert_eq!(
+        symbolic_binary(&pi_val, &Value::Float(1.0), |a, b| a.checked_mul(b)),
+        None,
+    );
+}
+
+#[test]
+fn exact_trig() {
+    assert_eq!(exact_sin(Ratio::new(1, 6)), Some(Ratio::new(1, 2)));
+    assert_eq!(exact_sin(Ratio::new(-1, 2)), Some((-1).into()));
+    assert_eq!(exact_sin(5.into()), Some(0.into()));
+    assert_eq!(exact_sin(Ratio::new(1, 4)), None);
+    assert_eq!(exact_cos(1.into()), Some((-1).into()));
+    assert_eq!(exact_cos(Ratio::new(1, 3)), Some(Ratio::new(1, 2)));
+    assert_eq!(exact_cos(Ratio::new(1, 2)), Some(0.into()));
+    assert_eq!(exact_tan(Ratio::new(3, 4)), Some(Some((-1).into())));
+    assert_eq!(exact_tan(Ratio::new(1, 2)), Some(None));
+    assert_eq!(exact_tan(Ratio::new(1, 3)), None);
+}
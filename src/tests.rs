@@ -2,7 +2,7 @@
 
 use std::f64::consts;
 
-use crate::{eval, lex, parse, Result, Value};
+use crate::{eval, lex, parse, AngleMeasure, Context, Error, Result, Value};
 
 fn try_ev(s: &str) -> Result<Value> {
     let tokens = lex(s, None);
@@ -26,6 +26,17 @@ fn basic_int() {
     assert_eq!(ev("5,4"), 4.into());
 }
 
+#[test]
+fn big_int_literal() {
+    assert_eq!(show("9223372036854775808"), "9223372036854775808");
+    assert_eq!(ev("9223372036854775808 - 1"), i64::MAX.into());
+    assert_eq!(show("-9223372036854775808"), "-9223372036854775808");
+    assert_eq!(
+        try_ev("fact(9223372036854775808)").map_err(|e| e.0),
+        Err(Error::Domain)
+    );
+}
+
 #[test]
 fn repeated_ops() {
     assert_eq!(ev("2+2+1"), 5.into());
@@ -58,19 +69,443 @@ fn basic_float() {
 
 #[test]
 fn builtin_const() {
-    assert_eq!(ev("pi"), consts::PI.into());
-    assert_eq!(ev("pi*2"), (consts::PI * 2.0).into());
-    assert_eq!(ev("e"), consts::E.into());
+    assert_eq!(ev("pi").as_f64(), Some(consts::PI));
+    assert_eq!(ev("pi*2").as_f64(), Some(consts::PI * 2.0));
+    assert_eq!(ev("pi*2.0"), (consts::PI * 2.0).into());
+    assert_eq!(ev("e").as_f64(), Some(consts::E));
 }
 
 #[test]
 fn builtin_func() {
     assert_eq!(ev("sin(0)"), 0.0.into());
-    assert_eq!(ev("sin(pi/2)"), 1.0.into());
+    assert_eq!(ev("sin(pi/2)"), 1.into());
     assert_eq!(ev("cos(0.0)"), 1.0.into());
-    assert_eq!(ev("cos(pi)"), (-1.0).into());
+    assert_eq!(ev("cos(pi)"), (-1).into());
     assert_eq!(ev("tan(0)"), 0.0.into());
     assert_eq!(ev("asin(0)"), 0.0.into());
     assert_eq!(ev("acos(1.0)"), 0.0.into());
     assert_eq!(ev("atan(1.0)"), (consts::PI / 4.0).into());
 }
+
+#[test]
+fn builtin_multiple_args() {
+    assert_eq!(ev("rationalize(0.125)"), (1, 8).into());
+    assert_eq!(ev("rationalize(pi, 0.001)"), (333, 106).into());
+    assert_eq!(ev("approx(pi, 7)"), (22, 7).into());
+    assert_eq!(ev("approx(sin(pi/6))"), (1, 2).into());
+    assert_eq!(try_ev("sin(1, 2)").map_err(|e| e.0), Err(Error::Syntax));
+}
+
+#[test]
+fn rounding_functions() {
+    assert_eq!(ev("floor(-7/2)"), (-4).into());
+    assert_eq!(ev("ceil(7/2)"), 4.into());
+    assert_eq!(ev("trunc(-7/2)"), (-3).into());
+    assert_eq!(ev("round(7/2)"), 4.into());
+    assert_eq!(ev("round(5/2)"), 2.into());
+    assert_eq!(ev("round(2/3, 1)"), (7, 10).into());
+    assert_eq!(
+        show("round(1/3, 20)"),
+        "33333333333333333333 / 100000000000000000000"
+    );
+    assert_eq!(ev("round(1234.5, -2)"), 1200.0.into());
+    // Floats too large to have digits at the scale are unchanged
+    assert_eq!(ev("round(1e300, 10)"), 1e300.into());
+    assert_eq!(ev("round(2.5, 310)"), 2.5.into());
+    assert_eq!(ev("round(1/3, 9223372036854775807)"), (1.0 / 3.0).into());
+    assert_eq!(ev("frac(7/2)"), (1, 2).into());
+    assert_eq!(ev("sign(-pi)"), (-1).into());
+    assert_eq!(ev("floor(2.7)"), 2.0.into());
+    assert_eq!(
+        try_ev("round(1, 2, 3)").map_err(|e| e.0),
+        Err(Error::Syntax)
+    );
+}
+
+#[test]
+fn number_theory() {
+    assert_eq!(ev("gcd(12, 18)"), 6.into());
+    assert_eq!(ev("lcm(4, 6, 10)"), 60.into());
+    assert_eq!(ev("modpow(3, 200, 1000007)"), 959082.into());
+    assert_eq!(ev("modinv(17, 3120)"), 2753.into());
+    assert_eq!(show("factor(-360)"), "-1 * 2^3 * 3^2 * 5");
+    assert_eq!(show("factor(1)"), "1");
+    assert_eq!(ev("factor(360) + 1"), 361.into());
+    // The factors of i64::MIN multiply out to more than i64::MAX
+    assert_eq!(show("factor(-9223372036854775807 - 1)"), "-1 * 2^63");
+    assert_eq!(
+        ev("factor(-9223372036854775807 - 1) + 1"),
+        (-9223372036854775807).into()
+    );
+    assert_eq!(ev("totient(factor(36))"), 12.into());
+    assert_eq!(
+        try_ev("gcd(12, 2.0)").map_err(|e| (e.0, e.1.start)),
+        Err((Error::Type, 9))
+    );
+    assert_eq!(try_ev("isprime(1/2)").map_err(|e| e.0), Err(Error::Type));
+}
+
+#[test]
+fn combinatorics() {
+    assert_eq!(ev("fact(20)"), 2_432_902_008_176_640_000.into());
+    assert_eq!(show("fact(25)"), "15511210043330985984000000");
+    assert_eq!(ev("fact(25) / fact(24)"), 25.into());
+    assert_eq!(ev("fact(25) - fact(25)"), 0.into());
+    assert_eq!(show("choose(100, 50)"), "100891344545564193334812497256");
+    assert_eq!(show("-fact(22) / 23"), "-1124000727777607680000 / 23");
+    assert_eq!(show("(fact(22))^2 / fact(22)"), "1124000727777607680000");
+    assert_eq!(ev("fact(25) * 1.0"), 1.5511210043330986e25.into());
+    assert_eq!(ev("fib(-6)"), (-8).into());
+    assert_eq!(show("abs(-fact(25))"), "15511210043330985984000000");
+    assert_eq!(try_ev("fact(-3)").map_err(|e| e.0), Err(Error::Domain));
+    assert_eq!(try_ev("fact(100000)").map_err(|e| e.0), Err(Error::Domain));
+    assert_eq!(try_ev("fib(2^62)").map_err(|e| e.0), Err(Error::Domain));
+}
+
+#[test]
+fn special_functions() {
+    assert_eq!(ev("gamma(5)"), 24.0.into());
+    assert_eq!(ev("gamma, egamma"), 0.577_215_664_901_532_9.into());
+    assert_eq!(ev("beta(2, 3) * 12"), 1.0.into());
+    assert_eq!(ev("erf(0) + erfc(0)"), 1.0.into());
+    assert_eq!(ev("zeta(-2)"), 0.0.into());
+    assert_eq!(ev("besselj(0, 0)"), 1.0.into());
+    assert_eq!(try_ev("gamma(-2)").map_err(|e| e.0), Err(Error::Domain));
+    assert_eq!(try_ev("zeta(1)").map_err(|e| e.0), Err(Error::Domain));
+    assert_eq!(try_ev("bessely(1, 0)").map_err(|e| e.0), Err(Error::Domain));
+    // Orders far above the argument underflow, and huge orders are refused
+    assert_eq!(ev("besselj(1000, 1)"), 0.0.into());
+    assert_eq!(
+        try_ev("besselj(10^12, 1)").map_err(|e| e.0),
+        Err(Error::Domain)
+    );
+}
+
+#[test]
+fn lists() {
+    assert_eq!(show("[1, 2, 3] * 2"), "[2, 4, 6]");
+    assert_eq!(show("[1, 2] + [1 / 2, 3]"), "[3 / 2, 5]");
+    assert_eq!(show("v = [3, 4], v / norm(v)"), "[0.6, 0.8]");
+    assert_eq!(ev("v = [1, 2, 3], v[0] + v[2]"), 4.into());
+    assert_eq!(ev("[[1, 2], [3, 4]][1][0]"), 3.into());
+    assert_eq!(ev("sum([1, 2, 3]^2)"), 14.into());
+    assert_eq!(show("cross([1, 0, 0], [0, 1, 0])"), "[0, 0, 1]");
+    assert_eq!(show("sum([1 m, 20 mm])"), "1.02 m");
+    let err = try_ev("[1, 2] + [1, 2, 3]").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Length, 8));
+    let err = try_ev("[1, 2][2]").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Domain, 8));
+}
+
+#[test]
+fn matrices() {
+    assert_eq!(
+        show("[[1, 2], [3, 4]] * [[0, 1], [1, 0]]"),
+        "[2  1]\n[4  3]"
+    );
+    assert_eq!(show("[[1, 2], [3, 4]] * [1, 1]"), "[3, 7]");
+    assert_eq!(
+        show("[[1, 2], [3, 4]]^(-1)"),
+        "[   -2       1]\n[3 / 2  -1 / 2]"
+    );
+    assert_eq!(
+        show("A = [[1, 2], [3, 4]], A * inv(A) - identity(2)"),
+        "[0  0]\n[0  0]"
+    );
+    assert_eq!(ev("det([[2, 0, 1], [1, 3, 2], [1, 1, 2]])"), 6.into());
+    assert_eq!(show("solve([[2, 1], [1, 3]], [3, 5])"), "[4 / 5, 7 / 5]");
+    assert_eq!(ev("rank([[1, 2, 3], [2, 4, 6]])"), 1.into());
+    assert_eq!(show("[[1, 2], [3]]"), "[[1, 2], [3]]");
+    let err = try_ev("[[1, 2]] + [[1], [2]]").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Shape, 10));
+    let err = try_ev("[[1, 2]] * [[1, 2]]").unwrap_err();
+    assert_eq!(err.0, Error::Shape);
+}
+
+#[test]
+fn statistics() {
+    assert_eq!(show("mean([1, 2, 3, 4])"), "5 / 2");
+    assert_eq!(show("var([2, 4, 4, 4, 5, 5, 7, 9])"), "32 / 7");
+    assert_eq!(ev("pstdev([2, 4, 4, 4, 5, 5, 7, 9])"), 2.0.into());
+    assert_eq!(show("quantile([1, 2, 3, 4], 1 / 4)"), "7 / 4");
+    assert_eq!(show("linreg([1, 2, 3], [2, 4, 7])"), "[5 / 2, -2 / 3]");
+    assert_eq!(ev("corr([1, 2, 3], [2, 4, 6])"), 1.0.into());
+    assert_eq!(ev("min(4, 2, 8)"), 2.into());
+    assert_eq!(show("30 s to min"), "0.5 min");
+    let err = try_ev("cov([1, 2], [1])").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Length, 13));
+    let err = try_ev("median([])").unwrap_err();
+    assert_eq!(err.0, Error::Domain);
+}
+
+#[test]
+fn ranges_and_sequences() {
+    assert_eq!(show("1..5"), "[1, 2, 3, 4, 5]");
+    assert_eq!(ev("sum(1..100)"), 5050.into());
+    assert_eq!(show("n = 2, 1 / n..n + 1"), "[1 / 2, 3 / 2, 5 / 2]");
+    assert_eq!(show("range(0, 1, 1 / 4)"), "[0, 1 / 4, 1 / 2, 3 / 4, 1]");
+    assert_eq!(show("seq(x -> x^2, 1, 5)"), "[1, 4, 9, 16, 25]");
+    assert_eq!(ev("f = (x, y) -> x * y, f(3, 4)"), 12.into());
+    // A function captures the variables of the context when it is defined
+    assert_eq!(ev("a = 2, k = x -> a * x, k(2)"), 4.into());
+    assert_eq!(ev("a = 2, k = x -> a * x, a = 5, k(2)"), 4.into());
+    assert_eq!(show("a = 2, x -> a * x + 1"), "x -> 2 * x + 1");
+    assert_eq!(ev("f = x -> (a = 1, a * x), a = 5, f(3)"), 3.into());
+    assert_eq!(show("map(x -> y -> x + y, [1])"), "[y -> 1 + y]");
+    assert_eq!(ev("f = x -> x + 1, g = x -> f(x) * 2, g(3)"), 8.into());
+    assert_eq!(
+        ev("f = x -> x + 1, g = x -> f(x) * 2, f = x -> 0, g(3)"),
+        8.into()
+    );
+    // A function is called with the options of the context
+    let mut ctx = Context {
+        angle: AngleMeasure::Degrees,
+        ..Default::default()
+    };
+    let expr = parse(lex("h = x -> sin(x), h(90)", None)).unwrap();
+    assert_eq!(eval(&expr, &mut ctx), Ok(1.into()));
+    assert_eq!(show("mean(seq(k -> 1 / k, 1, 4))"), "25 / 48");
+    let err = try_ev("range(1, 2, 0)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Domain, 13));
+    let err = try_ev("(1, x) -> x").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Syntax, 2));
+}
+
+#[test]
+fn higher_order_functions() {
+    assert_eq!(show("map(x -> x^2, [1, 2, 3])"), "[1, 4, 9]");
+    assert_eq!(show("map(sin, [0, pi / 2])"), "[0, 1]");
+    assert_eq!(
+        show("filter(isprime, 1..20)"),
+        "[2, 3, 5, 7, 11, 13, 17, 19]"
+    );
+    assert_eq!(ev("reduce((a, b) -> a * b, 1..5)"), 120.into());
+    assert_eq!(ev("reduce((a, b) -> a + b, [], 7)"), 7.into());
+    assert_eq!(ev("h = compose(x -> x + 1, x -> 2 * x), h(5)"), 11.into());
+    assert_eq!(ev("h = compose(abs, sin), h(-pi / 2)"), 1.into());
+    assert_eq!(ev("g = fact, g(5)"), 120.into());
+    assert_eq!(show("seq(fib, 1, 6)"), "[1, 1, 2, 3, 5, 8]");
+    assert_eq!(show("abs"), "abs");
+    let err = try_ev("map(2, [1])").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Type, 5));
+}
+
+#[test]
+fn summation() {
+    assert_eq!(ev("sum(k, 1, 100, k^2)"), 338350.into());
+    assert_eq!(ev("n = 5, prod(k, 1, n, 1 + 1 / k)"), 6.into());
+    assert_eq!(show("sum(k, 1, 10, 1 / k)"), "7381 / 2520");
+    assert_eq!(show("sum(k, 1, 3, pi)"), "3 pi (\u{2248} 9.42477796076938)");
+    assert_eq!(ev("sum(i, 1, 3, sum(j, 1, i, i * j))"), 25.into());
+    assert_eq!(ev("k = 7, sum(k, 1, 3, k), k"), 7.into());
+    assert_eq!(ev("sum([1, 2, 3])"), 6.into());
+    assert_eq!(ev("f = n -> prod(k, 1, n, k), f(6)"), 720.into());
+    let err = try_ev("sum(k, 1, 2.5, k)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Type, 11));
+    let err = try_ev("sum(k, 1, 10^7, k)").unwrap_err();
+    assert_eq!(err.0, Error::Domain);
+}
+
+#[test]
+fn integration() {
+    let approx = |s: &str, y: f64| {
+        let x = ev(s).as_f64().unwrap();
+        assert!((x - y).abs() < 1e-9, "{} = {}, not {}", s, x, y);
+    };
+    approx("integrate(x -> x^2, 0, 3)", 9.0);
+    approx("q = quad(x -> x^2, 0, 3), q[0]", 9.0);
+    let err = ev("q = quad(x -> x^(1 / 2), 0, 1), q[1]").as_f64().unwrap();
+    assert!(err > 0.0 && err < 1e-9, "{}", err);
+    approx("integrate(sin, 0, pi)", 2.0);
+    approx("integrate(x, 0, pi, sin(x))", 2.0);
+    approx("a = 3, integrate(t, 0, 1, a * t^2)", 1.0);
+    approx("integrate(x, 0, inf, e^(-x^2))", consts::PI.sqrt() / 2.0);
+    approx("integrate(x, -inf, inf, 1 / (1 + x^2))", consts::PI);
+    approx("integrate(x, 1, 0, x^(-1 / 2))", -2.0);
+    assert_eq!(ev("x = 5, integrate(x, 0, 1, x), x"), 5.into());
+    let err = try_ev("integrate(x, 0, 1, 1 / x)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Convergence, 1));
+    let err = try_ev("integrate(x, 0, 1, [x])").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Type, 20));
+}
+
+#[test]
+fn root_finding() {
+    let approx = |s: &str, y: f64| {
+        let x = ev(s).as_f64().unwrap();
+        assert!((x - y).abs() < 1e-12, "{} = {}, not {}", s, x, y);
+    };
+    approx("solve(x^2 = 2, x)", consts::SQRT_2);
+    approx("solve(x^2 = 2, x, -1)", -consts::SQRT_2);
+    approx("solve(x^3 - x = 1, x, 1, 2)", 1.324717957244746);
+    approx("solve(x = cos(x), x)", 0.7390851332151607);
+    approx("solve(x -> cos(x) - x, 0, 1)", 0.7390851332151607);
+    approx("f = x -> x^2 - 2, solve(f, 1)", consts::SQRT_2);
+    approx("a = 3, solve(2^t = a, t)", 3f64.log2());
+    assert_eq!(ev("x = 5, solve(x = 2, x), x"), 5.into());
+    // The linear system solver is unaffected
+    assert_eq!(
+        ev("solve([[2, 1], [1, 3]], [5, 10])"),
+        Value::List(vec![1.into(), 3.into()])
+    );
+    let err = try_ev("solve(x^2 = -1, x)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Convergence, 1));
+    let err = try_ev("solve(x^2 = 2, x, 2, 3)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Domain, 1));
+    let err = try_ev("solve(x^2 = 2, 3)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Syntax, 16));
+    let err = try_ev("1 + 1 = 2").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Syntax, 7));
+}
+
+#[test]
+fn differentiation() {
+    let approx = |s: &str, y: f64| {
+        let x = ev(s).as_f64().unwrap();
+        assert!((x - y).abs() < 1e-12, "{} = {}, not {}", s, x, y);
+    };
+    assert_eq!(ev("d = diff(x^3, x), d(2)"), 12.into());
+    assert_eq!(ev("d = diff(x^3 + 5 * x, x), d(1 / 2)"), (23, 4).into());
+    assert_eq!(ev("f = t -> t^2, d = diff(f(x) + x, x), d(3)"), 7.into());
+    assert_eq!(ev("f = t -> t^2, g = diff(f), g(5)"), 10.into());
+    approx("g = diff(sin), g(0)", 1.0);
+    approx("g = diff(x -> x * ln(x)), g(e)", 2.0);
+    approx("d = diff(2^x, x), d(3)", 8.0 * consts::LN_2);
+    assert_eq!(ev("x = 5, diff(x^2, x), x"), 5.into());
+    // Variables of the context are constants of the derivative
+    assert_eq!(ev("a = 3, h = diff(a * x^2, x), h(1)"), 6.into());
+    assert_eq!(show("a = 3, diff(a * x^2, x)"), "x -> 6 * x");
+    let err = try_ev("diff(x^2, 2)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Syntax, 11));
+    let err = try_ev("diff(gamma(x), x)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::NotDifferentiable, 6));
+    let err = try_ev("diff((x, y) -> x * y)").unwrap_err();
+    assert_eq!(err.0, Error::Type);
+}
+
+#[test]
+fn simplification() {
+    assert_eq!(ev("f = simplify(2 * x + 3 * x), f(2)"), 10.into());
+    assert_eq!(ev("f = simplify(x * y / x), f(3, 4)"), 4.into());
+    assert_eq!(ev("f = simplify(t -> t^2 / t), f(7)"), 7.into());
+    assert_eq!(
+        ev("g = x -> (x + 1) * 2 - 2, f = simplify(g), f(5)"),
+        10.into()
+    );
+    assert_eq!(ev("simplify(2^(-1) + 1 / 2)"), 1.into());
+    assert_eq!(
+        ev("f = x -> x + x, h = map(simplify, [f]), g = h[0], g(4)"),
+        8.into()
+    );
+    match ev("simplify(x - x)") {
+        Value::Func(params, body) => {
+            assert_eq!(params, vec!["x".to_string()]);
+            assert_eq!(body.0, 0.into());
+        }
+        val => panic!("{:?}", val),
+    }
+    let err = try_ev("simplify(x, y)").unwrap_err();
+    assert_eq!((err.0, err.1.start), (Error::Syntax, 1));
+}
+
+#[test]
+fn si_prefix_literals() {
+    assert_eq!(ev("4.7k * 2"), 9400.0.into());
+    assert_eq!(ev("1k + 1"), 1001.into());
+    assert_eq!(ev("n = 3, 2n"), 2e-9.into());
+}
+
+fn show(s: &str) -> String {
+    let ctx = Context::default();
+    let val = ev(s);
+    let s = ctx.display(&val).to_string();
+    s
+}
+
+#[test]
+fn units() {
+    assert_eq!(show("3 km + 500 m"), "3.5 km");
+    assert_eq!(show("36 km/h to m/s"), "10 m/s");
+    assert_eq!(show("2 h to min"), "120 min");
+    assert_eq!(show("1 inch in mm"), "25.4 mm");
+    assert_eq!(show("2 m * 3 m"), "6 m*m");
+    assert_eq!(show("(2 m * 3 m) to m^2"), "6 m^2");
+    assert_eq!(show("10 kg * 9.8 m / s^2"), "98 kg*m/s^2");
+    assert_eq!(show("10 kg * 9.8 m / s^2 to N"), "98 N");
+    assert_eq!(show("1 km / 1 m"), "1000");
+    assert_eq!(show("abs(-2 s)"), "2 s");
+    assert_eq!(show("x = 5 km to m, x"), "5000 m");
+    // Units and variables have separate names
+    assert_eq!(show("m = 2, 3 m"), "3 m");
+    assert_eq!(show("m = 2, 3 * m"), "6");
+    assert_eq!(show("1m"), "1 m");
+    assert_eq!(show("1m * 2"), "2 m");
+    assert_eq!(show("1m to mm"), "1000 mm");
+    assert_eq!(show("2 m to cm"), "200 cm");
+    // The unit of a fraction applies to the whole fraction
+    assert_eq!(show("1/2 m"), "0.5 m");
+    assert_eq!(show("x = 3, x / 2 m"), "1.5 1/m");
+    assert_eq!(show("(2 m) to cm"), "200 cm");
+}
+
+#[test]
+fn unit_errors() {
+    assert_eq!(
+        try_ev("t + 1").map_err(|e| e.0),
+        Err(Error::Undefined("t".to_string()))
+    );
+    assert_eq!(try_ev("1 m + 1 s").map_err(|e| e.0), Err(Error::Dimension));
+    assert_eq!(try_ev("1 m + 1").map_err(|e| e.0), Err(Error::Dimension));
+    assert_eq!(try_ev("1 m to s").map_err(|e| e.0), Err(Error::Dimension));
+    assert_eq!(try_ev("1 m to 2").map_err(|e| e.0), Err(Error::Type));
+    assert_eq!(
+        try_ev("sqrt_m = 1 m ^ 0.5").map_err(|e| e.0),
+        Err(Error::Dimension)
+    );
+    assert_eq!(try_ev("sin(1 m)").map_err(|e| e.0), Err(Error::Type));
+    // Exponents of the dimension that overflow
+    assert_eq!(
+        try_ev("(1 m)^100 * (1 m)^100").map_err(|e| e.0),
+        Err(Error::Dimension)
+    );
+    assert_eq!(
+        try_ev("(1 m^2)^4611686018427387904").map_err(|e| e.0),
+        Err(Error::Dimension)
+    );
+}
+
+#[test]
+fn symbolic_constants() {
+    assert_eq!(ev("sin(pi)"), 0.into());
+    assert_eq!(ev("sin(pi/6)"), (1, 2).into());
+    assert_eq!(ev("cos(2*pi/3)"), (-1, 2).into());
+    assert_eq!(ev("tan(-pi/4)"), (-1).into());
+    assert_eq!(try_ev("tan(pi/2)").map_err(|e| e.0), Err(Error::Domain));
+    assert_eq!(ev("2*pi/pi"), 2.into());
+    assert_eq!(ev("pi - pi"), 0.into());
+    assert_eq!(ev("(pi^2)^(1/2) / pi"), 1.into());
+    assert_eq!(ev("e^2 / e"), ev("e"));
+    assert_eq!(ev("pi + 1"), (consts::PI + 1.0).into());
+    assert_eq!(ev("pi * 0.5"), (consts::PI * 0.5).into());
+    assert_eq!(ev("sin(pi/5)"), (consts::PI / 5.0).sin().into());
+    assert_eq!(show("2*pi/3"), "2 pi / 3 (\u{2248} 2.0943951023931953)");
+    assert_eq!(show("-pi"), "-pi (\u{2248} -3.141592653589793)");
+    assert_eq!(show("e^2"), "e^2 (\u{2248} 7.3890560989306495)");
+    assert_eq!(show("3/(2*pi)"), "3 / (2 pi) (\u{2248} 0.477464829275686)");
+    assert_eq!(show("pi^(1/2)"), "pi^(1/2) (\u{2248} 1.7724538509055159)");
+}
+
+#[test]
+fn constants_library() {
+    assert_eq!(ev("tau / pi"), 2.into());
+    assert_eq!(ev("phi^2 - phi"), 1.0.into());
+    assert_eq!(show("phys.c"), "2.99792458e+8 m/s");
+    assert_eq!(show("phys.c * 1 s to km"), "299792.458 km");
+    assert_eq!(
+        show("phys.NA * phys.k"),
+        "8.31446261815324 m^2*kg/(s^2*K*mol)"
+    );
+    assert_eq!(show("-inf"), "-inf");
+    assert_eq!(show("1 / 0"), "inf");
+    assert_eq!(show("0 / 0"), "NaN");
+}
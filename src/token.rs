@@ -23,10 +23,19 @@ pub enum Token<'a> {
     LeftParen,
     /// A right parenthesis `)`.
     RightParen,
+    /// A left square bracket `[`.
+    LeftBracket,
+    /// A right square bracket `]`.
+    RightBracket,
     /// An equal sign `=`.
     Equals,
     /// A comma `,`.
     Comma,
+    /// A range operator `..`.
+    DotDot,
+    /// An arrow `->`, which separates the parameters of a function from its
+    /// body.
+    Arrow,
     /// End of file or input.
     Eof,
 }
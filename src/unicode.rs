@@ -0,0 +1,666 @@
+//! Render expressions and values in a multi-line textbook layout, with
+//! stacked fractions, raised exponents and tall brackets.
+
+use std::cmp::max;
+
+use num::rational::Ratio;
+use num::traits::{One, Signed, Zero};
+use num::BigRational;
+
+use crate::format::{quantity_options, FloatFormat};
+use crate::latex::{greek_letter, level};
+use crate::parser::unit_name;
+use crate::printer::{COMMA, CONVERT, INDEX, LAMBDA, LET, PREFIX, PRODUCT, SUM};
+use crate::{Constant, Context, Expression, Function, Span, Symbolic, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// Rendering as lines of Unicode text.
+pub trait ToUnicode {
+    /// Render in a 2-D layout, writing floats with the context's display
+    /// options. Lines are separated by newlines.
+    fn to_unicode(&self, ctx: &Context) -> String;
+}
+
+impl ToUnicode for Expression {
+    fn to_unicode(&self, ctx: &Context) -> String {
+        expr_block(self, ctx).render()
+    }
+}
+
+impl ToUnicode for Value {
+    fn to_unicode(&self, ctx: &Context) -> String {
+        value_block(self, ctx).render()
+    }
+}
+
+const MINUS: &str = "\u{2212}";
+const DOT: &str = "\u{22c5}";
+
+/// A rectangle of text, with every line padded to the same width.
+#[derive(Clone, Debug)]
+struct Block {
+    lines: Vec<String>,
+    /// The row that lines up with the rows of the blocks beside it.
+    baseline: usize,
+}
+
+fn blank(width: usize) -> String {
+    " ".repeat(width)
+}
+
+impl Block {
+    fn text(s: &str) -> Block {
+        Block {
+            lines: vec![s.to_string()],
+            baseline: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.lines[0].chars().count()
+    }
+
+    fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Place another block to the right, lining up the baselines.
+    fn beside(self, other: Block) -> Block {
+        let above = max(self.baseline, other.baseline);
+        let below = max(
+            self.height() - self.baseline,
+            other.height() - other.baseline,
+        );
+        let grow = |b: Block| {
+            let w = b.width();
+            let mut lines = vec![blank(w); above - b.baseline];
+            lines.extend(b.lines);
+            lines.resize(above + below, blank(w));
+            lines
+        };
+        let lines = grow(self)
+            .into_iter()
+            .zip(grow(other))
+            .map(|(a, b)| a + &b)
+            .collect();
+        Block {
+            lines,
+            baseline: above,
+        }
+    }
+
+    fn then(self, s: &str) -> Block {
+        self.beside(Block::text(s))
+    }
+
+    /// Pad to a width, keeping the text in the middle.
+    fn center(self, width: usize) -> Block {
+        let left = (width - self.width()) / 2;
+        let right = width - self.width() - left;
+        Block {
+            lines: self
+                .lines
+                .into_iter()
+                .map(|l| blank(left) + &l + &blank(right))
+                .collect(),
+            baseline: self.baseline,
+        }
+    }
+
+    /// Join the lines, without the spaces at their ends.
+    fn render(&self) -> String {
+        let lines: Vec<_> = self.lines.iter().map(|l| l.trim_end()).collect();
+        lines.join("\n")
+    }
+}
+
+/// Place three blocks above each other, centered, with the baseline of the
+/// middle one.
+fn stack(top: Block, mid: Block, bottom: Block) -> Block {
+    let width = max(top.width(), max(mid.width(), bottom.width()));
+    let baseline = top.height() + mid.baseline;
+    let mut lines = top.center(width).lines;
+    lines.extend(mid.center(width).lines);
+    lines.extend(bottom.center(width).lines);
+    Block { lines, baseline }
+}
+
+fn fraction(numer: Block, denom: Block) -> Block {
+    let width = max(numer.width(), denom.width()) + 2;
+    stack(numer, Block::text(&"\u{2500}".repeat(width)), denom)
+}
+
+fn join(blocks: impl IntoIterator<Item = Block>, sep: &str) -> Block {
+    let mut result: Option<Block> = None;
+    for b in blocks {
+        result = Some(match result {
+            Some(r) => r.then(sep).beside(b),
+            None => b,
+        });
+    }
+    result.unwrap_or_else(|| Block::text(""))
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '\u{2070}',
+        '1' => '\u{b9}',
+        '2' => '\u{b2}',
+        '3' => '\u{b3}',
+        '4'..='9' => char::from_u32(0x2070 + c as u32 - '0' as u32)?,
+        '+' => '\u{207a}',
+        '-' | '\u{2212}' => '\u{207b}',
+        '=' => '\u{207c}',
+        '(' => '\u{207d}',
+        ')' => '\u{207e}',
+        'i' => '\u{2071}',
+        'n' => '\u{207f}',
+        'k' => '\u{1d4f}',
+        'x' => '\u{2e3}',
+        'y' => '\u{2b8}',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0'..='9' => char::from_u32(0x2080 + c as u32 - '0' as u32)?,
+        '+' => '\u{208a}',
+        '-' | '\u{2212}' => '\u{208b}',
+        '=' => '\u{208c}',
+        '(' => '\u{208d}',
+        ')' => '\u{208e}',
+        'a' => '\u{2090}',
+        'e' => '\u{2091}',
+        'o' => '\u{2092}',
+        'x' => '\u{2093}',
+        'h' => '\u{2095}',
+        'k' => '\u{2096}',
+        'l' => '\u{2097}',
+        'm' => '\u{2098}',
+        'n' => '\u{2099}',
+        'p' => '\u{209a}',
+        's' => '\u{209b}',
+        't' => '\u{209c}',
+        'i' => '\u{1d62}',
+        'r' => '\u{1d63}',
+        'u' => '\u{1d64}',
+        'v' => '\u{1d65}',
+        'j' => '\u{2c7c}',
+        _ => return None,
+    })
+}
+
+/// Convert a line of text to small raised or lowered characters, dropping
+/// spaces, if there is a character for each one.
+fn small(s: &str, convert: fn(char) -> Option<char>) -> Option<String> {
+    s.chars().filter(|&c| c != ' ').map(convert).collect()
+}
+
+/// Raise an exponent after a base, with superscript characters if it is a
+/// line that has them.
+fn superscript(base: Block, exp: Block) -> Block {
+    if exp.height() == 1 {
+        if let Some(s) = small(&exp.lines[0], superscript_char) {
+            return base.then(&s);
+        }
+    }
+    let (bw, ew) = (base.width(), exp.width());
+    let mut lines: Vec<_> = exp.lines.into_iter().map(|l| blank(bw) + &l).collect();
+    let baseline = lines.len() + base.baseline;
+    lines.extend(base.lines.into_iter().map(|l| l + &blank(ew)));
+    Block { lines, baseline }
+}
+
+/// The pieces of a bracket of a height, from top to bottom.
+fn tall(delim: &str, height: usize) -> Vec<String> {
+    let (top, mid, bottom) = match delim {
+        "(" => ('\u{239b}', '\u{239c}', '\u{239d}'),
+        ")" => ('\u{239e}', '\u{239f}', '\u{23a0}'),
+        "[" => ('\u{23a1}', '\u{23a2}', '\u{23a3}'),
+        "]" => ('\u{23a4}', '\u{23a5}', '\u{23a6}'),
+        "\u{230a}" => ('\u{23a2}', '\u{23a2}', '\u{23a3}'),
+        "\u{230b}" => ('\u{23a5}', '\u{23a5}', '\u{23a6}'),
+        "\u{2308}" => ('\u{23a1}', '\u{23a2}', '\u{23a2}'),
+        "\u{2309}" => ('\u{23a4}', '\u{23a5}', '\u{23a5}'),
+        "|" => ('\u{2502}', '\u{2502}', '\u{2502}'),
+        _ => ('\u{2016}', '\u{2016}', '\u{2016}'),
+    };
+    (0..height)
+        .map(|i| match i {
+            0 => top,
+            _ if i + 1 == height => bottom,
+            _ => mid,
+        })
+        .map(String::from)
+        .collect()
+}
+
+/// Surround a block with brackets as tall as it is.
+fn delimit(inner: Block, open: &str, close: &str) -> Block {
+    if inner.height() == 1 {
+        return Block::text(open).beside(inner).then(close);
+    }
+    let height = inner.height();
+    let lines = tall(open, height)
+        .into_iter()
+        .zip(inner.lines)
+        .zip(tall(close, height))
+        .map(|((o, l), c)| o + &l + &c)
+        .collect();
+    Block {
+        lines,
+        baseline: inner.baseline,
+    }
+}
+
+/// Write a radical sign with a bar over the radicand.
+fn radical(body: Block, index: Option<Block>) -> Block {
+    let index = match index {
+        Some(n) if n.height() == 1 => {
+            small(&n.lines[0], superscript_char).unwrap_or_else(|| n.render())
+        }
+        _ => String::new(),
+    };
+    let pad = index.chars().count();
+    let height = body.height();
+    let mut lines = vec![blank(pad + 1) + &"_".repeat(body.width())];
+    for (i, line) in body.lines.into_iter().enumerate() {
+        lines.push(if i + 1 == height {
+            format!("{}\u{221a}{}", index, line)
+        } else {
+            format!("{}\u{2502}{}", blank(pad), line)
+        });
+    }
+    Block {
+        lines,
+        baseline: body.baseline + 1,
+    }
+}
+
+/// Render an expression, in parentheses if it is below the `level` of the
+/// grammar.
+fn group(expr: &Expression, ctx: &Context, min: u8) -> Block {
+    if level(expr) < min {
+        delimit(expr_block(expr, ctx), "(", ")")
+    } else {
+        expr_block(expr, ctx)
+    }
+}
+
+/// Render expressions separated by commas.
+fn items<'a>(v: impl IntoIterator<Item = &'a Expression>, ctx: &Context) -> Block {
+    join(v.into_iter().map(|e| group(e, ctx, LET)), ", ")
+}
+
+/// Render a name with Greek letters and any subscript after an underscore,
+/// as in `x₁`.
+fn name_text(name: &str) -> String {
+    let letter = |s: &str| match greek_letter(s) {
+        Some(ch) => ch.to_string(),
+        None => s.to_string(),
+    };
+    match name.split_once('_') {
+        Some((base, sub)) if !base.is_empty() && !sub.is_empty() => {
+            match small(sub, subscript_char) {
+                Some(sub) => letter(base) + &sub,
+                None => format!("{}_{}", letter(base), letter(sub)),
+            }
+        }
+        _ => letter(name),
+    }
+}
+
+fn const_block(con: Constant) -> Block {
+    match con {
+        Constant::Sqrt2 => radical(Block::text("2"), None),
+        _ => Block::text(&name_text(con.symbol())),
+    }
+}
+
+/// Test whether an expression is rendered starting with a number, so that
+/// it needs a visible multiplication sign after another factor.
+fn starts_with_number(expr: &Expression) -> bool {
+    use Expression::*;
+    match expr {
+        Val(Value::Func(..) | Value::Builtin(_) | Value::List(_) | Value::Matrix(_)) => false,
+        Val(_) | Neg(_) => true,
+        Mul(v) => starts_with_number(&v[0].0),
+        Exp(args) | Index(args) => level(&args[0].0) >= INDEX && starts_with_number(&args[0].0),
+        _ => false,
+    }
+}
+
+/// Render a function of the named parameters, such as `x ↦ x²`.
+fn lambda_block(params: &[String], body: &Expression, ctx: &Context) -> Block {
+    let params: Vec<_> = params.iter().map(|p| name_text(p)).collect();
+    let params = if params.len() == 1 {
+        params[0].clone()
+    } else {
+        format!("({})", params.join(", "))
+    };
+    Block::text(&params)
+        .then(" \u{21a6} ")
+        .beside(group(body, ctx, LAMBDA))
+}
+
+/// Render a call to a function given its name and arguments.
+fn call_block(name: &str, args: Block) -> Block {
+    Block::text(name).beside(delimit(args, "(", ")"))
+}
+
+/// Render a call to a built-in function.
+fn func_block(func: Function, arg: &Expression, ctx: &Context) -> Block {
+    let inner = || expr_block(arg, ctx);
+    match func {
+        Function::Abs => delimit(inner(), "|", "|"),
+        Function::Floor => delimit(inner(), "\u{230a}", "\u{230b}"),
+        Function::Ceil => delimit(inner(), "\u{2308}", "\u{2309}"),
+        Function::Norm => delimit(inner(), "\u{2016}", "\u{2016}"),
+        Function::Fact => group(arg, ctx, INDEX).then("!"),
+        Function::Choose => match arg {
+            Expression::Comma(v) if v.len() == 2 => {
+                let (n, k) = (expr_block(&v[0].0, ctx), expr_block(&v[1].0, ctx));
+                let mut binom = stack(n, Block::text(""), k);
+                binom.lines.retain(|l| !l.trim().is_empty());
+                binom.baseline = binom.height() / 2;
+                delimit(binom, "(", ")")
+            }
+            _ => call_block(&func.to_string(), inner()),
+        },
+        _ => call_block(&func.to_string(), inner()),
+    }
+}
+
+/// Render a sum or product over an index variable.
+fn series_block(op: &str, var: &str, args: &[(Expression, Span); 3], ctx: &Context) -> Block {
+    let [start, end, body] = args;
+    let under = Block::text(&name_text(var))
+        .then(" = ")
+        .beside(expr_block(&start.0, ctx));
+    stack(expr_block(&end.0, ctx), Block::text(op), under)
+        .then(" ")
+        .beside(group(&body.0, ctx, PRODUCT))
+}
+
+fn expr_block(expr: &Expression, ctx: &Context) -> Block {
+    use Expression::*;
+    match expr {
+        Val(val) => value_block(val, ctx),
+        // A unit, such as the `km/h` in `x to km/h`
+        Exp(_) | Mul(_) | Frac(_) if unit_name(expr).is_some() => {
+            Block::text(&unit_text(&unit_name(expr).unwrap()))
+        }
+        Neg(e) => Block::text(MINUS).beside(group(&e.0, ctx, PREFIX)),
+        Add(v) => {
+            let mut b = group(&v[0].0, ctx, SUM);
+            for (term, _) in &v[1..] {
+                // Write `a + -b` as `a - b`
+                b = match term {
+                    Neg(e) => b
+                        .then(&format!(" {} ", MINUS))
+                        .beside(group(&e.0, ctx, PRODUCT)),
+                    _ => b.then(" + ").beside(group(term, ctx, PRODUCT)),
+                };
+            }
+            b
+        }
+        Sub(args) => group(&args[0].0, ctx, SUM)
+            .then(&format!(" {} ", MINUS))
+            .beside(group(&args[1].0, ctx, PRODUCT)),
+        Mul(v) => {
+            let mut b = group(&v[0].0, ctx, PRODUCT);
+            for (factor, _) in &v[1..] {
+                // Juxtapose factors unless they would run together as a
+                // number
+                if level(factor) >= PREFIX && starts_with_number(factor) {
+                    b = b.then(&format!(" {} ", DOT));
+                } else {
+                    b = b.then(" ");
+                }
+                b = match unit_name(factor) {
+                    Some(unit) => b.then(&unit_text(&unit)),
+                    None => b.beside(group(factor, ctx, PREFIX)),
+                };
+            }
+            b
+        }
+        Frac(args) => fraction(expr_block(&args[0].0, ctx), expr_block(&args[1].0, ctx)),
+        Exp(args) => superscript(group(&args[0].0, ctx, INDEX), expr_block(&args[1].0, ctx)),
+        Root(args) => match &args[1].0 {
+            Val(Value::Ratio(n)) if *n == Ratio::from_integer(2) => {
+                radical(expr_block(&args[0].0, ctx), None)
+            }
+            n => radical(expr_block(&args[0].0, ctx), Some(expr_block(n, ctx))),
+        },
+        Const(con) => const_block(*con),
+        Func(func, e) => func_block(*func, &e.0, ctx),
+        Var(var) => Block::text(&name_text(var)),
+        Let(var, e) => Block::text(&name_text(var))
+            .then(" = ")
+            .beside(group(&e.0, ctx, LAMBDA)),
+        Call(name, args) => call_block(&name_text(name), items(args.iter().map(|a| &a.0), ctx)),
+        Lambda(params, body) => lambda_block(params, &body.0, ctx),
+        Convert(args) => {
+            group(&args[0].0, ctx, CONVERT)
+                .then(" \u{2192} ")
+                .beside(match unit_name(&args[1].0) {
+                    Some(unit) => Block::text(&unit_text(&unit)),
+                    None => group(&args[1].0, ctx, SUM),
+                })
+        }
+        List(v) => delimit(items(v.iter().map(|a| &a.0), ctx), "[", "]"),
+        Index(args) => {
+            group(&args[0].0, ctx, INDEX).beside(delimit(expr_block(&args[1].0, ctx), "[", "]"))
+        }
+        Range(args) => group(&args[0].0, ctx, CONVERT)
+            .then("\u{2026}")
+            .beside(group(&args[1].0, ctx, CONVERT)),
+        Sum(var, args) => series_block("\u{2211}", var, args, ctx),
+        Product(var, args) => series_block("\u{220f}", var, args, ctx),
+        Integral(var, args) => {
+            let [lower, upper, body] = args.as_ref();
+            stack(
+                expr_block(&upper.0, ctx),
+                Block::text("\u{222b}"),
+                expr_block(&lower.0, ctx),
+            )
+            .then(" ")
+            .beside(group(&body.0, ctx, SUM))
+            .then(&format!(" d{}", name_text(var)))
+        }
+        Equation(args) => group(&args[0].0, ctx, LAMBDA)
+            .then(" = ")
+            .beside(group(&args[1].0, ctx, LAMBDA)),
+        Solve(var, eq, guesses) => {
+            let var = Var(var.clone());
+            let args = std::iter::once(&eq.0).chain(std::iter::once(&var));
+            let args = items(args.chain(guesses.iter().map(|g| &g.0)), ctx);
+            call_block(&Function::Solve.to_string(), args)
+        }
+        Derivative(var, e) => fraction(
+            Block::text("d"),
+            Block::text(&format!("d{}", name_text(var))),
+        )
+        .then(" ")
+        .beside(group(&e.0, ctx, PREFIX)),
+        Simplify(e) => call_block(&Function::Simplify.to_string(), group(&e.0, ctx, COMMA)),
+        Comma(v) => items(v.iter().map(|a| &a.0), ctx),
+    }
+}
+
+/// Render a formatted number, with a minus sign for a leading hyphen.
+fn number(s: &str) -> Block {
+    match s.strip_prefix('-') {
+        Some(s) => Block::text(&format!("{}{}", MINUS, s)),
+        None => Block::text(s),
+    }
+}
+
+/// Render a fraction of two formatted integers, with the sign in front.
+fn int_fraction(numer: String, denom: String) -> Block {
+    match numer.strip_prefix('-') {
+        Some(numer) => Block::text(MINUS).beside(fraction(Block::text(numer), Block::text(&denom))),
+        None => fraction(Block::text(&numer), Block::text(&denom)),
+    }
+}
+
+/// Render a float, with scientific notation as a power of ten.
+fn float_block(ctx: &Context, num: f64) -> Block {
+    if num.is_nan() {
+        return Block::text("NaN");
+    } else if num.is_infinite() {
+        return Block::text(if num < 0.0 {
+            "\u{2212}\u{221e}"
+        } else {
+            "\u{221e}"
+        });
+    }
+    let s = FloatFormat { ctx, num }.to_string();
+    match s.split_once('e') {
+        Some((mantissa, exp)) => {
+            let exp = exp.trim_start_matches('+');
+            superscript(number(mantissa).then(" \u{d7} 10"), Block::text(exp))
+        }
+        None => number(&s),
+    }
+}
+
+/// Render a unit with superscript exponents, such as `m/s²`.
+fn unit_text(unit: &str) -> String {
+    let mut s = String::new();
+    let mut chars = unit.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => s.push_str(DOT),
+            '^' => {
+                let mut exp = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '-' && exp.is_empty() {
+                        exp.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                s += &small(&exp, superscript_char).unwrap_or(exp);
+            }
+            _ => s.push(ch),
+        }
+    }
+    s
+}
+
+/// Render an exact symbolic value, such as `2π/3` as a stacked fraction.
+fn symbolic_block(ctx: &Context, sym: Symbolic) -> Block {
+    let power = sym.power.abs();
+    let mut term = const_block(sym.base);
+    if !power.is_one() {
+        term = superscript(term, value_block(&Value::Ratio(power), ctx));
+    }
+    let numer = sym.coef.numer().abs();
+    let denom = *sym.coef.denom();
+    let int = |n: i64| {
+        number(
+            &ctx.display(&Value::Ratio(Ratio::from_integer(n)))
+                .to_string(),
+        )
+    };
+    let (top, bottom) = if sym.power > Ratio::zero() {
+        let top = if numer == 1 {
+            term
+        } else {
+            int(numer).beside(term)
+        };
+        (top, (denom != 1).then(|| int(denom)))
+    } else if denom == 1 {
+        (int(numer), Some(term))
+    } else {
+        (int(numer), Some(int(denom).beside(term)))
+    };
+    let b = match bottom {
+        Some(bottom) => fraction(top, bottom),
+        None => top,
+    };
+    if sym.coef.is_negative() {
+        Block::text(MINUS).beside(b)
+    } else {
+        b
+    }
+}
+
+fn value_block(val: &Value, ctx: &Context) -> Block {
+    match val {
+        Value::Ratio(r) => {
+            let int = |n| {
+                let val = Value::Ratio(Ratio::from_integer(n));
+                let s = ctx.display(&val).to_string();
+                s
+            };
+            let numer = int(*r.numer());
+            if r.is_integer() {
+                number(&numer)
+            } else {
+                int_fraction(numer, int(*r.denom()))
+            }
+        }
+        Value::BigRatio(r) => {
+            let int = |n| {
+                let val = Value::BigRatio(BigRational::from_integer(n));
+                let s = ctx.display(&val).to_string();
+                s
+            };
+            let numer = int(r.numer().clone());
+            if r.is_integer() {
+                number(&numer)
+            } else {
+                int_fraction(numer, int(r.denom().clone()))
+            }
+        }
+        Value::Float(num) => float_block(ctx, *num),
+        Value::Symbolic(sym) => symbolic_block(ctx, *sym),
+        Value::Quantity(q) => {
+            let (num, unit) = q.display_parts();
+            float_block(&quantity_options(ctx), num).then(&format!(" {}", unit_text(&unit)))
+        }
+        Value::Factors(factors) if factors.is_empty() => Block::text("1"),
+        Value::Factors(factors) => {
+            let factors = factors.iter().map(|&(p, e)| {
+                let p = ctx
+                    .display(&Value::Ratio(Ratio::from_integer(p)))
+                    .to_string();
+                let e = if e > 1 { e.to_string() } else { String::new() };
+                Block::text(&(p + &small(&e, superscript_char).unwrap_or_default()))
+            });
+            join(factors, &format!(" {} ", DOT))
+        }
+        Value::List(items) => delimit(
+            join(items.iter().map(|v| value_block(v, ctx)), ", "),
+            "[",
+            "]",
+        ),
+        Value::Matrix(m) => {
+            let cells: Vec<Vec<Block>> = (0..m.rows())
+                .map(|i| m.row(i).iter().map(|v| value_block(v, ctx)).collect())
+                .collect();
+            let widths: Vec<usize> = (0..m.cols())
+                .map(|j| cells.iter().map(|row| row[j].width()).max().unwrap_or(0))
+                .collect();
+            let mut lines = vec![];
+            for row in cells {
+                let row = row.into_iter().zip(&widths).map(|(cell, &w)| {
+                    let pad = w - cell.width();
+                    Block::text(&blank(pad)).beside(cell)
+                });
+                lines.extend(join(row, "  ").lines);
+            }
+            let baseline = (lines.len() - 1) / 2;
+            let grid = Block { lines, baseline };
+            delimit(Block::text(" ").beside(grid).then(" "), "[", "]")
+        }
+        Value::Func(params, body) => lambda_block(params, &body.0, ctx),
+        Value::Builtin(func) => Block::text(&func.to_string()),
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{eval, lex, parse, Context, ToUnicode};
+
+fn unicode(s: &str) -> String {
+    parse(lex(s, None))
+        .unwrap()
+        .0
+        .to_unicode(&Context::default())
+}
+
+fn result(s: &str) -> String {
+    let mut ctx = Context::default();
+    let val = eval(&parse(lex(s, None)).unwrap(), &mut ctx).unwrap();
+    val.to_unicode(&ctx)
+}
+
+#[test]
+fn lines() {
+    assert_eq!(unicode("(a + b) * c - 2 * -x"), "(a + b) c − 2 ⋅ −x");
+    assert_eq!(unicode("alpha + x_1 + y_ab"), "α + x₁ + y_ab");
+    assert_eq!(unicode("x^2 + e^(n + 1)"), "x² + eⁿ⁺¹");
+    assert_eq!(unicode("f = x -> x^2"), "f = x ↦ x²");
+    assert_eq!(unicode("3 km to m"), "3 km → m");
+    assert_eq!(unicode("abs(x - 1)"), "|x − 1|");
+}
+
+#[test]
+fn fractions() {
+    assert_eq!(unicode("1 / 2 + x"), " 1\n─── + x\n 2");
+    assert_eq!(unicode("(x + 1) / 2"), " x + 1\n───────\n   2");
+    assert_eq!(unicode("sin(x / 2)"), "   ⎛ x ⎞\nsin⎜───⎟\n   ⎝ 2 ⎠");
+    assert_eq!(unicode("(1 / 2)^2"), "⎛ 1 ⎞\n⎜───⎟²\n⎝ 2 ⎠");
+    assert_eq!(unicode("e^(x / 2)"), "  x\n ───\n  2\ne");
+    assert_eq!(unicode("diff(x^2, x)"), " d\n──── x²\n dx");
+}
+
+#[test]
+fn forms() {
+    assert_eq!(unicode("sum(k, 1, n, k^2)"), "  n\n  ∑   k²\nk = 1");
+    assert_eq!(unicode("integrate(x, 0, 1, x^2)"), "1\n∫ x² dx\n0");
+    assert_eq!(unicode("floor(x / 2)"), "⎢ x ⎥\n⎢───⎥\n⎣ 2 ⎦");
+    assert_eq!(unicode("choose(n, 2)"), "⎛n⎞\n⎝2⎠");
+}
+
+#[test]
+fn values() {
+    assert_eq!(result("-3 / 4"), "  3\n−───\n  4");
+    assert_eq!(result("1.5e-9"), "1.5 × 10⁻⁹");
+    assert_eq!(result("2 * pi / 3"), " 2π\n────\n 3");
+    assert_eq!(result("9.8 m/s^2"), "9.8 m/s²");
+    assert_eq!(result("factor(360)"), "2³ ⋅ 3² ⋅ 5");
+    assert_eq!(result("[1 / 2, 3]"), "⎡ 1    ⎤\n⎢───, 3⎥\n⎣ 2    ⎦");
+    assert_eq!(
+        result("[[1, 2], [3, 4 / 5]]"),
+        "⎡ 1    2 ⎤\n⎢     4  ⎥\n⎢ 3  ─── ⎥\n⎣     5  ⎦"
+    );
+}
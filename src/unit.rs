@@ -0,0 +1,375 @@
+//! Physical units and quantities with dimensional analysis.
+
+use num::rational::Ratio;
+use num::traits::CheckedMul;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{si_prefix_exponent, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// Exponents of the seven SI base units, in the order of `BASE_UNITS`.
+pub type Dimension = [i8; 7];
+
+/// The dimension of a plain number.
+pub const DIMENSIONLESS: Dimension = [0; 7];
+
+/// The symbols of the SI base units: length, mass, time, electric current,
+/// temperature, amount of substance, and luminous intensity.
+pub const BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+const LENGTH: Dimension = [1, 0, 0, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0, 0, 0];
+const CURRENT: Dimension = [0, 0, 0, 1, 0, 0, 0];
+const TEMPERATURE: Dimension = [0, 0, 0, 0, 1, 0, 0];
+const AMOUNT: Dimension = [0, 0, 0, 0, 0, 1, 0];
+const LUMINOSITY: Dimension = [0, 0, 0, 0, 0, 0, 1];
+const AREA: Dimension = [2, 0, 0, 0, 0, 0, 0];
+const VOLUME: Dimension = [3, 0, 0, 0, 0, 0, 0];
+const FREQUENCY: Dimension = [0, 0, -1, 0, 0, 0, 0];
+const SPEED: Dimension = [1, 0, -1, 0, 0, 0, 0];
+const FORCE: Dimension = [1, 1, -2, 0, 0, 0, 0];
+const ENERGY: Dimension = [2, 1, -2, 0, 0, 0, 0];
+const POWER: Dimension = [2, 1, -3, 0, 0, 0, 0];
+const PRESSURE: Dimension = [-1, 1, -2, 0, 0, 0, 0];
+const CHARGE: Dimension = [0, 0, 1, 1, 0, 0, 0];
+const VOLTAGE: Dimension = [2, 1, -3, -1, 0, 0, 0];
+const RESISTANCE: Dimension = [2, 1, -3, -2, 0, 0, 0];
+const CAPACITANCE: Dimension = [-2, -1, 4, 2, 0, 0, 0];
+
+/// The known units: symbol, size in SI base units, dimension, and whether
+/// the unit accepts SI prefixes.
+const UNITS: [(&str, f64, Dimension, bool); 43] = [
+    ("m", 1.0, LENGTH, true),
+    ("g", 1e-3, MASS, true),
+    ("s", 1.0, TIME, true),
+    ("A", 1.0, CURRENT, true),
+    ("K", 1.0, TEMPERATURE, true),
+    ("mol", 1.0, AMOUNT, true),
+    ("cd", 1.0, LUMINOSITY, true),
+    ("N", 1.0, FORCE, true),
+    ("J", 1.0, ENERGY, true),
+    ("W", 1.0, POWER, true),
+    ("Pa", 1.0, PRESSURE, true),
+    ("Hz", 1.0, FREQUENCY, true),
+    ("C", 1.0, CHARGE, true),
+    ("V", 1.0, VOLTAGE, true),
+    ("ohm", 1.0, RESISTANCE, true),
+    ("F", 1.0, CAPACITANCE, true),
+    ("L", 1e-3, VOLUME, true),
+    ("eV", 1.602176634e-19, ENERGY, true),
+    ("Wh", 3600.0, ENERGY, true),
+    ("bar", 1e5, PRESSURE, true),
+    ("inch", 0.0254, LENGTH, false),
+    ("ft", 0.3048, LENGTH, false),
+    ("yd", 0.9144, LENGTH, false),
+    ("mi", 1609.344, LENGTH, false),
+    ("nmi", 1852.0, LENGTH, false),
+    ("au", 1.495978707e11, LENGTH, false),
+    ("ly", 9.4607304725808e15, LENGTH, false),
+    ("ha", 1e4, AREA, false),
+    ("gal", 3.785411784e-3, VOLUME, false),
+    ("t", 1e3, MASS, false),
+    ("lb", 0.45359237, MASS, false),
+    ("oz", 0.028349523125, MASS, false),
+    ("min", 60.0, TIME, false),
+    ("h", 3600.0, TIME, false),
+    ("day", 86400.0, TIME, false),
+    ("week", 604800.0, TIME, false),
+    ("yr", 31557600.0, TIME, false),
+    ("mph", 0.44704, SPEED, false),
+    ("kn", 1852.0 / 3600.0, SPEED, false),
+    ("lbf", 4.4482216152605, FORCE, false),
+    ("cal", 4.184, ENERGY, false),
+    ("atm", 101325.0, PRESSURE, false),
+    ("psi", 6894.757293168361, PRESSURE, false),
+];
+
+/// Named units that are used to display quantities without a unit of their
+/// own, in preference to a combination of base units.
+const DERIVED_UNITS: [(&str, Dimension); 8] = [
+    ("N", FORCE),
+    ("J", ENERGY),
+    ("W", POWER),
+    ("Pa", PRESSURE),
+    ("C", CHARGE),
+    ("V", VOLTAGE),
+    ("ohm", RESISTANCE),
+    ("F", CAPACITANCE),
+];
+
+/// A unit in which a quantity is displayed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Unit {
+    /// The symbol or expression that names the unit, such as `km/h`.
+    pub name: String,
+    /// The size of the unit in SI base units.
+    pub factor: f64,
+}
+
+impl Unit {
+    /// Get the name, parenthesized if it contains any of the operators `ops`.
+    fn name_within(&self, ops: &[char]) -> String {
+        if self.name.contains(ops) {
+            format!("({})", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// A physical quantity with a dimension, such as `3 km`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quantity {
+    /// The magnitude in SI base units.
+    pub value: f64,
+    pub dim: Dimension,
+    /// The unit to display the quantity in, or `None` to use SI base units.
+    pub unit: Option<Unit>,
+}
+
+/// The SI prefixes that are not powers of a thousand, which are only used
+/// with units, with their symbols and powers of ten.
+const UNIT_PREFIXES: [(&str, i32); 4] = [("da", 1), ("h", 2), ("d", -1), ("c", -2)];
+
+/// Split a unit symbol into the power of ten of each prefix that it may
+/// start with and the rest of the symbol.
+fn split_prefix(name: &str) -> impl Iterator<Item = (i32, &str)> {
+    let first = name.chars().next().map_or(0, char::len_utf8);
+    let si = si_prefix_exponent(&name[..first]).map(|exp| (exp, &name[first..]));
+    let other = UNIT_PREFIXES
+        .iter()
+        .filter(move |(p, _)| name.starts_with(p))
+        .map(move |&(p, exp)| (exp, &name[p.len()..]));
+    si.into_iter().chain(other)
+}
+
+/// Parse a unit symbol, possibly with an SI prefix, such as `km` or `mph`.
+///
+/// The result is a quantity of one of that unit.
+pub fn parse_unit(name: &str) -> Option<Quantity> {
+    let found = UNITS
+        .iter()
+        .find(|u| u.0 == name)
+        .map(|&(_, f, d, _)| (f, d));
+    let found = found.or_else(|| {
+        // Try splitting off an SI prefix
+        split_prefix(name).find_map(|(exp, rest)| {
+            UNITS
+                .iter()
+                .find(|u| u.0 == rest && u.3)
+                .map(|&(_, f, d, _)| (f * 10_f64.powi(exp), d))
+        })
+    });
+    found.map(|(factor, dim)| Quantity {
+        value: factor,
+        dim,
+        unit: Some(Unit {
+            name: name.to_string(),
+            factor,
+        }),
+    })
+}
+
+/// Combine two dimensions by adding their exponents, each scaled by the
+/// given sign.
+///
+/// Returns `None` if an exponent overflows.
+fn combine(a: Dimension, b: Dimension, sign: i8) -> Option<Dimension> {
+    let mut dim = a;
+    for (d, e) in dim.iter_mut().zip(b.iter()) {
+        *d = d.checked_add(sign.checked_mul(*e)?)?;
+    }
+    Some(dim)
+}
+
+impl Quantity {
+    /// Create a dimensionless quantity from a number.
+    pub fn scalar(value: f64) -> Quantity {
+        Quantity {
+            value,
+            dim: DIMENSIONLESS,
+            unit: None,
+        }
+    }
+
+    /// Convert the quantity into a value, which is a plain float if the
+    /// quantity is dimensionless.
+    pub fn into_value(self) -> Value {
+        if self.dim == DIMENSIONLESS {
+            Value::Float(self.value)
+        } else {
+            Value::Quantity(self)
+        }
+    }
+
+    /// Add a quantity of the same dimension, keeping the unit of `self`.
+    ///
+    /// Returns `None` if the dimensions differ.
+    pub fn add(&self, rhs: &Quantity) -> Option<Quantity> {
+        if self.dim != rhs.dim {
+            return None;
+        }
+        Some(Quantity {
+            value: self.value + rhs.value,
+            dim: self.dim,
+            unit: self.unit.clone().or_else(|| rhs.unit.clone()),
+        })
+    }
+
+    /// Subtract a quantity of the same dimension, keeping the unit of `self`.
+    ///
+    /// Returns `None` if the dimensions differ.
+    pub fn sub(&self, rhs: &Quantity) -> Option<Quantity> {
+        let neg = Quantity {
+            value: -rhs.value,
+            ..rhs.clone()
+        };
+        self.add(&neg)
+    }
+
+    /// Multiply by another quantity.
+    ///
+    /// Returns `None` if an exponent of the dimension overflows.
+    pub fn mul(&self, rhs: &Quantity) -> Option<Quantity> {
+        let unit = match (&self.unit, &rhs.unit) {
+            (Some(a), Some(b)) => Some(Unit {
+                name: format!("{}*{}", a.name, b.name_within(&['/'])),
+                factor: a.factor * b.factor,
+            }),
+            (Some(u), None) if rhs.dim == DIMENSIONLESS => Some(u.clone()),
+            (None, Some(u)) if self.dim == DIMENSIONLESS => Some(u.clone()),
+            _ => None,
+        };
+        Some(Quantity {
+            value: self.value * rhs.value,
+            dim: combine(self.dim, rhs.dim, 1)?,
+            unit,
+        })
+    }
+
+    /// Divide by another quantity.
+    ///
+    /// Returns `None` if an exponent of the dimension overflows.
+    pub fn div(&self, rhs: &Quantity) -> Option<Quantity> {
+        let unit = match (&self.unit, &rhs.unit) {
+            (Some(a), Some(b)) => Some(Unit {
+                name: format!("{}/{}", a.name, b.name_within(&['*', '/'])),
+                factor: a.factor / b.factor,
+            }),
+            (Some(u), None) if rhs.dim == DIMENSIONLESS => Some(u.clone()),
+            (None, Some(u)) if self.dim == DIMENSIONLESS => Some(Unit {
+                name: format!("1/{}", u.name_within(&['*', '/'])),
+                factor: u.factor.recip(),
+            }),
+            _ => None,
+        };
+        Some(Quantity {
+            value: self.value / rhs.value,
+            dim: combine(self.dim, rhs.dim, -1)?,
+            unit,
+        })
+    }
+
+    /// Raise the quantity to a rational power.
+    ///
+    /// Returns `None` if the resulting dimension would have non-integer
+    /// exponents, such as the square root of a length, or exponents that
+    /// overflow.
+    pub fn pow(&self, exp: Ratio<i64>) -> Option<Quantity> {
+        let mut dim = DIMENSIONLESS;
+        for (d, e) in dim.iter_mut().zip(self.dim.iter()) {
+            let x = exp.checked_mul(&Ratio::from_integer(*e as i64))?;
+            if !x.is_integer() || x.numer().abs() > i8::MAX as i64 {
+                return None;
+            }
+            *d = *x.numer() as i8;
+        }
+        let power = *exp.numer() as f64 / *exp.denom() as f64;
+        let unit = match &self.unit {
+            Some(u) if exp.is_integer() => Some(Unit {
+                name: format!("{}^{}", u.name_within(&['*', '/', '^']), exp.numer()),
+                factor: u.factor.powf(power),
+            }),
+            _ => None,
+        };
+        Some(Quantity {
+            value: self.value.powf(power),
+            dim,
+            unit,
+        })
+    }
+
+    /// Express the quantity in the unit of `target`, which must have the
+    /// same dimension.
+    ///
+    /// Returns `None` if the dimensions differ.
+    pub fn convert(&self, target: &Quantity) -> Option<Quantity> {
+        if self.dim != target.dim {
+            return None;
+        }
+        Some(Quantity {
+            value: self.value,
+            dim: self.dim,
+            unit: target.unit.clone(),
+        })
+    }
+
+    /// Get the magnitude in the display unit, and the display unit's name.
+    pub fn display_parts(&self) -> (f64, String) {
+        match &self.unit {
+            Some(u) => (self.value / u.factor, u.name.clone()),
+            None => (self.value, base_unit_name(self.dim)),
+        }
+    }
+}
+
+/// Write a base unit with an exponent, such as `m^2`.
+fn write_base(f: &mut String, unit: &str, exp: i8) {
+    if !f.is_empty() {
+        f.push('*');
+    }
+    f.push_str(unit);
+    if exp != 1 {
+        f.push_str(&format!("^{}", exp));
+    }
+}
+
+/// Name the combination of SI units for a dimension, such as `m/s^2`.
+pub fn base_unit_name(dim: Dimension) -> String {
+    if let Some((name, _)) = DERIVED_UNITS.iter().find(|(_, d)| *d == dim) {
+        return name.to_string();
+    }
+    let mut numer = String::new();
+    let mut denom = String::new();
+    let mut n_denom = 0;
+    for (unit, &exp) in BASE_UNITS.iter().zip(dim.iter()) {
+        if exp > 0 {
+            write_base(&mut numer, unit, exp);
+        } else if exp < 0 {
+            write_base(&mut denom, unit, -exp);
+            n_denom += 1;
+        }
+    }
+    if denom.is_empty() {
+        numer
+    } else if numer.is_empty() {
+        // Write negative exponents when there is no numerator
+        let mut s = String::new();
+        for (unit, &exp) in BASE_UNITS.iter().zip(dim.iter()) {
+            if exp < 0 {
+                write_base(&mut s, unit, exp);
+            }
+        }
+        s
+    } else if n_denom > 1 {
+        format!("{}/({})", numer, denom)
+    } else {
+        format!("{}/{}", numer, denom)
+    }
+}
@@ -0,0 +1,80 @@
+use num::rational::Ratio;
+
+use crate::{base_unit_name, parse_unit, Quantity, DIMENSIONLESS};
+
+fn unit(name: &str) -> Quantity {
+    parse_unit(name).expect("unknown unit")
+}
+
+#[test]
+fn parse() {
+    assert_eq!(unit("m").value, 1.0);
+    assert_eq!(unit("km").value, 1000.0);
+    assert_eq!(unit("µs").value, 1e-6);
+    assert_eq!(unit("us").value, 1e-6);
+    assert_eq!(unit("kg").value, 1.0);
+    assert_eq!(unit("mph").value, 0.44704);
+    assert_eq!(unit("min").value, 60.0);
+    assert_eq!(unit("mm").unit.unwrap().name, "mm");
+    assert_eq!(unit("cm").value, 0.01);
+    assert_eq!(unit("dam").value, 10.0);
+    assert_eq!(unit("hPa").value, 100.0);
+    assert_eq!(parse_unit("kft"), None);
+    assert_eq!(parse_unit("foo"), None);
+    assert_eq!(parse_unit(""), None);
+}
+
+#[test]
+fn add() {
+    let sum = unit("km").add(&unit("m")).unwrap();
+    assert_eq!(sum.value, 1001.0);
+    assert_eq!(sum.unit.unwrap().name, "km");
+    assert_eq!(unit("m").add(&unit("s")), None);
+    assert_eq!(unit("m").sub(&Quantity::scalar(1.0)), None);
+}
+
+#[test]
+fn mul_div() {
+    let speed = unit("km").div(&unit("h")).unwrap();
+    assert_eq!(speed.dim, unit("mph").dim);
+    assert_eq!(speed.unit.as_ref().unwrap().name, "km/h");
+    assert_eq!(speed.display_parts(), (1.0, "km/h".to_string()));
+    let area = unit("m").mul(&unit("ft")).unwrap();
+    assert_eq!(area.unit.unwrap().name, "m*ft");
+    let ratio = unit("km").div(&unit("m")).unwrap();
+    assert_eq!(ratio.dim, DIMENSIONLESS);
+    assert_eq!(ratio.value, 1000.0);
+    let freq = Quantity::scalar(1.0).div(&unit("ms")).unwrap();
+    assert_eq!(freq.display_parts(), (1.0, "1/ms".to_string()));
+}
+
+#[test]
+fn pow() {
+    let area = unit("km").pow(Ratio::from_integer(2)).unwrap();
+    assert_eq!(area.value, 1e6);
+    assert_eq!(area.unit.as_ref().unwrap().name, "km^2");
+    let side = area.pow(Ratio::new(1, 2)).unwrap();
+    assert_eq!(side.dim, unit("m").dim);
+    assert_eq!(unit("m").pow(Ratio::new(1, 2)), None);
+}
+
+#[test]
+fn convert() {
+    let q = unit("mi").convert(&unit("km")).unwrap();
+    assert_eq!(q.display_parts(), (1.609344, "km".to_string()));
+    assert_eq!(unit("mi").convert(&unit("kg")), None);
+}
+
+#[test]
+fn base_names() {
+    let mut dim = DIMENSIONLESS;
+    dim[0] = 1;
+    dim[2] = -2;
+    assert_eq!(base_unit_name(dim), "m/s^2");
+    dim[1] = 1;
+    assert_eq!(base_unit_name(dim), "N");
+    dim[3] = 1;
+    assert_eq!(base_unit_name(dim), "m*kg*A/s^2");
+    assert_eq!(base_unit_name([0, 0, -1, 0, 0, 0, 0]), "s^-1");
+    assert_eq!(base_unit_name([2, 0, -1, -1, 0, 0, 0]), "m^2/(s*A)");
+}
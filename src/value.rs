@@ -1,8 +1,11 @@
-use num::rational::Ratio;
+use num::bigint::BigInt;
+use num::pow::pow;
+use num::rational::{BigRational, Ratio};
+use num::traits::ToPrimitive;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{Expression, Span};
+use crate::{Expression, Function, Matrix, Quantity, Span, Symbolic};
 
 /// A value that an expression can return, such as a float.
 #[derive(Clone, Debug, PartialEq)]
@@ -10,10 +13,62 @@ use crate::{Expression, Span};
 pub enum Value {
     /// A rational number.
     Ratio(Ratio<i64>),
+    /// A rational number that is too large for `Ratio`.
+    BigRatio(BigRational),
     /// A floating-point number.
     Float(f64),
+    /// An exact multiple of a power of a constant, such as pi.
+    Symbolic(Symbolic),
+    /// A physical quantity with a unit.
+    Quantity(Quantity),
+    /// The prime factorisation of a non-zero integer, as primes and their
+    /// exponents. A negative integer has a leading factor of -1.
+    Factors(Vec<(i64, u32)>),
+    /// A list of values.
+    List(Vec<Value>),
+    /// A matrix of values.
+    Matrix(Matrix),
     /// A function.
     Func(Vec<String>, Box<(Expression, Span)>),
+    /// A built-in function, such as `sin`.
+    Builtin(Function),
+}
+
+impl Value {
+    /// Convert the value to a float, if it is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Ratio(r) => Some(*r.numer() as f64 / *r.denom() as f64),
+            Value::BigRatio(r) => Some(big_to_f64(r)),
+            Value::Float(f) => Some(*f),
+            Value::Symbolic(s) => Some(s.to_f64()),
+            Value::Factors(_) => self.clone().expand().as_f64(),
+            _ => None,
+        }
+    }
+
+    /// Convert the value to a big rational, if it is a rational number.
+    pub fn to_big(&self) -> Option<BigRational> {
+        match self {
+            Value::Ratio(r) => Some(BigRational::new((*r.numer()).into(), (*r.denom()).into())),
+            Value::BigRatio(r) => Some(r.clone()),
+            _ => None,
+        }
+    }
+
+    /// Multiply out a factorisation into the integer that it represents.
+    /// Other values are returned unchanged.
+    pub fn expand(self) -> Value {
+        match self {
+            // The product may only fit in a big integer, such as 2^63
+            Value::Factors(factors) => factors
+                .iter()
+                .map(|&(p, e)| pow(BigInt::from(p), e as usize))
+                .product::<BigInt>()
+                .into(),
+            val => val,
+        }
+    }
 }
 
 impl From<i64> for Value {
@@ -34,8 +89,54 @@ impl From<(i64, i64)> for Value {
     }
 }
 
+/// Convert a big rational to a float, even if its numerator and
+/// denominator are too large to be floats themselves.
+fn big_to_f64(r: &BigRational) -> f64 {
+    // Keep the 64 most significant bits of each part, and scale afterward
+    let numer_shift = r.numer().bits().saturating_sub(64);
+    let denom_shift = r.denom().bits().saturating_sub(64);
+    let numer = (r.numer() >> numer_shift).to_f64().unwrap_or(f64::NAN);
+    let denom = (r.denom() >> denom_shift).to_f64().unwrap_or(f64::NAN);
+    let exp = (numer_shift as i64 - denom_shift as i64).clamp(-2000, 2000);
+    numer / denom * 2_f64.powi(exp as i32)
+}
+
+impl From<BigRational> for Value {
+    /// Convert a big rational to a value, which is a `Ratio` if it fits.
+    fn from(val: BigRational) -> Value {
+        match (val.numer().to_i64(), val.denom().to_i64()) {
+            (Some(n), Some(d)) => Value::Ratio(Ratio::new_raw(n, d)),
+            _ => Value::BigRatio(val),
+        }
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(val: BigInt) -> Value {
+        BigRational::from_integer(val).into()
+    }
+}
+
+impl From<Symbolic> for Value {
+    fn from(val: Symbolic) -> Value {
+        Value::Symbolic(val)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(val: Vec<Value>) -> Value {
+        Value::List(val)
+    }
+}
+
 impl From<f64> for Value {
     fn from(val: f64) -> Value {
         Value::Float(val)
     }
 }
+
+impl From<Function> for Value {
+    fn from(val: Function) -> Value {
+        Value::Builtin(val)
+    }
+}